@@ -0,0 +1,122 @@
+use crate::agents::validate::{make_targets, npm_scripts};
+use std::path::Path;
+
+#[derive(Debug, Clone)]
+pub struct ProjectContext {
+    pub name: String,
+    pub project_type: String,
+    pub build_cmd: Option<String>,
+    pub test_cmd: Option<String>,
+    pub lint_cmd: Option<String>,
+    pub is_workspace: bool,
+    pub workspace_members: Vec<String>,
+}
+
+impl ProjectContext {
+    /// Guess the project type and its build/test/lint commands from the
+    /// manifests at `root`
+    pub fn detect(root: &Path) -> Self {
+        let name = root
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut context = Self {
+            name,
+            project_type: "unknown".to_string(),
+            build_cmd: None,
+            test_cmd: None,
+            lint_cmd: None,
+            is_workspace: false,
+            workspace_members: Vec::new(),
+        };
+
+        let commands = |build: &str, test: &str, lint: &str| {
+            (
+                Some(build.to_string()),
+                Some(test.to_string()),
+                Some(lint.to_string()),
+            )
+        };
+
+        let (build_cmd, test_cmd, lint_cmd) = if root.join("Cargo.toml").is_file() {
+            context.project_type = "rust".to_string();
+            context.read_cargo_manifest(&root.join("Cargo.toml"));
+            commands("cargo build", "cargo test", "cargo clippy -- -D warnings")
+        } else if root.join("package.json").is_file() {
+            context.project_type = "node".to_string();
+            // Only the scripts package.json actually defines
+            let scripts = std::fs::read_to_string(root.join("package.json"))
+                .ok()
+                .and_then(|package| npm_scripts(&package))
+                .unwrap_or_default();
+            let script =
+                |name: &str, command: &str| scripts.contains(name).then(|| command.to_string());
+            (
+                script("build", "npm run build"),
+                script("test", "npm test"),
+                script("lint", "npm run lint"),
+            )
+        } else if root.join("go.mod").is_file() {
+            context.project_type = "go".to_string();
+            commands("go build ./...", "go test ./...", "go vet ./...")
+        } else if root.join("pyproject.toml").is_file() || root.join("setup.py").is_file() {
+            context.project_type = "python".to_string();
+            (None, Some("pytest".to_string()), None)
+        } else {
+            (None, None, None)
+        };
+        context.build_cmd = build_cmd;
+        context.test_cmd = test_cmd;
+        context.lint_cmd = lint_cmd;
+
+        // A Makefile usually wraps the toolchain with the project's own
+        // flags, for the targets it has
+        if let Ok(makefile) = std::fs::read_to_string(root.join("Makefile")) {
+            if context.project_type == "unknown" {
+                context.project_type = "make".to_string();
+            }
+            let targets = make_targets(&makefile);
+            if !targets.is_empty() {
+                context.build_cmd = Some("make".to_string());
+            }
+            if targets.contains("test") {
+                context.test_cmd = Some("make test".to_string());
+            }
+            if targets.contains("lint") {
+                context.lint_cmd = Some("make lint".to_string());
+            }
+        }
+
+        context
+    }
+
+    fn read_cargo_manifest(&mut self, path: &Path) {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return;
+        };
+        let Ok(manifest) = content.parse::<toml::Table>() else {
+            return;
+        };
+
+        if let Some(name) = manifest
+            .get("package")
+            .and_then(|package| package.get("name"))
+            .and_then(|name| name.as_str())
+        {
+            self.name = name.to_string();
+        }
+        if let Some(workspace) = manifest.get("workspace") {
+            self.is_workspace = true;
+            self.workspace_members = workspace
+                .get("members")
+                .and_then(|members| members.as_array())
+                .map(|members| {
+                    members
+                        .iter()
+                        .filter_map(|member| member.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default();
+        }
+    }
+}
@@ -1,36 +1,263 @@
-use crate::error::Result;
-use std::path::Path;
+use crate::agents::managed::{self, ManagedFile, Outcome};
+use crate::agents::manifest::{EntryKind, Manifest, ManifestEntry, Scope};
+use crate::agents::PromptLibrary;
+use crate::error::{KayfabeError, Result};
+use crate::ui::Output;
+use console::style;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
-pub struct AgentInstaller;
+/// Agents `kayfabe install` knows how to set up
+pub const AGENTS: &[&str] = &["claude", "windsurf"];
 
-impl AgentInstaller {
-    pub fn install_global(agent: &str, target_dir: &Path) -> Result<()> {
+/// Writes an agent's kayfabe rules, and for Claude Code the bundled
+/// commands and subagents, into one scope. Every file written is recorded
+/// in the scope's manifest.
+pub struct AgentInstaller<'a> {
+    scope: Scope,
+    /// Home directory for user scope, else the checkout
+    root: PathBuf,
+    vars: &'a BTreeMap<String, String>,
+    dry_run: bool,
+}
+
+impl<'a> AgentInstaller<'a> {
+    pub fn new(
+        scope: Scope,
+        root: PathBuf,
+        vars: &'a BTreeMap<String, String>,
+        dry_run: bool,
+    ) -> Self {
+        Self {
+            scope,
+            root,
+            vars,
+            dry_run,
+        }
+    }
+
+    /// Rule file for `agent` in `scope`, relative to the scope's root
+    pub fn rules_location(agent: &str, scope: Scope) -> Option<&'static str> {
+        match (agent, scope) {
+            ("claude", Scope::User) => Some(".claude/CLAUDE.md"),
+            ("claude", _) => Some("CLAUDE.md"),
+            ("windsurf", Scope::User) => Some(".codeium/windsurf/memories/global_rules.md"),
+            ("windsurf", _) => Some(".windsurfrules"),
+            _ => None,
+        }
+    }
+
+    /// The rules block kayfabe installs for `agent`
+    pub fn rules(agent: &str) -> Option<String> {
         match agent {
-            "windsurf" => Self::install_windsurf_global(target_dir),
-            _ => Err(crate::error::KayfabeError::Other(format!(
-                "Unknown agent: {}",
-                agent
-            ))),
+            "claude" => Some(Self::generate_rules("Claude Code", "<ide>")),
+            "windsurf" => Some(Self::generate_rules("Windsurf", "windsurf")),
+            _ => None,
         }
     }
 
-    fn install_windsurf_global(target_dir: &Path) -> Result<()> {
-        let local_path = target_dir.join(".windsurfrules");
-        let content = Self::generate_windsurf_config()?;
-        std::fs::write(&local_path, &content)?;
+    /// What kayfabe would write today for a recorded file: the rules block
+    /// body, or a rendered prompt. `None` for files it no longer provides,
+    /// and for kayfabe's own files.
+    pub fn expected(
+        entry: &ManifestEntry,
+        library: &PromptLibrary,
+        vars: &BTreeMap<String, String>,
+    ) -> Result<Option<String>> {
+        match entry.kind {
+            EntryKind::Block => Ok(Self::rules(&entry.agent)),
+            EntryKind::File if entry.agent == "claude" => {
+                let Some(name) = entry.path.file_stem() else {
+                    return Ok(None);
+                };
+                match library.get(&name.to_string_lossy()) {
+                    Ok(prompt) => prompt.render(vars).map(Some),
+                    Err(_) => Ok(None),
+                }
+            }
+            EntryKind::File => Ok(None),
+        }
+    }
 
-        println!("✓ Windsurf agent installed locally");
+    /// The manifest hash of `content` written as `kind`
+    pub fn content_hash(kind: EntryKind, content: &str) -> String {
+        match kind {
+            EntryKind::Block => managed::stamp(content),
+            EntryKind::File => managed::hash(content),
+        }
+    }
+
+    /// Install an agent. With `dry_run`, print the changes as diffs instead.
+    pub fn install(&self, agent: &str, manifest: &mut Manifest) -> Result<()> {
+        let location = Self::rules_location(agent, self.scope).ok_or_else(|| {
+            KayfabeError::Other(format!(
+                "Unknown agent: {} (expected one of: {})",
+                agent,
+                AGENTS.join(", ")
+            ))
+        })?;
+        let agent_name = if agent == "claude" {
+            "Claude Code"
+        } else {
+            "Windsurf"
+        };
+        let content = Self::rules(agent).unwrap_or_default();
+
+        let file = ManagedFile::new(self.root.join(location));
+        self.install_managed(agent, &file, &content, manifest)?;
+        if agent == "claude" {
+            self.install_prompts(agent, manifest)?;
+        }
+
+        if !self.dry_run {
+            println!(
+                "{} {} agent installed ({} scope)",
+                style("✓").green(),
+                agent_name,
+                self.scope
+            );
+        }
         Ok(())
     }
 
-    fn generate_windsurf_config() -> Result<String> {
-        Ok(r#"# Kayfabe Windsurf Rules
+    /// Update only the kayfabe block of `file`, leaving the rest alone
+    fn install_managed(
+        &self,
+        agent: &str,
+        file: &ManagedFile,
+        content: &str,
+        manifest: &mut Manifest,
+    ) -> Result<()> {
+        let merge = file.plan(content)?;
+        let path = file.path().display().to_string();
+
+        if self.dry_run {
+            if merge.outcome == Outcome::Unchanged {
+                println!("{} {} is up to date", style("✓").green(), path);
+            } else {
+                let existing = std::fs::read_to_string(file.path()).unwrap_or_default();
+                println!("{} would be {}:", style(&path).cyan(), merge.outcome);
+                Output::diff(&existing, &merge.content, &path, &path);
+            }
+            return Ok(());
+        }
+
+        let backup = file.apply(content, &merge)?;
+        manifest.record(self.entry(
+            agent,
+            file.path(),
+            EntryKind::Block,
+            managed::stamp(content),
+        ));
+        println!(
+            "  {} {} {}",
+            style("✓").green(),
+            path,
+            style(format!("({})", merge.outcome)).dim()
+        );
+        if let Some(backup) = backup {
+            println!(
+                "    {}",
+                style(format!("previous version kept as {}", backup.display())).dim()
+            );
+        }
+        if merge.outcome == Outcome::Conflict {
+            Output::warning(&format!(
+                "Resolve the <<<<<<< / >>>>>>> conflict markers in {}",
+                path
+            ));
+        }
+        Ok(())
+    }
+
+    /// Install the bundled commands and subagents as whole files under
+    /// `.claude/`. Files edited since kayfabe wrote them are left alone.
+    fn install_prompts(&self, agent: &str, manifest: &mut Manifest) -> Result<()> {
+        let dir = self.root.join(".claude");
+        let (mut unchanged, mut kept) = (0, 0);
+        for prompt in PromptLibrary::load()?.list()? {
+            let path = dir
+                .join(prompt.kind.install_dir())
+                .join(format!("{}.md", prompt.name));
+            let content = prompt.render(self.vars)?;
+            let existing = std::fs::read_to_string(&path).ok();
+
+            let outcome = match &existing {
+                None => Outcome::Created,
+                Some(existing) if *existing == content => Outcome::Unchanged,
+                Some(existing) => match manifest.get(&path) {
+                    Some(entry) if entry.hash == managed::hash(existing) => Outcome::Updated,
+                    _ => Outcome::Kept,
+                },
+            };
+
+            match outcome {
+                Outcome::Unchanged => unchanged += 1,
+                Outcome::Kept => kept += 1,
+                _ if self.dry_run => {
+                    println!("{} would be {}", style(path.display()).cyan(), outcome);
+                    if let Some(existing) = &existing {
+                        let label = path.display().to_string();
+                        Output::diff(existing, &content, &label, &label);
+                    }
+                }
+                _ => {
+                    if let Some(parent) = path.parent() {
+                        std::fs::create_dir_all(parent)?;
+                    }
+                    std::fs::write(&path, &content)?;
+                    println!(
+                        "  {} {} {}",
+                        style("✓").green(),
+                        path.display(),
+                        style(format!("({})", outcome)).dim()
+                    );
+                }
+            }
+            if !self.dry_run && outcome != Outcome::Kept {
+                manifest.record(self.entry(agent, &path, EntryKind::File, managed::hash(&content)));
+            }
+        }
+
+        if unchanged > 0 {
+            println!(
+                "  {} {}",
+                style("✓").green(),
+                style(format!(
+                    "{} prompts in {} up to date",
+                    unchanged,
+                    dir.display()
+                ))
+                .dim()
+            );
+        }
+        if kept > 0 {
+            Output::warning(&format!(
+                "Left {} prompts in {} alone because they were changed since kayfabe installed them",
+                kept,
+                dir.display()
+            ));
+        }
+        Ok(())
+    }
+
+    fn entry(&self, agent: &str, path: &Path, kind: EntryKind, hash: String) -> ManifestEntry {
+        ManifestEntry::new(path.to_path_buf(), agent, self.scope, kind, hash)
+    }
+
+    /// The rules block, with `ide` as the `--open` example
+    fn generate_rules(agent_name: &str, ide: &str) -> String {
+        RULES.replace("{agent}", agent_name).replace("{ide}", ide)
+    }
+}
+
+const RULES: &str = r#"# Kayfabe {agent} Rules
 
 This project uses kayfabe for AI-assisted development with git worktree management.
 
 ## Kayfabe Commands
-- `kayfabe worktree create <name> [--open windsurf]` - Create isolated development environment
-- `kayfabe worktree list [--stale]` - List all worktrees and their status  
+- `kayfabe worktree create <name> [--open {ide}]` - Create isolated development environment
+- `kayfabe worktree list [--stale <days>]` - List all worktrees and their status  
 - `kayfabe worktree remove <name>` - Remove completed worktree
 - `kayfabe worktree cleanup` - Clean up stale worktrees
 - `kayfabe status` - Show repository and worktree status
@@ -44,7 +271,7 @@ This project uses kayfabe for AI-assisted development with git worktree manageme
 
 ## Best Practices
 - Create descriptive worktree names (e.g., `feature-auth`, `fix-memory-leak`)
-- Use `--open windsurf` to launch Windsurf in the new worktree
+- Use `--open {ide}` to open the new worktree in your editor
 - Run `kayfabe status` to understand current repository state
 - Clean up merged worktrees regularly with `kayfabe worktree cleanup`
 - Work from any directory - kayfabe auto-detects the repository root
@@ -55,7 +282,4 @@ This project uses kayfabe for AI-assisted development with git worktree manageme
 - Keep commits atomic and well-described
 - Ensure all tests pass before merging
 - Document complex logic and public APIs
-"#
-        .to_string())
-    }
-}
+"#;
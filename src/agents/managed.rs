@@ -0,0 +1,672 @@
+use crate::error::{KayfabeError, Result};
+use sha2::{Digest, Sha256};
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+const BEGIN: &str = "<!-- kayfabe:begin";
+const END: &str = "<!-- kayfabe:end -->";
+
+/// Hashes of whole files kayfabe wrote before it used markers: the
+/// `.windsurfrules` from `kayfabe install windsurf`. These are replaced by
+/// the block rather than kept as the user's own content.
+const LEGACY: &[&str] = &["b82b0749d7d82364"];
+
+/// What installing did, or would do, to a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The file didn't exist
+    Created,
+    /// The block was added after the file's own content
+    Appended,
+    /// The block was replaced; nobody had edited it
+    Updated,
+    Unchanged,
+    /// Edits inside the block were merged with the new content
+    Merged,
+    /// Edits inside the block clash with the new content; conflict markers
+    /// were written
+    Conflict,
+    /// A whole file kayfabe installed was changed since, so it was left alone
+    Kept,
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Created => write!(f, "created"),
+            Outcome::Appended => write!(f, "added kayfabe block"),
+            Outcome::Updated => write!(f, "updated"),
+            Outcome::Unchanged => write!(f, "up to date"),
+            Outcome::Merged => write!(f, "merged with your edits"),
+            Outcome::Conflict => write!(f, "conflicts with your edits"),
+            Outcome::Kept => write!(f, "kept your version"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Merge {
+    pub content: String,
+    pub outcome: Outcome,
+    /// The block had been edited by hand, so the old file should be kept
+    pub backup: bool,
+}
+
+/// A file whose kayfabe content lives between `<!-- kayfabe:begin -->` and
+/// `<!-- kayfabe:end -->`, leaving the rest of it to the user. The begin
+/// marker carries a hash and the version of what kayfabe wrote, so hand
+/// edits inside the block can be told apart from an outdated block.
+pub struct ManagedFile {
+    path: PathBuf,
+}
+
+impl ManagedFile {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Work out the new file content for `body` without writing anything
+    pub fn plan(&self, body: &str) -> Result<Merge> {
+        let existing = std::fs::read_to_string(&self.path).ok();
+        let base = self
+            .base_path()
+            .and_then(|path| std::fs::read_to_string(path).ok());
+        merge(existing.as_deref(), body, base.as_deref())
+    }
+
+    /// Write a planned merge, keeping the previous file as `.kayfabe-bak`
+    /// (or `.kayfabe-bak.1`, `.2`, ... if that is taken) if its block had
+    /// been edited, and remember `body` as the base for the next merge.
+    /// Returns where the previous file was kept.
+    pub fn apply(&self, body: &str, merge: &Merge) -> Result<Option<PathBuf>> {
+        let backup = if merge.backup {
+            let backup = self.backup_path();
+            std::fs::copy(&self.path, &backup)?;
+            Some(backup)
+        } else {
+            None
+        };
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        if merge.outcome != Outcome::Unchanged {
+            std::fs::write(&self.path, &merge.content)?;
+        }
+
+        if let Some(base) = self.base_path() {
+            if let Some(parent) = base.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(base, normalize(body))?;
+        }
+        Ok(backup)
+    }
+
+    /// The first `.kayfabe-bak` name not used by an earlier backup
+    fn backup_path(&self) -> PathBuf {
+        (0..)
+            .map(|n| {
+                let mut backup = self.path.as_os_str().to_owned();
+                backup.push(".kayfabe-bak");
+                if n > 0 {
+                    backup.push(format!(".{}", n));
+                }
+                PathBuf::from(backup)
+            })
+            .find(|backup| !backup.exists())
+            .unwrap()
+    }
+
+    /// The file without its kayfabe block, and whether the block had been
+    /// edited by hand. `None` if the file has no block.
+    pub fn without_block(&self) -> Result<Option<(String, bool)>> {
+        without_block(&std::fs::read_to_string(&self.path)?)
+    }
+
+    /// Drop the remembered base once the block is gone
+    pub fn forget(&self) -> Result<()> {
+        if let Some(base) = self.base_path() {
+            if base.exists() {
+                std::fs::remove_file(base)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Where the last body written to this file is kept, keyed by the
+    /// file's absolute path
+    fn base_path(&self) -> Option<PathBuf> {
+        let absolute = if self.path.is_absolute() {
+            self.path.clone()
+        } else {
+            std::env::current_dir().ok()?.join(&self.path)
+        };
+        let key = hash(&absolute.to_string_lossy());
+        dirs::data_dir().map(|dir| {
+            dir.join("kayfabe")
+                .join("managed")
+                .join(format!("{}.md", key))
+        })
+    }
+}
+
+/// New content for a file given its current content, the block to install,
+/// and the block as kayfabe last wrote it, if known
+pub fn merge(existing: Option<&str>, body: &str, base: Option<&str>) -> Result<Merge> {
+    let body = normalize(body);
+    let Some(existing) = existing else {
+        return Ok(Merge {
+            content: block(&body),
+            outcome: Outcome::Created,
+            backup: false,
+        });
+    };
+
+    let Some(Split {
+        before,
+        body: current,
+        stamp,
+        after,
+        ..
+    }) = split(existing)?
+    else {
+        // A file kayfabe wrote whole, before it used markers
+        if normalize(existing) == body || is_legacy(existing) {
+            return Ok(Merge {
+                content: block(&body),
+                outcome: Outcome::Updated,
+                backup: false,
+            });
+        }
+        let mut content = existing.to_string();
+        if !content.is_empty() {
+            if !content.ends_with('\n') {
+                content.push('\n');
+            }
+            content.push('\n');
+        }
+        content.push_str(&block(&body));
+        return Ok(Merge {
+            content,
+            outcome: Outcome::Appended,
+            backup: false,
+        });
+    };
+
+    let edited = stamp.as_deref() != Some(hash(&current).as_str());
+    let (new_body, outcome) = if current == body {
+        (body.clone(), Outcome::Unchanged)
+    } else if !edited {
+        (body.clone(), Outcome::Updated)
+    } else {
+        // Only trust a base that matches the stamp of the block on disk
+        let base = base
+            .map(normalize)
+            .filter(|base| stamp.as_deref() == Some(hash(base).as_str()));
+        let (merged, clean) = merge3(base.as_deref().unwrap_or(""), &current, &body);
+        if clean {
+            (merged, Outcome::Merged)
+        } else {
+            (merged, Outcome::Conflict)
+        }
+    };
+
+    // Re-stamped with what kayfabe wrote, so the user's surviving edits are
+    // seen as edits again next time
+    let mut content = before.to_string();
+    content.push_str(&stamped_block(&new_body, &hash(&body)));
+    content.push_str(after);
+    // Hand edits and nothing new from kayfabe
+    let outcome = if content == existing {
+        Outcome::Unchanged
+    } else {
+        outcome
+    };
+    Ok(Merge {
+        content,
+        outcome,
+        backup: edited && outcome != Outcome::Unchanged,
+    })
+}
+
+/// Whether `content` is a whole file written by a kayfabe that predates
+/// the markers, and not edited since
+pub fn is_legacy(content: &str) -> bool {
+    LEGACY.contains(&hash(&normalize(content)).as_str())
+}
+
+/// `content` without its kayfabe block, and whether the block had been
+/// edited by hand. `None` if it has no block.
+pub fn without_block(content: &str) -> Result<Option<(String, bool)>> {
+    let Some(Split {
+        before,
+        body,
+        stamp,
+        after,
+        ..
+    }) = split(content)?
+    else {
+        return Ok(None);
+    };
+
+    let edited = stamp.as_deref() != Some(hash(&body).as_str());
+    // Drop the blank line left between the user's content and the block
+    let mut rest = before.trim_end_matches('\n').to_string();
+    if !rest.is_empty() {
+        rest.push('\n');
+    }
+    if !after.is_empty() {
+        if !rest.is_empty() {
+            rest.push('\n');
+        }
+        rest.push_str(after.trim_start_matches('\n'));
+    }
+    Ok(Some((rest, edited)))
+}
+
+/// `generated` followed by the kayfabe block from `existing`, if it has one,
+/// so a tool that rewrites the whole file keeps what `kayfabe install` added
+pub fn keep_block(existing: Option<&str>, generated: &str) -> Result<String> {
+    let Some(Split { block, .. }) = existing.map(split).transpose()?.flatten() else {
+        return Ok(generated.to_string());
+    };
+    let mut content = generated.trim_end_matches('\n').to_string();
+    if !content.is_empty() {
+        content.push_str("\n\n");
+    }
+    content.push_str(block);
+    content.push('\n');
+    Ok(content)
+}
+
+/// A file split around its kayfabe block
+struct Split<'a> {
+    before: &'a str,
+    body: String,
+    /// Hash in the begin marker
+    stamp: Option<String>,
+    after: &'a str,
+    /// The block itself, from the begin marker to the end marker
+    block: &'a str,
+}
+
+fn split(content: &str) -> Result<Option<Split<'_>>> {
+    let Some(begin) = content.find(BEGIN) else {
+        return Ok(None);
+    };
+    let marker_end = content[begin..]
+        .find("-->")
+        .map(|i| begin + i + 3)
+        .ok_or_else(|| KayfabeError::Other("Unterminated kayfabe:begin marker".to_string()))?;
+    let stamp = content[begin + BEGIN.len()..marker_end - 3]
+        .split_whitespace()
+        .find_map(|attr| attr.strip_prefix("sha256="))
+        .map(str::to_string);
+
+    let end = content[marker_end..]
+        .find(END)
+        .map(|i| marker_end + i)
+        .ok_or_else(|| {
+            KayfabeError::Other("kayfabe:begin marker without kayfabe:end".to_string())
+        })?;
+    if content[end + END.len()..].contains(BEGIN) {
+        return Err(KayfabeError::Other(
+            "More than one kayfabe block in the file".to_string(),
+        ));
+    }
+
+    let body = content[marker_end..end].trim_start_matches('\n');
+    let mut after = &content[end + END.len()..];
+    if let Some(rest) = after.strip_prefix('\n') {
+        after = rest;
+    }
+    Ok(Some(Split {
+        before: &content[..begin],
+        body: normalize(body),
+        stamp,
+        after,
+        block: &content[begin..end + END.len()],
+    }))
+}
+
+fn block(body: &str) -> String {
+    stamped_block(body, &hash(body))
+}
+
+/// The begin marker also names the kayfabe version that wrote the block,
+/// for people reading the file; only the hash is compared
+fn stamped_block(body: &str, stamp: &str) -> String {
+    format!(
+        "{} sha256={} version={} -->\n{}{}\n",
+        BEGIN,
+        stamp,
+        env!("CARGO_PKG_VERSION"),
+        body,
+        END
+    )
+}
+
+/// Trimmed, with a single trailing newline
+fn normalize(body: &str) -> String {
+    let body = body.trim_matches('\n');
+    if body.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", body)
+    }
+}
+
+/// The hash a block of `body` is stamped with
+pub fn stamp(body: &str) -> String {
+    hash(&normalize(body))
+}
+
+/// Short hex digest, enough to tell edits apart
+pub fn hash(content: &str) -> String {
+    let digest = Sha256::digest(content.as_bytes());
+    digest
+        .iter()
+        .take(8)
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Lines of `base` replaced in `other`, as (base range, replacement)
+fn changes<'a>(base: &[&str], other: &[&'a str]) -> Vec<(Range<usize>, Vec<&'a str>)> {
+    let mut changes: Vec<(Range<usize>, Vec<&'a str>)> = Vec::new();
+    for op in capture_diff_slices(Algorithm::Myers, base, other) {
+        let (old, new) = match op {
+            DiffOp::Equal { .. } => continue,
+            DiffOp::Delete {
+                old_index, old_len, ..
+            } => (old_index..old_index + old_len, 0..0),
+            DiffOp::Insert {
+                old_index,
+                new_index,
+                new_len,
+            } => (old_index..old_index, new_index..new_index + new_len),
+            DiffOp::Replace {
+                old_index,
+                old_len,
+                new_index,
+                new_len,
+            } => (
+                old_index..old_index + old_len,
+                new_index..new_index + new_len,
+            ),
+        };
+
+        match changes.last_mut() {
+            Some((range, lines)) if range.end == old.start => {
+                range.end = old.end;
+                lines.extend_from_slice(&other[new]);
+            }
+            _ => changes.push((old, other[new].to_vec())),
+        }
+    }
+    changes
+}
+
+/// Line-based three-way merge. Returns the merged text and whether it is
+/// free of conflicts; conflicting regions get git-style markers with the
+/// user's lines first.
+fn merge3(base: &str, ours: &str, theirs: &str) -> (String, bool) {
+    let base: Vec<&str> = base.lines().collect();
+    let ours: Vec<&str> = ours.lines().collect();
+    let theirs: Vec<&str> = theirs.lines().collect();
+    let our_changes = changes(&base, &ours);
+    let their_changes = changes(&base, &theirs);
+
+    let mut out: Vec<&str> = Vec::new();
+    let mut clean = true;
+    let mut pos = 0;
+    let (mut i, mut j) = (0, 0);
+
+    while i < our_changes.len() || j < their_changes.len() {
+        // Start a region at the earliest change, then pull in every change
+        // from either side that overlaps or touches it
+        let start = match (our_changes.get(i), their_changes.get(j)) {
+            (Some(ours), Some(theirs)) => ours.0.start.min(theirs.0.start),
+            (Some(ours), None) => ours.0.start,
+            (None, Some(theirs)) => theirs.0.start,
+            (None, None) => unreachable!(),
+        };
+        let mut end = start;
+        let (first_ours, first_theirs) = (i, j);
+        loop {
+            if let Some((range, _)) = our_changes.get(i).filter(|(r, _)| r.start <= end) {
+                end = end.max(range.end);
+                i += 1;
+            } else if let Some((range, _)) = their_changes.get(j).filter(|(r, _)| r.start <= end) {
+                end = end.max(range.end);
+                j += 1;
+            } else {
+                break;
+            }
+        }
+
+        out.extend_from_slice(&base[pos..start]);
+        let our_region = apply_changes(&base, start, end, &our_changes[first_ours..i]);
+        let their_region = apply_changes(&base, start, end, &their_changes[first_theirs..j]);
+
+        if first_ours == i {
+            out.extend(their_region);
+        } else if first_theirs == j || our_region == their_region {
+            out.extend(our_region);
+        } else {
+            clean = false;
+            out.push("<<<<<<< yours");
+            out.extend(our_region);
+            out.push("=======");
+            out.extend(their_region);
+            out.push(">>>>>>> kayfabe");
+        }
+        pos = end;
+    }
+    out.extend_from_slice(&base[pos..]);
+
+    (normalize(&out.join("\n")), clean)
+}
+
+/// `base[start..end]` with one side's changes applied
+fn apply_changes<'a>(
+    base: &[&'a str],
+    start: usize,
+    end: usize,
+    changes: &[(Range<usize>, Vec<&'a str>)],
+) -> Vec<&'a str> {
+    let mut lines = Vec::new();
+    let mut pos = start;
+    for (range, replacement) in changes {
+        lines.extend_from_slice(&base[pos..range.start]);
+        lines.extend_from_slice(replacement);
+        pos = range.end;
+    }
+    lines.extend_from_slice(&base[pos..end]);
+    lines
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// `.windsurfrules` as `kayfabe install windsurf` wrote it before markers
+    pub(crate) const LEGACY_WINDSURF: &str = r#"# Kayfabe Windsurf Rules
+
+This project uses kayfabe for AI-assisted development with git worktree management.
+
+## Kayfabe Commands
+- `kayfabe worktree create <name> [--open windsurf]` - Create isolated development environment
+- `kayfabe worktree list [--stale]` - List all worktrees and their status  
+- `kayfabe worktree remove <name>` - Remove completed worktree
+- `kayfabe worktree cleanup` - Clean up stale worktrees
+- `kayfabe status` - Show repository and worktree status
+
+## Development Workflow
+1. **Feature Development**: Create dedicated worktrees for each feature/task
+2. **Isolation**: Each worktree is completely isolated - no branch conflicts
+3. **Parallel Work**: Multiple agents can work on different features simultaneously
+4. **Clean Merging**: Merge completed work back to main branch
+5. **Cleanup**: Remove merged worktrees to keep workspace tidy
+
+## Best Practices
+- Create descriptive worktree names (e.g., `feature-auth`, `fix-memory-leak`)
+- Use `--open windsurf` to launch Windsurf in the new worktree
+- Run `kayfabe status` to understand current repository state
+- Clean up merged worktrees regularly with `kayfabe worktree cleanup`
+- Work from any directory - kayfabe auto-detects the repository root
+
+## Code Quality
+- Write comprehensive tests for new functionality
+- Follow existing project conventions and style
+- Keep commits atomic and well-described
+- Ensure all tests pass before merging
+- Document complex logic and public APIs
+"#;
+
+    #[test]
+    fn test_merge_outside_and_inside_block() {
+        let v1 = "# Rules\n- one\n- two\n- three\n";
+        let created = merge(None, v1, None).unwrap();
+        assert_eq!(created.outcome, Outcome::Created);
+
+        // The user's own content around the block survives an update
+        let file = format!("My rules\n\n{}\nMore of mine\n", created.content);
+        let v2 = "# Rules\n- one\n- two\n- three\n- four\n";
+        let updated = merge(Some(&file), v2, Some(v1)).unwrap();
+        assert_eq!(updated.outcome, Outcome::Updated);
+        assert!(!updated.backup);
+        assert!(updated
+            .content
+            .starts_with("My rules\n\n<!-- kayfabe:begin sha256="));
+        assert!(updated
+            .content
+            .ends_with("- four\n<!-- kayfabe:end -->\n\nMore of mine\n"));
+        assert_eq!(
+            merge(Some(&updated.content), v2, Some(v2)).unwrap().outcome,
+            Outcome::Unchanged
+        );
+
+        // An edit inside the block is kept while kayfabe's content is the same
+        let edited = updated.content.replace("- one\n", "- one (ours)\n");
+        let kept = merge(Some(&edited), v2, Some(v2)).unwrap();
+        assert_eq!(kept.outcome, Outcome::Unchanged);
+        assert!(!kept.backup);
+
+        // ... and merges with a change elsewhere in it
+        let v3 = "# Rules\n- one\n- two\n- three\n- four\n- five\n";
+        let merged = merge(Some(&edited), v3, Some(v2)).unwrap();
+        assert_eq!(merged.outcome, Outcome::Merged);
+        assert!(merged.backup);
+        assert!(merged
+            .content
+            .contains("- one (ours)\n- two\n- three\n- four\n- five\n<!-- kayfabe:end -->"));
+
+        // ... and conflicts with a change to the same line
+        let v4 = "# Rules\n- one (theirs)\n- two\n- three\n- four\n";
+        let conflict = merge(Some(&edited), v4, Some(v2)).unwrap();
+        assert_eq!(conflict.outcome, Outcome::Conflict);
+        assert!(conflict
+            .content
+            .contains("<<<<<<< yours\n- one (ours)\n=======\n- one (theirs)\n>>>>>>> kayfabe\n"));
+    }
+
+    #[test]
+    fn test_merge_appends_to_hand_written_file() {
+        let merged = merge(Some("# Project rules\n"), "kayfabe stuff\n", None).unwrap();
+        assert_eq!(merged.outcome, Outcome::Appended);
+        assert!(merged
+            .content
+            .starts_with("# Project rules\n\n<!-- kayfabe:begin"));
+
+        // A file kayfabe wrote whole before markers is taken over
+        let legacy = merge(Some("kayfabe stuff\n"), "kayfabe stuff\n", None).unwrap();
+        assert_eq!(legacy.outcome, Outcome::Updated);
+        assert!(legacy.content.starts_with("<!-- kayfabe:begin"));
+        // ... as is the template older versions wrote, even though it differs
+        assert!(is_legacy(LEGACY_WINDSURF));
+        let legacy = merge(Some(LEGACY_WINDSURF), "kayfabe stuff\n", None).unwrap();
+        assert_eq!(legacy.outcome, Outcome::Updated);
+        assert!(!legacy.content.contains("Kayfabe Windsurf Rules"));
+        assert!(!is_legacy(&LEGACY_WINDSURF.replace("tidy", "neat")));
+
+        assert!(merge(Some("<!-- kayfabe:begin -->\nno end\n"), "x", None).is_err());
+
+        // Removing the block gives back the hand-written file
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let file = ManagedFile::new(temp_dir.path().join("rules.md"));
+        std::fs::write(file.path(), &merged.content).unwrap();
+        assert_eq!(
+            file.without_block().unwrap(),
+            Some(("# Project rules\n".to_string(), false))
+        );
+        std::fs::write(file.path(), merged.content.replace("stuff", "things")).unwrap();
+        assert_eq!(
+            file.without_block().unwrap(),
+            Some(("# Project rules\n".to_string(), true))
+        );
+    }
+
+    #[test]
+    fn test_without_block_keeps_content_around_it() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let file = ManagedFile::new(temp_dir.path().join("CLAUDE.md"));
+        let block = block("kayfabe stuff\n");
+
+        std::fs::write(file.path(), format!("Before\n\n{}\nAfter\n", block)).unwrap();
+        assert_eq!(
+            file.without_block().unwrap(),
+            Some(("Before\n\nAfter\n".to_string(), false))
+        );
+
+        std::fs::write(file.path(), &block).unwrap();
+        assert_eq!(file.without_block().unwrap(), Some((String::new(), false)));
+
+        std::fs::write(file.path(), "No block here\n").unwrap();
+        assert_eq!(file.without_block().unwrap(), None);
+    }
+
+    #[test]
+    fn test_keep_block_when_rewriting_whole_file() {
+        let installed = merge(Some("# Generated\n"), "kayfabe stuff\n", None)
+            .unwrap()
+            .content;
+        let regenerated = keep_block(Some(&installed), "# Generated again\n").unwrap();
+        assert_eq!(
+            regenerated,
+            installed.replace("# Generated\n", "# Generated again\n")
+        );
+        assert_eq!(
+            without_block(&regenerated).unwrap(),
+            Some(("# Generated again\n".to_string(), false))
+        );
+        assert_eq!(keep_block(None, "# New\n").unwrap(), "# New\n");
+    }
+
+    #[test]
+    fn test_apply_numbers_backups() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let file = ManagedFile::new(temp_dir.path().join("CLAUDE.md"));
+        let backup = Merge {
+            content: "new\n".to_string(),
+            outcome: Outcome::Merged,
+            backup: true,
+        };
+
+        std::fs::write(file.path(), "first\n").unwrap();
+        let first = file.apply("x", &backup).unwrap().unwrap();
+        std::fs::write(file.path(), "second\n").unwrap();
+        let second = file.apply("x", &backup).unwrap().unwrap();
+
+        assert_eq!(first, temp_dir.path().join("CLAUDE.md.kayfabe-bak"));
+        assert_eq!(second, temp_dir.path().join("CLAUDE.md.kayfabe-bak.1"));
+        assert_eq!(std::fs::read_to_string(first).unwrap(), "first\n");
+        assert_eq!(std::fs::read_to_string(second).unwrap(), "second\n");
+        file.forget().unwrap();
+    }
+}
@@ -0,0 +1,198 @@
+use crate::error::{KayfabeError, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Where `kayfabe install` puts an agent's files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// The agent's per-user config in the home directory
+    User,
+    /// The main checkout, shared by every worktree once committed
+    Project,
+    /// Only the current checkout
+    Worktree,
+}
+
+impl Scope {
+    pub const NAMES: &'static [&'static str] = &["user", "project", "worktree"];
+
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "user" => Ok(Scope::User),
+            "project" => Ok(Scope::Project),
+            "worktree" => Ok(Scope::Worktree),
+            _ => Err(KayfabeError::Other(format!(
+                "Unknown scope: {} (expected one of: {})",
+                name,
+                Self::NAMES.join(", ")
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for Scope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Scope::User => write!(f, "user"),
+            Scope::Project => write!(f, "project"),
+            Scope::Worktree => write!(f, "worktree"),
+        }
+    }
+}
+
+/// How much of a recorded file kayfabe owns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryKind {
+    /// The whole file
+    File,
+    /// Only the `<!-- kayfabe:begin -->` block
+    Block,
+}
+
+/// Agent name recorded for kayfabe's own files, such as the project config
+pub const KAYFABE: &str = "kayfabe";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub path: PathBuf,
+    /// Agent the file was installed for, or `kayfabe`
+    pub agent: String,
+    pub scope: Scope,
+    pub kind: EntryKind,
+    /// `managed::hash` of the whole file, or of the block body, as written
+    pub hash: String,
+    /// kayfabe version that wrote it
+    pub version: String,
+}
+
+impl ManifestEntry {
+    /// An entry for a file written by this version of kayfabe
+    pub fn new(path: PathBuf, agent: &str, scope: Scope, kind: EntryKind, hash: String) -> Self {
+        Self {
+            path,
+            agent: agent.to_string(),
+            scope,
+            kind,
+            hash,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// Record of every file `kayfabe install`, `init` and `prompts install`
+/// have written, so they can be upgraded or removed later without touching
+/// anything else. User-scope installs are kept in the data directory;
+/// project and worktree installs next to the layout's other machine state.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    #[serde(default, rename = "file")]
+    pub files: Vec<ManifestEntry>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl Manifest {
+    pub fn user_path() -> Result<PathBuf> {
+        dirs::data_dir()
+            .map(|dir| dir.join("kayfabe").join("manifest.toml"))
+            .ok_or_else(|| KayfabeError::Other("Could not find data directory".to_string()))
+    }
+
+    pub fn project_path(layout_root: &Path) -> PathBuf {
+        layout_root.join(".kayfabe").join("manifest.toml")
+    }
+
+    pub fn load(path: PathBuf) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self {
+                files: Vec::new(),
+                path,
+            });
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+        let mut manifest: Manifest = toml::from_str(&content).map_err(|e| {
+            KayfabeError::Other(format!(
+                "Failed to parse install manifest {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        manifest.path = path;
+        Ok(manifest)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if self.files.is_empty() {
+            if self.path.exists() {
+                std::fs::remove_file(&self.path)?;
+            }
+            return Ok(());
+        }
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = toml::to_string_pretty(self).map_err(|e| {
+            KayfabeError::Other(format!("Failed to serialize install manifest: {}", e))
+        })?;
+        std::fs::write(&self.path, content)?;
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&ManifestEntry> {
+        self.files.iter().find(|entry| entry.path == path)
+    }
+
+    /// Add an entry, replacing any earlier one for the same file
+    pub fn record(&mut self, entry: ManifestEntry) {
+        self.files.retain(|existing| existing.path != entry.path);
+        self.files.push(entry);
+        self.files.sort_by(|a, b| a.path.cmp(&b.path));
+    }
+
+    pub fn forget(&mut self, path: &Path) {
+        self.files.retain(|entry| entry.path != path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_and_reload() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = Manifest::project_path(temp_dir.path());
+        let entry = |file: &str, hash: &str| ManifestEntry {
+            path: temp_dir.path().join(file),
+            agent: "claude".to_string(),
+            scope: Scope::Worktree,
+            kind: EntryKind::File,
+            hash: hash.to_string(),
+            version: "0.1.0".to_string(),
+        };
+
+        let mut manifest = Manifest::load(path.clone()).unwrap();
+        manifest.record(entry("b.md", "1"));
+        manifest.record(entry("a.md", "2"));
+        manifest.record(entry("b.md", "3"));
+        manifest.save().unwrap();
+
+        let manifest = Manifest::load(path.clone()).unwrap();
+        assert_eq!(manifest.files, vec![entry("a.md", "2"), entry("b.md", "3")]);
+
+        let mut manifest = manifest;
+        manifest.forget(&temp_dir.path().join("a.md"));
+        manifest.forget(&temp_dir.path().join("b.md"));
+        manifest.save().unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -1,14 +1,14 @@
+pub mod context;
 pub mod installer;
+pub mod managed;
+pub mod manifest;
+pub mod prompts;
+pub mod rules;
+pub mod runner;
+pub mod validate;
 
+pub use context::ProjectContext;
 pub use installer::AgentInstaller;
-
-#[derive(Debug, Clone)]
-pub struct ProjectContext {
-    pub name: String,
-    pub project_type: String,
-    pub build_cmd: Option<String>,
-    pub test_cmd: Option<String>,
-    pub lint_cmd: Option<String>,
-    pub is_workspace: bool,
-    pub workspace_members: Vec<String>,
-}
+pub use manifest::{Manifest, Scope};
+pub use prompts::{Prompt, PromptKind, PromptLibrary, PromptSource};
+pub use runner::{AgentRunner, AgentStatus};
@@ -0,0 +1,419 @@
+use crate::agents::ProjectContext;
+use crate::config::PromptsConfig;
+use crate::error::{KayfabeError, Result};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// The prompts in `agents/`, compiled in
+const BUILTINS: &[(&str, &str)] = &[
+    ("ci_commit", include_str!("../../agents/ci_commit.md")),
+    (
+        "ci_describe_pr",
+        include_str!("../../agents/ci_describe_pr.md"),
+    ),
+    (
+        "codebase-analyzer",
+        include_str!("../../agents/codebase-analyzer.md"),
+    ),
+    (
+        "codebase-locator",
+        include_str!("../../agents/codebase-locator.md"),
+    ),
+    (
+        "codebase-pattern-finder",
+        include_str!("../../agents/codebase-pattern-finder.md"),
+    ),
+    ("commit", include_str!("../../agents/commit.md")),
+    (
+        "create_handoff",
+        include_str!("../../agents/create_handoff.md"),
+    ),
+    ("create_plan", include_str!("../../agents/create_plan.md")),
+    (
+        "create_plan_generic",
+        include_str!("../../agents/create_plan_generic.md"),
+    ),
+    (
+        "create_plan_nt",
+        include_str!("../../agents/create_plan_nt.md"),
+    ),
+    (
+        "create_worktree",
+        include_str!("../../agents/create_worktree.md"),
+    ),
+    ("describe_pr", include_str!("../../agents/describe_pr.md")),
+    (
+        "describe_pr_nt",
+        include_str!("../../agents/describe_pr_nt.md"),
+    ),
+    ("founder_mode", include_str!("../../agents/founder_mode.md")),
+    (
+        "implement_plan",
+        include_str!("../../agents/implement_plan.md"),
+    ),
+    ("iterate_plan", include_str!("../../agents/iterate_plan.md")),
+    (
+        "iterate_plan_nt",
+        include_str!("../../agents/iterate_plan_nt.md"),
+    ),
+    ("local_review", include_str!("../../agents/local_review.md")),
+    (
+        "research_codebase",
+        include_str!("../../agents/research_codebase.md"),
+    ),
+    (
+        "research_codebase_generic",
+        include_str!("../../agents/research_codebase_generic.md"),
+    ),
+    (
+        "research_codebase_nt",
+        include_str!("../../agents/research_codebase_nt.md"),
+    ),
+    (
+        "resume_handoff",
+        include_str!("../../agents/resume_handoff.md"),
+    ),
+    (
+        "thoughts-analyzer",
+        include_str!("../../agents/thoughts-analyzer.md"),
+    ),
+    (
+        "thoughts-locator",
+        include_str!("../../agents/thoughts-locator.md"),
+    ),
+    (
+        "validate_plan",
+        include_str!("../../agents/validate_plan.md"),
+    ),
+    (
+        "web-search-researcher",
+        include_str!("../../agents/web-search-researcher.md"),
+    ),
+];
+
+/// Slash command or subagent definition; subagents have a `name:` in their
+/// frontmatter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptKind {
+    Command,
+    Subagent,
+}
+
+impl PromptKind {
+    /// Directory under `.claude/` the prompt is installed into
+    pub fn install_dir(&self) -> &'static str {
+        match self {
+            PromptKind::Command => "commands",
+            PromptKind::Subagent => "agents",
+        }
+    }
+}
+
+impl std::fmt::Display for PromptKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PromptKind::Command => write!(f, "command"),
+            PromptKind::Subagent => write!(f, "subagent"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptSource {
+    /// Bundled with kayfabe
+    Builtin,
+    /// A user file shadowing a built-in of the same name
+    Override,
+    /// A user file with no built-in counterpart
+    User,
+}
+
+impl std::fmt::Display for PromptSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PromptSource::Builtin => write!(f, "builtin"),
+            PromptSource::Override => write!(f, "override"),
+            PromptSource::User => write!(f, "user"),
+        }
+    }
+}
+
+/// A prompt template, rendered with Tera
+#[derive(Debug, Clone)]
+pub struct Prompt {
+    pub name: String,
+    pub kind: PromptKind,
+    pub source: PromptSource,
+    pub template: String,
+    pub description: Option<String>,
+}
+
+impl Prompt {
+    fn new(name: &str, template: String, source: PromptSource) -> Self {
+        let fields = frontmatter(&template).unwrap_or_default();
+        let kind = if fields.iter().any(|(key, _)| key == "name") {
+            PromptKind::Subagent
+        } else {
+            PromptKind::Command
+        };
+        let description = fields
+            .into_iter()
+            .find(|(key, _)| key == "description")
+            .map(|(_, value)| value);
+
+        Self {
+            name: name.to_string(),
+            kind,
+            source,
+            template,
+            description,
+        }
+    }
+
+    pub fn render(&self, vars: &BTreeMap<String, String>) -> Result<String> {
+        render_template(&self.template, vars, &format!("prompt {}", self.name))
+    }
+}
+
+/// Render a Tera template; `what` names it in errors
+pub fn render_template(
+    template: &str,
+    vars: &BTreeMap<String, String>,
+    what: &str,
+) -> Result<String> {
+    let mut context = tera::Context::new();
+    for (key, value) in vars {
+        context.insert(key, value);
+    }
+
+    tera::Tera::one_off(template, &context, false).map_err(|e| {
+        // Tera's top-level message only names the template; the cause is
+        // further down the chain
+        let mut message = format!("Failed to render {}", what);
+        let mut source = std::error::Error::source(&e);
+        while let Some(cause) = source {
+            message.push_str(&format!(": {}", cause));
+            source = cause.source();
+        }
+        KayfabeError::Other(message)
+    })
+}
+
+/// Built-in prompts plus the user's own in `~/.config/kayfabe/prompts/`,
+/// where a file named like a built-in shadows it
+pub struct PromptLibrary {
+    override_dir: PathBuf,
+}
+
+impl PromptLibrary {
+    pub fn new(override_dir: PathBuf) -> Self {
+        Self { override_dir }
+    }
+
+    /// The library with the user's override directory
+    pub fn load() -> Result<Self> {
+        let config_dir = dirs::config_dir()
+            .ok_or_else(|| KayfabeError::Other("Could not find config directory".to_string()))?;
+        Ok(Self::new(config_dir.join("kayfabe").join("prompts")))
+    }
+
+    pub fn override_dir(&self) -> &Path {
+        &self.override_dir
+    }
+
+    pub fn override_path(&self, name: &str) -> PathBuf {
+        self.override_dir.join(format!("{}.md", name))
+    }
+
+    pub fn builtin(name: &str) -> Option<Prompt> {
+        BUILTINS
+            .iter()
+            .find(|(builtin, _)| *builtin == name)
+            .map(|(name, template)| Prompt::new(name, template.to_string(), PromptSource::Builtin))
+    }
+
+    /// Every prompt by name, user files taking precedence
+    pub fn list(&self) -> Result<Vec<Prompt>> {
+        let mut prompts: BTreeMap<String, Prompt> = BUILTINS
+            .iter()
+            .map(|(name, template)| {
+                (
+                    name.to_string(),
+                    Prompt::new(name, template.to_string(), PromptSource::Builtin),
+                )
+            })
+            .collect();
+
+        if self.override_dir.is_dir() {
+            for entry in std::fs::read_dir(&self.override_dir)? {
+                let path = entry?.path();
+                if path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+                    continue;
+                }
+                let Some(name) = path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                else {
+                    continue;
+                };
+                let source = if prompts.contains_key(&name) {
+                    PromptSource::Override
+                } else {
+                    PromptSource::User
+                };
+                let template = std::fs::read_to_string(&path)?;
+                prompts.insert(name.clone(), Prompt::new(&name, template, source));
+            }
+        }
+
+        Ok(prompts.into_values().collect())
+    }
+
+    pub fn get(&self, name: &str) -> Result<Prompt> {
+        let name = name.trim_end_matches(".md");
+        let path = self.override_path(name);
+        if path.is_file() {
+            let template = std::fs::read_to_string(&path)?;
+            let source = if Self::builtin(name).is_some() {
+                PromptSource::Override
+            } else {
+                PromptSource::User
+            };
+            return Ok(Prompt::new(name, template, source));
+        }
+
+        Self::builtin(name)
+            .ok_or_else(|| KayfabeError::Other(format!("Prompt not found: {}", name)))
+    }
+}
+
+/// Template variables for a checkout: detected project commands, then the
+/// `[prompts]` settings, then `[prompts.vars]`, which win over everything
+pub fn variables(context: &ProjectContext, config: &PromptsConfig) -> BTreeMap<String, String> {
+    let mut vars = BTreeMap::new();
+    vars.insert("project_name".to_string(), context.name.clone());
+    vars.insert("project_type".to_string(), context.project_type.clone());
+    // Empty when unknown, so templates can leave them out with `{% if %}`
+    vars.insert(
+        "build_cmd".to_string(),
+        context.build_cmd.clone().unwrap_or_default(),
+    );
+    vars.insert(
+        "test_cmd".to_string(),
+        context.test_cmd.clone().unwrap_or_default(),
+    );
+    vars.insert(
+        "lint_cmd".to_string(),
+        context.lint_cmd.clone().unwrap_or_default(),
+    );
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "me".to_string());
+    vars.insert("user".to_string(), user);
+    vars.insert("ticket_prefix".to_string(), config.ticket_prefix.clone());
+    vars.insert("thoughts_dir".to_string(), config.thoughts_dir.clone());
+
+    for (key, value) in &config.vars {
+        vars.insert(key.clone(), value.clone());
+    }
+    vars
+}
+
+/// `key: value` pairs of a leading `---` block, or `None` without one
+pub fn frontmatter(content: &str) -> Option<Vec<(String, String)>> {
+    let mut lines = content.lines();
+    if lines.next()?.trim_end() != "---" {
+        return None;
+    }
+
+    let mut fields = Vec::new();
+    for line in lines {
+        if line.trim_end() == "---" {
+            return Some(fields);
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            if !key.starts_with(' ') {
+                fields.push((key.trim().to_string(), value.trim().to_string()));
+            }
+        }
+    }
+    // Never closed
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn context() -> ProjectContext {
+        ProjectContext {
+            name: "proj".to_string(),
+            project_type: "rust".to_string(),
+            build_cmd: Some("cargo build".to_string()),
+            test_cmd: Some("cargo test".to_string()),
+            lint_cmd: None,
+            is_workspace: false,
+            workspace_members: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_builtins_render() {
+        let config = PromptsConfig {
+            ticket_prefix: "PLAT".to_string(),
+            vars: BTreeMap::from([("lint_cmd".to_string(), "just lint".to_string())]),
+            ..Default::default()
+        };
+        let vars = variables(&context(), &config);
+        assert_eq!(vars["lint_cmd"], "just lint");
+
+        for (name, _) in BUILTINS {
+            let prompt = PromptLibrary::builtin(name).unwrap();
+            let rendered = prompt.render(&vars).unwrap();
+            assert!(!rendered.contains("{{"), "{} left a placeholder", name);
+        }
+
+        let plan = PromptLibrary::builtin("create_plan").unwrap();
+        assert_eq!(plan.kind, PromptKind::Command);
+        let rendered = plan.render(&vars).unwrap();
+        assert!(rendered.contains("tickets/plat_1234.md"));
+        assert!(!rendered.contains("~/vibes"));
+        assert!(rendered.contains("`cargo test`"));
+        assert!(rendered.contains("`just lint`"));
+
+        let locator = PromptLibrary::builtin("codebase-locator").unwrap();
+        assert_eq!(locator.kind, PromptKind::Subagent);
+    }
+
+    #[test]
+    fn test_overrides_shadow_builtins() {
+        let temp_dir = TempDir::new().unwrap();
+        let library = PromptLibrary::new(temp_dir.path().to_path_buf());
+        std::fs::write(
+            library.override_path("commit"),
+            "---\ndescription: Commit my way\n---\n\nRun {{ test_cmd }} first\n",
+        )
+        .unwrap();
+        std::fs::write(library.override_path("triage"), "Triage {{ nope }}\n").unwrap();
+
+        let prompts = library.list().unwrap();
+        assert_eq!(prompts.len(), BUILTINS.len() + 1);
+
+        let commit = library.get("commit").unwrap();
+        assert_eq!(commit.source, PromptSource::Override);
+        assert_eq!(commit.description.as_deref(), Some("Commit my way"));
+        let vars = variables(&context(), &PromptsConfig::default());
+        assert_eq!(
+            commit.render(&vars).unwrap().trim_end(),
+            "---\ndescription: Commit my way\n---\n\nRun cargo test first"
+        );
+
+        let triage = library.get("triage.md").unwrap();
+        assert_eq!(triage.source, PromptSource::User);
+        let err = triage.render(&vars).unwrap_err().to_string();
+        assert!(err.contains("nope"), "{}", err);
+
+        assert!(library.get("missing").is_err());
+    }
+}
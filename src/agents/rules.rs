@@ -0,0 +1,307 @@
+use crate::agents::prompts::{frontmatter, render_template};
+use crate::agents::ProjectContext;
+use crate::error::{KayfabeError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Canonical rules, relative to the checkout so they are committed with it
+pub const RULES_DIR: &str = ".kayfabe/rules";
+
+/// Agents to generate rule files for, and variables to render the rules
+/// with, committed next to the rules so `rules check` gives the same answer
+/// on every machine
+pub const TARGETS_FILE: &str = "agents.toml";
+
+/// First line of every generated file (after Cursor's frontmatter)
+pub const GENERATED_MARKER: &str =
+    "<!-- Generated by `kayfabe rules sync` from .kayfabe/rules/. Edit those files instead. -->";
+
+/// Agents `rules sync` can write for, and where their rule file lives
+const TARGETS: &[(&str, &str)] = &[
+    ("claude", "CLAUDE.md"),
+    ("codex", "AGENTS.md"),
+    ("cursor", ".cursor/rules/kayfabe.mdc"),
+    ("windsurf", ".windsurfrules"),
+];
+
+/// Default rule file for an agent, if rules can be generated for it
+pub fn default_location(agent: &str) -> Option<&'static str> {
+    TARGETS
+        .iter()
+        .find(|(name, _)| *name == agent)
+        .map(|(_, location)| *location)
+}
+
+pub fn known_agents() -> impl Iterator<Item = &'static str> {
+    TARGETS.iter().map(|(name, _)| *name)
+}
+
+/// Whether a file was written by `rules sync`, and so may be overwritten
+pub fn is_generated(content: &str) -> bool {
+    content.lines().take(8).any(|line| line == GENERATED_MARKER)
+}
+
+/// Strip the frontmatter, such as Cursor's, from a rule file
+pub fn body(content: &str) -> String {
+    if frontmatter(content).is_none() {
+        return content.to_string();
+    }
+    content
+        .lines()
+        .skip(1)
+        .skip_while(|line| line.trim_end() != "---")
+        .skip(1)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Targets {
+    agents: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    vars: BTreeMap<String, String>,
+}
+
+/// One file of `.kayfabe/rules/`
+#[derive(Debug, Clone)]
+struct RuleSource {
+    name: String,
+    /// From `agents:` in the frontmatter; `None` means every agent
+    agents: Option<Vec<String>>,
+    body: String,
+}
+
+/// The markdown files in `.kayfabe/rules/`, in name order. Each is a Tera
+/// template rendered once per agent, with `agent` set to the agent's name,
+/// so sections can differ with `{% if agent == "cursor" %}`. A file with
+/// `agents: claude, codex` in its frontmatter is only used for those.
+pub struct RuleSet {
+    sources: Vec<RuleSource>,
+}
+
+impl RuleSet {
+    pub fn dir(root: &Path) -> PathBuf {
+        root.join(RULES_DIR)
+    }
+
+    /// The agents listed in `.kayfabe/rules/agents.toml`, if it exists
+    pub fn targets(root: &Path) -> Result<Option<Vec<String>>> {
+        Ok(Self::read_targets(root)?.map(|targets| targets.agents))
+    }
+
+    /// Variables the rules are rendered with: the commands detected from
+    /// the checkout's build files, overridden by `[vars]` in `agents.toml`.
+    /// Nothing comes from the machine or the checkout's directory, so every
+    /// clone renders the same files.
+    pub fn variables(root: &Path) -> Result<BTreeMap<String, String>> {
+        let context = ProjectContext::detect(root);
+        let mut vars = BTreeMap::from([
+            ("project_type".to_string(), context.project_type),
+            (
+                "build_cmd".to_string(),
+                context.build_cmd.unwrap_or_default(),
+            ),
+            ("test_cmd".to_string(), context.test_cmd.unwrap_or_default()),
+            ("lint_cmd".to_string(), context.lint_cmd.unwrap_or_default()),
+        ]);
+        if let Some(targets) = Self::read_targets(root)? {
+            vars.extend(targets.vars);
+        }
+        Ok(vars)
+    }
+
+    fn read_targets(root: &Path) -> Result<Option<Targets>> {
+        let path = Self::dir(root).join(TARGETS_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(&path)?;
+        let targets: Targets = toml::from_str(&content).map_err(|e| {
+            KayfabeError::Other(format!("Failed to parse {}: {}", path.display(), e))
+        })?;
+        Ok(Some(targets))
+    }
+
+    pub fn write_targets(
+        root: &Path,
+        agents: &[String],
+        vars: &BTreeMap<String, String>,
+    ) -> Result<()> {
+        let targets = Targets {
+            agents: agents.to_vec(),
+            vars: vars.clone(),
+        };
+        let content = toml::to_string(&targets)
+            .map_err(|e| KayfabeError::Other(format!("Failed to serialize rule targets: {}", e)))?;
+        std::fs::write(Self::dir(root).join(TARGETS_FILE), content)?;
+        Ok(())
+    }
+
+    pub fn load(root: &Path) -> Result<Self> {
+        let dir = Self::dir(root);
+        if !dir.is_dir() {
+            return Err(KayfabeError::Other(format!(
+                "No {} directory (create it with `kayfabe rules init`)",
+                RULES_DIR
+            )));
+        }
+
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+            .collect();
+        paths.sort();
+
+        let mut sources = Vec::new();
+        for path in paths {
+            let content = std::fs::read_to_string(&path)?;
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            sources.push(parse_source(name, &content));
+        }
+
+        Ok(Self { sources })
+    }
+
+    /// The complete rule file for `agent`
+    pub fn render(&self, agent: &str, vars: &BTreeMap<String, String>) -> Result<String> {
+        let mut vars = vars.clone();
+        vars.insert("agent".to_string(), agent.to_string());
+
+        let mut sections = Vec::new();
+        for source in &self.sources {
+            if let Some(agents) = &source.agents {
+                if !agents.iter().any(|name| name == agent) {
+                    continue;
+                }
+            }
+            let rendered = render_template(
+                &source.body,
+                &vars,
+                &format!("{}/{}", RULES_DIR, source.name),
+            )?;
+            let rendered = rendered.trim();
+            if !rendered.is_empty() {
+                sections.push(rendered.to_string());
+            }
+        }
+
+        let mut content = String::new();
+        if agent == "cursor" {
+            // Project rules need frontmatter to be applied to every request
+            content.push_str("---\ndescription: Project rules\nalwaysApply: true\n---\n");
+        }
+        content.push_str(GENERATED_MARKER);
+        content.push_str("\n\n");
+        content.push_str(&sections.join("\n\n"));
+        content.push('\n');
+        Ok(content)
+    }
+}
+
+fn parse_source(name: String, content: &str) -> RuleSource {
+    let Some(fields) = frontmatter(content) else {
+        return RuleSource {
+            name,
+            agents: None,
+            body: content.to_string(),
+        };
+    };
+
+    let agents = fields
+        .iter()
+        .find(|(key, _)| key == "agents")
+        .map(|(_, value)| {
+            value
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .split(',')
+                .map(|agent| agent.trim().trim_matches('"').to_lowercase())
+                .filter(|agent| !agent.is_empty())
+                .collect()
+        });
+    RuleSource {
+        name,
+        agents,
+        body: body(content),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_render_per_agent() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = RuleSet::dir(temp_dir.path());
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("00-project.md"),
+            "# {{ project_name }}\n\nRun `{{ test_cmd }}` before committing.\n\
+             {% if agent == \"windsurf\" %}\nUse Cascade's terminal.\n{% endif %}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("10-subagents.md"),
+            "---\nagents: [claude, codex]\n---\n\n## Subagents\n\nUse codebase-locator first.\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let rules = RuleSet::load(temp_dir.path()).unwrap();
+        let vars = BTreeMap::from([
+            ("project_name".to_string(), "proj".to_string()),
+            ("test_cmd".to_string(), "cargo test".to_string()),
+        ]);
+
+        let claude = rules.render("claude", &vars).unwrap();
+        assert!(claude.starts_with(GENERATED_MARKER));
+        assert!(claude.contains("Run `cargo test` before committing."));
+        assert!(claude.contains("## Subagents"));
+        assert!(!claude.contains("Cascade"));
+
+        let windsurf = rules.render("windsurf", &vars).unwrap();
+        assert!(windsurf.contains("Use Cascade's terminal."));
+        assert!(!windsurf.contains("Subagents"));
+
+        let cursor = rules.render("cursor", &vars).unwrap();
+        assert!(cursor.starts_with("---\ndescription: Project rules\nalwaysApply: true\n---\n"));
+        assert!(is_generated(&cursor));
+        assert!(!is_generated("# Hand-written rules\n"));
+    }
+
+    #[test]
+    fn test_targets_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        assert_eq!(RuleSet::targets(temp_dir.path()).unwrap(), None);
+
+        std::fs::create_dir_all(RuleSet::dir(temp_dir.path())).unwrap();
+        let agents = vec!["claude".to_string(), "cursor".to_string()];
+        let vars = BTreeMap::from([
+            ("project_name".to_string(), "proj".to_string()),
+            ("test_cmd".to_string(), "just test".to_string()),
+        ]);
+        RuleSet::write_targets(temp_dir.path(), &agents, &vars).unwrap();
+        assert_eq!(RuleSet::targets(temp_dir.path()).unwrap(), Some(agents));
+
+        // Committed variables win over detected ones
+        std::fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"x\"\n",
+        )
+        .unwrap();
+        let vars = RuleSet::variables(temp_dir.path()).unwrap();
+        assert_eq!(vars["project_name"], "proj");
+        assert_eq!(vars["test_cmd"], "just test");
+        assert_eq!(vars["lint_cmd"], "cargo clippy -- -D warnings");
+        assert!(!vars.contains_key("user"));
+
+        assert_eq!(body("---\nalwaysApply: true\n---\n# Rules"), "# Rules");
+        assert_eq!(body("# Rules\n"), "# Rules\n");
+    }
+}
@@ -0,0 +1,280 @@
+use crate::config::{AgentCliConfig, AgentProcess, Config};
+use crate::error::{KayfabeError, Result};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// (name, command, args)
+const BUILTINS: &[(&str, &str, &[&str])] = &[
+    ("claude", "claude", &["-p", "{prompt}"]),
+    ("aider", "aider", &["--yes-always", "--message", "{prompt}"]),
+    ("codex", "codex", &["exec", "{prompt}"]),
+];
+
+/// How far the process start time `ps` reports may be from `started_at`
+const START_TOLERANCE_SECS: u64 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgentStatus {
+    Running,
+    /// Exit code, if the agent exited on its own
+    Exited(Option<i32>),
+}
+
+pub struct AgentRunner;
+
+impl AgentRunner {
+    /// Look up an agent CLI, preferring `[agent_cli.<name>]` over the built-in
+    pub fn resolve(name: &str, config: &Config) -> Option<AgentCliConfig> {
+        config.agent_cli.get(name).cloned().or_else(|| {
+            BUILTINS
+                .iter()
+                .find(|(builtin, ..)| *builtin == name)
+                .map(|(_, command, args)| AgentCliConfig {
+                    command: command.to_string(),
+                    args: args.iter().map(|arg| arg.to_string()).collect(),
+                })
+        })
+    }
+
+    /// Start `cli` in the background in `wt_path`, with output going to a log
+    /// under `.kayfabe/logs/<worktree>/`. The agent runs in its own process
+    /// group so it outlives kayfabe and can be stopped as a unit.
+    pub fn spawn(
+        layout_root: &Path,
+        worktree: &str,
+        wt_path: &Path,
+        agent: &str,
+        cli: &AgentCliConfig,
+        prompt: &str,
+        env: &[(String, String)],
+    ) -> Result<AgentProcess> {
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let log_dir = Self::log_dir(layout_root, worktree);
+        std::fs::create_dir_all(&log_dir)?;
+        let log = log_dir.join(format!(
+            "{}-{}.log",
+            agent,
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        let log_file = std::fs::File::create(&log)?;
+
+        let args: Vec<String> = cli
+            .args
+            .iter()
+            .map(|arg| arg.replace("{prompt}", prompt))
+            .collect();
+
+        // A small shell wrapper records the exit code next to the log
+        let mut command = Command::new("sh");
+        command
+            .arg("-c")
+            .arg(r#""$@"; echo $? > "$0""#)
+            .arg(Self::exit_file(&log))
+            .arg(&cli.command)
+            .args(&args)
+            .current_dir(wt_path)
+            .envs(env.iter().cloned())
+            .stdin(Stdio::null())
+            .stdout(log_file.try_clone()?)
+            .stderr(log_file);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
+
+        let child = command
+            .spawn()
+            .map_err(|e| KayfabeError::Other(format!("Failed to start {}: {}", cli.command, e)))?;
+
+        Ok(AgentProcess {
+            agent: agent.to_string(),
+            pid: child.id(),
+            log,
+            started_at,
+        })
+    }
+
+    /// The exit file is checked first: once the agent has exited its PID may
+    /// be reused, so a live process with that PID proves nothing on its own
+    pub fn status(process: &AgentProcess) -> AgentStatus {
+        if let Ok(code) = std::fs::read_to_string(Self::exit_file(&process.log)) {
+            if !code.trim().is_empty() {
+                return AgentStatus::Exited(code.trim().parse().ok());
+            }
+        }
+
+        if Self::is_ours(process) && Self::signal(process.pid, "0") {
+            AgentStatus::Running
+        } else {
+            AgentStatus::Exited(None)
+        }
+    }
+
+    /// Send SIGTERM to the agent's process group, escalating to SIGKILL if it
+    /// is still running after a few seconds. Returns `false` if it had
+    /// already exited.
+    pub fn stop(process: &AgentProcess) -> Result<bool> {
+        if Self::status(process) != AgentStatus::Running {
+            return Ok(false);
+        }
+
+        Self::signal(process.pid, "TERM");
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if Self::status(process) != AgentStatus::Running {
+                return Ok(true);
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+
+        Self::signal(process.pid, "KILL");
+        Ok(true)
+    }
+
+    pub fn log_dir(layout_root: &Path, worktree: &str) -> PathBuf {
+        layout_root.join(".kayfabe").join("logs").join(worktree)
+    }
+
+    fn exit_file(log: &Path) -> PathBuf {
+        log.with_extension("exit")
+    }
+
+    /// Whether `process.pid` is still the process kayfabe started, judged by
+    /// its start time
+    fn is_ours(process: &AgentProcess) -> bool {
+        let Ok(output) = Command::new("ps")
+            .args(["-o", "etime=", "-p", &process.pid.to_string()])
+            .stderr(Stdio::null())
+            .output()
+        else {
+            return false;
+        };
+        let Some(elapsed) = parse_etime(&String::from_utf8_lossy(&output.stdout)) else {
+            return false;
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        now.saturating_sub(elapsed).abs_diff(process.started_at) <= START_TOLERANCE_SECS
+    }
+
+    /// Signal the process group led by `pid` (`0` just checks it exists)
+    fn signal(pid: u32, signal: &str) -> bool {
+        Command::new("kill")
+            .arg(format!("-{}", signal))
+            .arg("--")
+            .arg(format!("-{}", pid))
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    }
+}
+
+/// Seconds from `ps -o etime`, formatted `[[dd-]hh:]mm:ss`
+fn parse_etime(etime: &str) -> Option<u64> {
+    let etime = etime.trim();
+    let (days, clock) = match etime.split_once('-') {
+        Some((days, clock)) => (days.parse::<u64>().ok()?, clock),
+        None => (0, etime),
+    };
+    let mut seconds = 0;
+    for part in clock.split(':') {
+        seconds = seconds * 60 + part.parse::<u64>().ok()?;
+    }
+    Some(days * 86400 + seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_spawn_records_output_and_exit_code() {
+        let temp_dir = TempDir::new().unwrap();
+        let cli = AgentCliConfig {
+            command: "sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                "echo \"$KAYFABE_WORKTREE: {prompt}\"; exit 3".to_string(),
+            ],
+        };
+        let env = vec![("KAYFABE_WORKTREE".to_string(), "feature".to_string())];
+
+        let process = AgentRunner::spawn(
+            temp_dir.path(),
+            "feature",
+            temp_dir.path(),
+            "test",
+            &cli,
+            "fix the bug",
+            &env,
+        )
+        .unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let exit_file = AgentRunner::exit_file(&process.log);
+        while !std::fs::read_to_string(&exit_file).is_ok_and(|code| !code.is_empty()) {
+            assert!(Instant::now() < deadline, "agent never exited");
+            std::thread::sleep(Duration::from_millis(20));
+        }
+
+        assert_eq!(
+            std::fs::read_to_string(&process.log).unwrap(),
+            "feature: fix the bug\n"
+        );
+        assert_eq!(std::fs::read_to_string(&exit_file).unwrap(), "3\n");
+    }
+
+    #[test]
+    fn test_reused_pid_is_not_running() {
+        assert_eq!(parse_etime("05:03\n"), Some(303));
+        assert_eq!(parse_etime("2-01:00:00"), Some(2 * 86400 + 3600));
+        assert_eq!(parse_etime(""), None);
+
+        // A live process that started long after the agent did
+        let temp_dir = TempDir::new().unwrap();
+        let process = AgentProcess {
+            agent: "test".to_string(),
+            pid: std::process::id(),
+            log: temp_dir.path().join("agent.log"),
+            started_at: 1_000_000,
+        };
+        assert_eq!(AgentRunner::status(&process), AgentStatus::Exited(None));
+        assert!(!AgentRunner::stop(&process).unwrap());
+
+        // The exit file wins even if the PID is alive
+        std::fs::write(AgentRunner::exit_file(&process.log), "0\n").unwrap();
+        assert_eq!(AgentRunner::status(&process), AgentStatus::Exited(Some(0)));
+    }
+
+    #[test]
+    fn test_spawned_agent_is_running() {
+        let temp_dir = TempDir::new().unwrap();
+        let cli = AgentCliConfig {
+            command: "sleep".to_string(),
+            args: vec!["{prompt}".to_string()],
+        };
+        let process = AgentRunner::spawn(
+            temp_dir.path(),
+            "feature",
+            temp_dir.path(),
+            "test",
+            &cli,
+            "30",
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(AgentRunner::status(&process), AgentStatus::Running);
+        // The test can't reap the child, so stop() would wait on a zombie
+        assert!(AgentRunner::signal(process.pid, "KILL"));
+    }
+}
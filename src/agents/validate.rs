@@ -0,0 +1,716 @@
+use crate::agents::prompts::frontmatter;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// `.windsurfrules` is silently truncated past this many characters
+const WINDSURF_RULES_MAX_CHARS: usize = 6_000;
+/// Per-file cap for `.windsurf/rules/*.md`
+const WINDSURF_RULE_FILE_MAX_CHARS: usize = 12_000;
+/// Cursor's guidance for a single rule
+const CURSOR_RULE_MAX_LINES: usize = 500;
+/// Claude Code warns that memory files past this size hurt performance
+const MEMORY_FILE_MAX_CHARS: usize = 40_000;
+
+const COMMAND_KEYS: &[&str] = &[
+    "description",
+    "argument-hint",
+    "allowed-tools",
+    "model",
+    "disable-model-invocation",
+];
+const SUBAGENT_KEYS: &[&str] = &["name", "description", "tools", "model", "color"];
+const CURSOR_RULE_KEYS: &[&str] = &["description", "globs", "alwaysApply"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub severity: Severity,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Finding {
+    fn error(line: Option<usize>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            line,
+            message: message.into(),
+        }
+    }
+
+    fn warning(line: Option<usize>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    /// `.windsurfrules`
+    WindsurfRules,
+    /// `.windsurf/rules/*.md`
+    WindsurfRule,
+    /// `.cursorrules`
+    CursorRules,
+    /// `.cursor/rules/*.mdc`
+    CursorRule,
+    /// `CLAUDE.md`, `AGENTS.md`
+    Memory,
+    /// `.claude/commands/**/*.md`
+    Command,
+    /// `.claude/agents/*.md`
+    Subagent,
+}
+
+/// An agent configuration file found in a checkout
+#[derive(Debug, Clone)]
+pub struct AgentFile {
+    /// Relative to the checkout root
+    pub path: PathBuf,
+    pub agent: &'static str,
+    pub kind: FileKind,
+}
+
+/// Checks agent files against what actually exists in a checkout: Make
+/// targets, npm scripts, slash commands and kayfabe's own commands
+pub struct Validator {
+    root: PathBuf,
+    /// kayfabe subcommands and their subcommands
+    kayfabe_commands: Vec<(String, Vec<String>)>,
+    make_targets: Option<BTreeSet<String>>,
+    /// `None` without a package.json, or with one that isn't valid JSON
+    npm_scripts: Option<BTreeSet<String>>,
+    slash_commands: Option<BTreeSet<String>>,
+}
+
+impl Validator {
+    pub fn new(root: &Path, kayfabe_commands: Vec<(String, Vec<String>)>) -> Self {
+        let make_targets = std::fs::read_to_string(root.join("Makefile"))
+            .ok()
+            .map(|makefile| make_targets(&makefile));
+        let npm_scripts = std::fs::read_to_string(root.join("package.json"))
+            .ok()
+            .and_then(|package| npm_scripts(&package));
+
+        let commands_dir = root.join(".claude").join("commands");
+        let slash_commands = commands_dir.is_dir().then(|| {
+            let mut names = BTreeSet::new();
+            for file in markdown_files(&commands_dir, "md") {
+                // Commands in subdirectories are namespaced as `dir:name`
+                let relative = file.strip_prefix(&commands_dir).unwrap_or(&file);
+                let name = relative.with_extension("");
+                names.insert(
+                    name.to_string_lossy()
+                        .replace(std::path::MAIN_SEPARATOR, ":"),
+                );
+                if let Some(stem) = file.file_stem() {
+                    names.insert(stem.to_string_lossy().to_string());
+                }
+            }
+            names
+        });
+
+        Self {
+            root: root.to_path_buf(),
+            kayfabe_commands,
+            make_targets,
+            npm_scripts,
+            slash_commands,
+        }
+    }
+
+    /// Every agent configuration file present in the checkout
+    pub fn files(&self) -> Vec<AgentFile> {
+        let mut files = Vec::new();
+        let mut add = |path: PathBuf, agent: &'static str, kind: FileKind| {
+            let path = path.strip_prefix(&self.root).unwrap_or(&path).to_path_buf();
+            files.push(AgentFile { path, agent, kind });
+        };
+
+        for (name, agent, kind) in [
+            (".windsurfrules", "Windsurf", FileKind::WindsurfRules),
+            (".cursorrules", "Cursor", FileKind::CursorRules),
+            ("CLAUDE.md", "Claude", FileKind::Memory),
+            ("AGENTS.md", "Codex", FileKind::Memory),
+        ] {
+            let path = self.root.join(name);
+            if path.is_file() {
+                add(path, agent, kind);
+            }
+        }
+        for (dir, extension, agent, kind) in [
+            (".windsurf/rules", "md", "Windsurf", FileKind::WindsurfRule),
+            (".cursor/rules", "mdc", "Cursor", FileKind::CursorRule),
+            (".claude/commands", "md", "Claude", FileKind::Command),
+            (".claude/agents", "md", "Claude", FileKind::Subagent),
+        ] {
+            for path in markdown_files(&self.root.join(dir), extension) {
+                add(path, agent, kind);
+            }
+        }
+
+        files
+    }
+
+    pub fn validate(&self, file: &AgentFile, content: &str) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        if content.trim().is_empty() {
+            findings.push(Finding::error(None, "empty file"));
+            return findings;
+        }
+
+        self.check_size(file, content, &mut findings);
+        self.check_frontmatter(file, content, &mut findings);
+        self.check_links(file, content, &mut findings);
+        self.check_commands(content, &mut findings);
+
+        findings.sort_by_key(|finding| (finding.line, std::cmp::Reverse(finding.severity)));
+        findings
+    }
+
+    fn check_size(&self, file: &AgentFile, content: &str, findings: &mut Vec<Finding>) {
+        let chars = content.chars().count();
+        let lines = content.lines().count();
+        match file.kind {
+            FileKind::WindsurfRules if chars > WINDSURF_RULES_MAX_CHARS => {
+                findings.push(Finding::error(
+                    None,
+                    format!(
+                        "{} characters; Windsurf ignores everything past {}",
+                        chars, WINDSURF_RULES_MAX_CHARS
+                    ),
+                ));
+            }
+            FileKind::WindsurfRule if chars > WINDSURF_RULE_FILE_MAX_CHARS => {
+                findings.push(Finding::error(
+                    None,
+                    format!(
+                        "{} characters; Windsurf ignores everything past {}",
+                        chars, WINDSURF_RULE_FILE_MAX_CHARS
+                    ),
+                ));
+            }
+            FileKind::CursorRules | FileKind::CursorRule if lines > CURSOR_RULE_MAX_LINES => {
+                findings.push(Finding::warning(
+                    None,
+                    format!(
+                        "{} lines; Cursor recommends keeping rules under {}",
+                        lines, CURSOR_RULE_MAX_LINES
+                    ),
+                ));
+            }
+            FileKind::Memory if chars > MEMORY_FILE_MAX_CHARS => {
+                findings.push(Finding::warning(
+                    None,
+                    format!(
+                        "{} characters; large memory files slow every session (over {})",
+                        chars, MEMORY_FILE_MAX_CHARS
+                    ),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    fn check_frontmatter(&self, file: &AgentFile, content: &str, findings: &mut Vec<Finding>) {
+        let (keys, required) = match file.kind {
+            FileKind::Command => (COMMAND_KEYS, &[][..]),
+            FileKind::Subagent => (SUBAGENT_KEYS, &["name", "description"][..]),
+            FileKind::CursorRule => (CURSOR_RULE_KEYS, &[][..]),
+            _ => return,
+        };
+
+        let fields = if content.starts_with("---") {
+            match frontmatter(content) {
+                Some(fields) => fields,
+                None => {
+                    findings.push(Finding::error(
+                        Some(1),
+                        "frontmatter is never closed with ---",
+                    ));
+                    return;
+                }
+            }
+        } else {
+            Vec::new()
+        };
+
+        for key in required {
+            match fields.iter().find(|(field, _)| field == key) {
+                None => findings.push(Finding::error(
+                    Some(1),
+                    format!("frontmatter is missing `{}`", key),
+                )),
+                Some((_, value)) if value.is_empty() => findings.push(Finding::error(
+                    Some(1),
+                    format!("frontmatter `{}` is empty", key),
+                )),
+                _ => {}
+            }
+        }
+        for (field, _) in &fields {
+            if !keys.contains(&field.as_str()) {
+                findings.push(Finding::warning(
+                    Some(1),
+                    format!("unknown frontmatter key `{}`", field),
+                ));
+            }
+        }
+
+        if file.kind == FileKind::Subagent {
+            if let Some((_, name)) = fields.iter().find(|(field, _)| field == "name") {
+                let valid = !name.is_empty()
+                    && name
+                        .chars()
+                        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+                if !valid {
+                    findings.push(Finding::error(
+                        Some(1),
+                        format!(
+                            "subagent name `{}` must be lowercase letters, digits and -",
+                            name
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    fn check_links(&self, file: &AgentFile, content: &str, findings: &mut Vec<Finding>) {
+        let dir = self
+            .root
+            .join(&file.path)
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| self.root.clone());
+
+        for (number, line) in prose_lines(content) {
+            for target in link_targets(line) {
+                let target = target.split(['#', '?']).next().unwrap_or_default();
+                if target.is_empty() {
+                    continue;
+                }
+                let resolved = match target.strip_prefix('/') {
+                    Some(from_root) => self.root.join(from_root),
+                    None => dir.join(target),
+                };
+                if !resolved.exists() {
+                    findings.push(Finding::error(
+                        Some(number),
+                        format!("broken link to {}", target),
+                    ));
+                }
+            }
+        }
+    }
+
+    fn check_commands(&self, content: &str, findings: &mut Vec<Finding>) {
+        for (number, snippet) in code_snippets(content) {
+            let mut first = true;
+            for segment in snippet.split(['|', ';']).flat_map(|part| part.split("&&")) {
+                let segment = segment.trim();
+                let segment = segment.strip_prefix("$ ").unwrap_or(segment);
+                if let Some(message) = self.check_command(segment, first) {
+                    findings.push(message.at(number));
+                }
+                first = false;
+            }
+        }
+    }
+
+    fn check_command(&self, command: &str, first: bool) -> Option<Pending> {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        match words.as_slice() {
+            ["kayfabe", sub, rest @ ..] if is_word(sub) => {
+                let Some((_, subcommands)) =
+                    self.kayfabe_commands.iter().find(|(name, _)| name == sub)
+                else {
+                    return Some(Pending::error(format!(
+                        "`kayfabe {}` is not a kayfabe command",
+                        sub
+                    )));
+                };
+                let next = rest.first().filter(|next| is_word(next))?;
+                (!subcommands.is_empty() && !subcommands.iter().any(|name| name == next)).then(
+                    || {
+                        Pending::error(format!(
+                            "`kayfabe {} {}` is not a kayfabe command",
+                            sub, next
+                        ))
+                    },
+                )
+            }
+            // Targets of another Makefile
+            ["make", args @ ..] if args.iter().any(|arg| matches!(*arg, "-C" | "-f")) => None,
+            ["make", args @ ..] => {
+                let targets = self.make_targets.as_ref();
+                for target in args
+                    .iter()
+                    .filter(|arg| !arg.starts_with('-') && !arg.contains('='))
+                    .filter(|arg| is_word(arg))
+                {
+                    match targets {
+                        None => {
+                            return Some(Pending::error(format!(
+                                "`make {}` but there is no Makefile",
+                                target
+                            )))
+                        }
+                        Some(targets) if !targets.contains(*target) => {
+                            return Some(Pending::error(format!(
+                                "`make {}` but the Makefile has no such target",
+                                target
+                            )))
+                        }
+                        _ => {}
+                    }
+                }
+                None
+            }
+            ["npm" | "pnpm" | "yarn", "run", script, ..] if is_word(script) => {
+                match self.npm_scripts.as_ref() {
+                    // Nothing reliable to check against
+                    None if self.root.join("package.json").exists() => None,
+                    None => Some(Pending::error(format!(
+                        "`{}` but there is no package.json",
+                        command
+                    ))),
+                    Some(scripts) if !scripts.contains(*script) => Some(Pending::error(format!(
+                        "`{}` but package.json has no `{}` script",
+                        command, script
+                    ))),
+                    _ => None,
+                }
+            }
+            [slash, ..] if first => {
+                let name = slash.strip_prefix('/')?;
+                let plausible = !name.is_empty()
+                    && name
+                        .chars()
+                        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "_-:".contains(c));
+                let known = self.slash_commands.as_ref()?;
+                (plausible && !known.contains(name)).then(|| {
+                    Pending::warning(format!(
+                        "`/{}` is not in .claude/commands (fine if it is a user or built-in command)",
+                        name
+                    ))
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A finding waiting for its line number
+struct Pending(Severity, String);
+
+impl Pending {
+    fn error(message: String) -> Self {
+        Self(Severity::Error, message)
+    }
+
+    fn warning(message: String) -> Self {
+        Self(Severity::Warning, message)
+    }
+
+    fn at(self, line: usize) -> Finding {
+        Finding {
+            severity: self.0,
+            line: Some(line),
+            message: self.1,
+        }
+    }
+}
+
+/// Looks like a subcommand or target name rather than a placeholder
+fn is_word(word: &str) -> bool {
+    word.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphanumeric())
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:".contains(c))
+}
+
+/// Lines outside fenced code blocks and frontmatter, numbered from 1
+fn prose_lines(content: &str) -> Vec<(usize, &str)> {
+    let mut lines = Vec::new();
+    let mut in_code = false;
+    let mut in_frontmatter = content.starts_with("---");
+    for (i, line) in content.lines().enumerate() {
+        if in_frontmatter {
+            in_frontmatter = i == 0 || line.trim_end() != "---";
+            continue;
+        }
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if !in_code {
+            lines.push((i + 1, line));
+        }
+    }
+    lines
+}
+
+/// Shell fences whose lines are commands; other fences (e.g. a markdown
+/// example of a plan) are skipped
+const SHELL_FENCES: &[&str] = &["", "bash", "sh", "shell", "console", "zsh"];
+
+/// Lines of shell code blocks, and inline `code` spans outside code blocks
+fn code_snippets(content: &str) -> Vec<(usize, String)> {
+    let mut snippets = Vec::new();
+    let mut fence: Option<bool> = None;
+    for (i, line) in content.lines().enumerate() {
+        if let Some(language) = line.trim_start().strip_prefix("```") {
+            fence = match fence {
+                Some(_) => None,
+                None => Some(SHELL_FENCES.contains(&language.trim())),
+            };
+            continue;
+        }
+        match fence {
+            Some(true) => snippets.push((i + 1, line.trim().to_string())),
+            Some(false) => {}
+            None => {
+                for (j, span) in line.split('`').enumerate() {
+                    if j % 2 == 1 && !span.is_empty() {
+                        snippets.push((i + 1, span.to_string()));
+                    }
+                }
+            }
+        }
+    }
+    snippets
+}
+
+/// Relative targets of `[text](target)` links on a line
+fn link_targets(line: &str) -> Vec<&str> {
+    let mut targets = Vec::new();
+    let mut rest = line;
+    while let Some(start) = rest.find("](") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find(')') else {
+            break;
+        };
+        let target = rest[..end].trim();
+        // `[text](path "title")`
+        let target = target.split_whitespace().next().unwrap_or_default();
+        let target = target.trim_start_matches('<').trim_end_matches('>');
+        let external = target.contains("://")
+            || target.starts_with("mailto:")
+            || target.starts_with('#')
+            || target.contains("{{");
+        if !target.is_empty() && !external {
+            targets.push(target);
+        }
+        rest = &rest[end..];
+    }
+    targets
+}
+
+pub(crate) fn make_targets(makefile: &str) -> BTreeSet<String> {
+    let mut targets = BTreeSet::new();
+    for line in makefile.lines() {
+        if line.starts_with(['\t', ' ', '#', '.']) {
+            continue;
+        }
+        let Some((names, rest)) = line.split_once(':') else {
+            continue;
+        };
+        // `VAR := value`, `VAR ?= value`
+        if rest.starts_with('=') || names.contains('=') {
+            continue;
+        }
+        targets.extend(names.split_whitespace().map(str::to_string));
+    }
+    targets
+}
+
+/// Keys of the `"scripts"` object, or `None` if `package` isn't valid JSON
+pub(crate) fn npm_scripts(package: &str) -> Option<BTreeSet<String>> {
+    let package: serde_json::Value = serde_json::from_str(package).ok()?;
+    Some(
+        package
+            .get("scripts")
+            .and_then(|scripts| scripts.as_object())
+            .map(|scripts| scripts.keys().cloned().collect())
+            .unwrap_or_default(),
+    )
+}
+
+fn markdown_files(dir: &Path, extension: &str) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return files;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(markdown_files(&path, extension));
+        } else if path.extension().is_some_and(|ext| ext == extension) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn validator(root: &Path) -> Validator {
+        Validator::new(
+            root,
+            vec![
+                ("status".to_string(), Vec::new()),
+                (
+                    "worktree".to_string(),
+                    vec!["create".to_string(), "list".to_string()],
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_rules_file_references() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::write(
+            root.join("Makefile"),
+            "VAR := 1\ntest lint: deps\n\tcargo test\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("docs")).unwrap();
+        std::fs::write(root.join("docs/arch.md"), "").unwrap();
+        let content = "\
+# Rules
+
+See [architecture](docs/arch.md#layers), [gone](docs/missing.md) and [site](https://example.com).
+
+- `kayfabe worktree create <name>` and `kayfabe status`
+- `kayfabe worktree prune` then `kayfabe sync`
+- Run `make test && make check`
+
+```bash
+make lint
+npm run build
+```
+
+```markdown
+- [ ] Migrations apply: `make migrate`
+```
+";
+        std::fs::write(root.join(".windsurfrules"), content).unwrap();
+
+        let validator = validator(root);
+        let files = validator.files();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].kind, FileKind::WindsurfRules);
+
+        let messages: Vec<(Option<usize>, String)> = validator
+            .validate(&files[0], content)
+            .into_iter()
+            .map(|finding| (finding.line, finding.message))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                (Some(3), "broken link to docs/missing.md".to_string()),
+                (
+                    Some(6),
+                    "`kayfabe worktree prune` is not a kayfabe command".to_string()
+                ),
+                (
+                    Some(6),
+                    "`kayfabe sync` is not a kayfabe command".to_string()
+                ),
+                (
+                    Some(7),
+                    "`make check` but the Makefile has no such target".to_string()
+                ),
+                (
+                    Some(11),
+                    "`npm run build` but there is no package.json".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_npm_scripts() {
+        let scripts = |package| npm_scripts(package).map(|scripts| scripts.into_iter().collect());
+        assert_eq!(
+            scripts(r#"{"scripts":{"build":"tsc","lint":"eslint . --rule '{}'"},"x":{}}"#),
+            Some(vec!["build".to_string(), "lint".to_string()])
+        );
+        assert_eq!(
+            scripts("{\n  \"name\": \"app\"\n}\n"),
+            Some(Vec::<String>::new())
+        );
+        assert_eq!(scripts("{ not json"), None);
+
+        // An unreadable package.json isn't held against the agent file
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("package.json"), "{ not json").unwrap();
+        assert!(validator(temp_dir.path())
+            .check_command("npm run build", true)
+            .is_none());
+    }
+
+    #[test]
+    fn test_subagent_frontmatter() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join(".claude/agents")).unwrap();
+        std::fs::create_dir_all(root.join(".claude/commands")).unwrap();
+        std::fs::write(root.join(".claude/commands/commit.md"), "Commit\n").unwrap();
+
+        let validator = validator(root);
+        let file = AgentFile {
+            path: PathBuf::from(".claude/agents/locator.md"),
+            agent: "Claude",
+            kind: FileKind::Subagent,
+        };
+
+        let findings = validator.validate(
+            &file,
+            "---\nname: Code Locator\ncolour: blue\n---\n\nThen run `/commit` or `/review`.\n",
+        );
+        let messages: Vec<(Severity, &str)> = findings
+            .iter()
+            .map(|finding| (finding.severity, finding.message.as_str()))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                (Severity::Error, "frontmatter is missing `description`"),
+                (
+                    Severity::Error,
+                    "subagent name `Code Locator` must be lowercase letters, digits and -"
+                ),
+                (Severity::Warning, "unknown frontmatter key `colour`"),
+                (
+                    Severity::Warning,
+                    "`/review` is not in .claude/commands (fine if it is a user or built-in command)"
+                ),
+            ]
+        );
+
+        let findings = validator.validate(&file, "---\nname: locator\n");
+        assert_eq!(
+            findings[0].message,
+            "frontmatter is never closed with ---".to_string()
+        );
+    }
+}
@@ -0,0 +1,187 @@
+use crate::agents::{AgentRunner, AgentStatus};
+use crate::config::{GlobalConfig, ProjectConfig, WorktreeMetadata};
+use crate::error::{KayfabeError, Result};
+use crate::git::GitRepo;
+use crate::provision;
+use console::style;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Prompt used by `agent run` when `--prompt` isn't given, relative to the
+/// worktree
+pub const TASK_FILE: &str = ".kayfabe/task.md";
+
+pub struct AgentCommand;
+
+impl AgentCommand {
+    pub fn run(worktree: String, agent: Option<String>, prompt: Option<PathBuf>) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let config = ProjectConfig::load(repo.layout_root())?;
+        let global = GlobalConfig::load()?;
+
+        let agent = agent
+            .or_else(|| {
+                config
+                    .agents
+                    .preferred
+                    .as_ref()
+                    .and_then(|preferred| preferred.first().cloned())
+            })
+            .unwrap_or_else(|| "claude".to_string());
+        let cli = AgentRunner::resolve(&agent, &global).ok_or_else(|| {
+            KayfabeError::Other(format!(
+                "Unknown agent: {} (define [agent_cli.{}] in the global config)",
+                agent, agent
+            ))
+        })?;
+
+        let wt_path = repo.find_worktree(&worktree)?;
+        let mut metadata = WorktreeMetadata::load(repo.layout_root(), &worktree)?;
+        if let Some(process) = &metadata.agent {
+            if AgentRunner::status(process) == AgentStatus::Running {
+                return Err(KayfabeError::Other(format!(
+                    "{} is already running in {} (pid {}); stop it with `kayfabe agent stop {}`",
+                    process.agent, worktree, process.pid, worktree
+                )));
+            }
+        }
+
+        let prompt_file = prompt.unwrap_or_else(|| wt_path.join(TASK_FILE));
+        let prompt = std::fs::read_to_string(&prompt_file).map_err(|e| {
+            KayfabeError::Other(format!(
+                "Failed to read prompt {}: {} (pass --prompt <file>)",
+                prompt_file.display(),
+                e
+            ))
+        })?;
+
+        let env = provision::worktree_env(repo.layout_root(), &worktree, &config)?;
+        let process = AgentRunner::spawn(
+            repo.layout_root(),
+            &worktree,
+            &wt_path,
+            &agent,
+            &cli,
+            prompt.trim(),
+            &env,
+        )?;
+
+        println!(
+            "{} Started {} in {} (pid {})",
+            style("✓").green(),
+            style(&agent).cyan(),
+            style(&worktree).cyan(),
+            process.pid
+        );
+        println!("  Log: {}", style(process.log.display()).dim());
+
+        metadata.agent = Some(process);
+        metadata.save(repo.layout_root())?;
+
+        Ok(())
+    }
+
+    pub fn ps() -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+
+        let agents: Vec<_> = WorktreeMetadata::list(repo.layout_root())?
+            .into_iter()
+            .filter_map(|metadata| metadata.agent.map(|agent| (metadata.name, agent)))
+            .collect();
+
+        if agents.is_empty() {
+            println!("{}", style("No agents have been started").yellow());
+            return Ok(());
+        }
+
+        println!(
+            "  {}",
+            style(format!(
+                "{:<24} {:<10} {:>8}  {:<14} {}",
+                "WORKTREE", "AGENT", "PID", "STATUS", "STARTED"
+            ))
+            .dim()
+        );
+        for (worktree, process) in agents {
+            let status = match AgentRunner::status(&process) {
+                AgentStatus::Running => style(format!("{:<14}", "running")).green(),
+                AgentStatus::Exited(Some(0)) => style(format!("{:<14}", "exited (0)")).dim(),
+                AgentStatus::Exited(Some(code)) => {
+                    style(format!("{:<14}", format!("exited ({})", code))).red()
+                }
+                AgentStatus::Exited(None) => style(format!("{:<14}", "stopped")).yellow(),
+            };
+            let started = chrono::DateTime::from_timestamp(process.started_at as i64, 0)
+                .map(|time| {
+                    time.with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string()
+                })
+                .unwrap_or_default();
+
+            println!(
+                "  {} {:<10} {:>8}  {} {}",
+                style(format!("{:<24}", worktree)).cyan(),
+                process.agent,
+                process.pid,
+                status,
+                started
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Print the agent's log, and with `follow` keep printing until it exits
+    pub fn logs(worktree: String, follow: bool) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let process = WorktreeMetadata::load(repo.layout_root(), &worktree)?
+            .agent
+            .ok_or_else(|| KayfabeError::Other(format!("No agent has run in {}", worktree)))?;
+
+        let mut file = std::fs::File::open(&process.log)?;
+        let mut stdout = std::io::stdout();
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            file.read_to_end(&mut buf)?;
+            stdout.write_all(&buf)?;
+            stdout.flush()?;
+
+            if !follow || (buf.is_empty() && AgentRunner::status(&process) != AgentStatus::Running)
+            {
+                break;
+            }
+            if buf.is_empty() {
+                std::thread::sleep(Duration::from_millis(250));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn stop(worktree: String) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let process = WorktreeMetadata::load(repo.layout_root(), &worktree)?
+            .agent
+            .ok_or_else(|| KayfabeError::Other(format!("No agent has run in {}", worktree)))?;
+
+        if AgentRunner::stop(&process)? {
+            println!(
+                "{} Stopped {} in {}",
+                style("✓").green(),
+                style(&process.agent).cyan(),
+                style(&worktree).cyan()
+            );
+        } else {
+            println!("{} is not running in {}", process.agent, worktree);
+        }
+
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
+use crate::agents::validate::{Severity, Validator};
 use crate::config::GlobalConfig;
-use crate::error::Result;
+use crate::error::{KayfabeError, Result};
 use crate::git::GitRepo;
 use console::style;
 
@@ -35,54 +36,85 @@ impl ConfigCommand {
         Ok(())
     }
 
-    pub fn validate() -> Result<()> {
+    /// Check every agent file in the checkout. `kayfabe_commands` are the
+    /// CLI's subcommands, to catch references to renamed ones. Fails on
+    /// errors, and on warnings too with `strict`.
+    pub fn validate(strict: bool, kayfabe_commands: Vec<(String, Vec<String>)>) -> Result<()> {
         let current_dir = std::env::current_dir()?;
         let repo = GitRepo::discover(&current_dir)?;
-
-        let files = vec![(".windsurfrules", "Windsurf")];
+        let validator = Validator::new(repo.root(), kayfabe_commands);
 
         println!("{}", style("Validating agent configurations...").bold());
         println!();
 
-        let mut all_valid = true;
+        let files = validator.files();
+        if files.is_empty() {
+            println!("{}", style("No agent configuration files found").yellow());
+            return Ok(());
+        }
 
-        for (file, agent_name) in files {
-            let path = repo.root().join(file);
-            if path.exists() {
-                let content = std::fs::read_to_string(&path)?;
-                if content.trim().is_empty() {
-                    println!(
-                        "{} {} - empty file",
-                        style("✗").red(),
-                        style(agent_name).cyan()
-                    );
-                    all_valid = false;
-                } else {
-                    println!(
-                        "{} {} - valid",
-                        style("✓").green(),
-                        style(agent_name).cyan()
-                    );
-                }
+        let (mut errors, mut warnings) = (0, 0);
+        for file in &files {
+            let content = std::fs::read_to_string(repo.root().join(&file.path))?;
+            let findings = validator.validate(file, &content);
+            let mark = if findings.iter().any(|f| f.severity == Severity::Error) {
+                style("✗").red()
+            } else if findings.is_empty() {
+                style("✓").green()
             } else {
-                println!(
-                    "{} {} - not found",
-                    style("⚠").yellow(),
-                    style(agent_name).dim()
-                );
+                style("⚠").yellow()
+            };
+            println!(
+                "{} {} {}",
+                mark,
+                style(file.path.display()).cyan(),
+                style(format!("({})", file.agent)).dim()
+            );
+
+            for finding in findings {
+                let location = finding
+                    .line
+                    .map(|line| format!("line {}: ", line))
+                    .unwrap_or_default();
+                match finding.severity {
+                    Severity::Error => {
+                        errors += 1;
+                        println!(
+                            "    {} {}{}",
+                            style("error").red(),
+                            location,
+                            finding.message
+                        );
+                    }
+                    Severity::Warning => {
+                        warnings += 1;
+                        println!(
+                            "    {} {}{}",
+                            style("warning").yellow(),
+                            location,
+                            finding.message
+                        );
+                    }
+                }
             }
         }
 
         println!();
-        if all_valid {
+        if errors == 0 && warnings == 0 {
             println!("{}", style("All configurations are valid!").green().bold());
-        } else {
-            println!(
-                "{}",
-                style("Some configurations need attention").yellow().bold()
-            );
+            return Ok(());
         }
 
+        let summary = format!(
+            "{} files checked: {} errors, {} warnings",
+            files.len(),
+            errors,
+            warnings
+        );
+        if errors > 0 || strict {
+            return Err(KayfabeError::Other(summary));
+        }
+        println!("{}", style(summary).yellow().bold());
         Ok(())
     }
 
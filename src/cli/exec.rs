@@ -0,0 +1,257 @@
+use crate::config::ProjectConfig;
+use crate::error::{KayfabeError, Result};
+use crate::git::GitRepo;
+use crate::provision;
+use console::{style, Color};
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+
+const PREFIX_COLORS: &[Color] = &[
+    Color::Cyan,
+    Color::Magenta,
+    Color::Yellow,
+    Color::Green,
+    Color::Blue,
+    Color::Red,
+];
+
+pub struct ExecCommand;
+
+impl ExecCommand {
+    pub fn execute(
+        name: Option<String>,
+        all: bool,
+        filter: Option<String>,
+        parallel: bool,
+        command: Vec<String>,
+    ) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let config = ProjectConfig::load(repo.layout_root())?;
+
+        if command.is_empty() {
+            return Err(KayfabeError::Other(
+                "No command given (usage: kayfabe exec <name> -- <cmd>)".to_string(),
+            ));
+        }
+
+        if !all {
+            let name = name
+                .ok_or_else(|| KayfabeError::Other("Pass a worktree name or --all".to_string()))?;
+            let wt_path = repo.find_worktree(&name)?;
+            let status = Self::command(&repo, &config, &name, &wt_path, &command)?.status()?;
+            if !status.success() {
+                return Err(KayfabeError::Other(format!(
+                    "Command failed in {} ({})",
+                    name, status
+                )));
+            }
+            return Ok(());
+        }
+
+        let worktrees = repo
+            .list_worktrees()?
+            .into_iter()
+            .map(|path| (repo.worktree_name(&path), path))
+            .collect();
+        let targets = Self::targets(worktrees, filter.as_deref())?;
+
+        if targets.is_empty() {
+            println!("{}", style("No worktrees match").yellow());
+            return Ok(());
+        }
+
+        let width = targets
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0);
+        let mut jobs = Vec::new();
+        for (i, (name, path)) in targets.iter().enumerate() {
+            let color = PREFIX_COLORS[i % PREFIX_COLORS.len()];
+            let prefix = style(format!("[{:<width$}]", name, width = width))
+                .fg(color)
+                .to_string();
+            let cmd = Self::command(&repo, &config, name, path, &command)?;
+            jobs.push((name.clone(), prefix, cmd));
+        }
+
+        let results: Vec<(String, Result<ExitStatus>)> = if parallel {
+            std::thread::scope(|scope| {
+                let handles: Vec<_> = jobs
+                    .into_iter()
+                    .map(|(name, prefix, cmd)| {
+                        let handle = scope.spawn(move || Self::run_prefixed(cmd, &prefix));
+                        (name, handle)
+                    })
+                    .collect();
+
+                handles
+                    .into_iter()
+                    .map(|(name, handle)| {
+                        let result = handle.join().unwrap_or_else(|_| {
+                            Err(KayfabeError::Other("worker thread panicked".to_string()))
+                        });
+                        (name, result)
+                    })
+                    .collect()
+            })
+        } else {
+            jobs.into_iter()
+                .map(|(name, prefix, cmd)| (name, Self::run_prefixed(cmd, &prefix)))
+                .collect()
+        };
+
+        Self::summarize(&results)
+    }
+
+    /// Worktrees to run in with `--all`, keeping those whose name matches
+    /// `filter`
+    fn targets(
+        worktrees: Vec<(String, PathBuf)>,
+        filter: Option<&str>,
+    ) -> Result<Vec<(String, PathBuf)>> {
+        let pattern = filter
+            .map(glob::Pattern::new)
+            .transpose()
+            .map_err(|e| KayfabeError::Other(format!("Invalid filter: {}", e)))?;
+
+        Ok(worktrees
+            .into_iter()
+            .filter(|(name, _)| match &pattern {
+                Some(pattern) => pattern.matches(name),
+                None => true,
+            })
+            .collect())
+    }
+
+    fn command(
+        repo: &GitRepo,
+        config: &ProjectConfig,
+        name: &str,
+        wt_path: &Path,
+        command: &[String],
+    ) -> Result<Command> {
+        let env = provision::worktree_env(repo.layout_root(), name, config)?;
+        let mut cmd = Command::new(&command[0]);
+        cmd.args(&command[1..]).current_dir(wt_path).envs(env);
+        Ok(cmd)
+    }
+
+    /// Run with stdout/stderr piped and every line tagged with `prefix`, so
+    /// output from parallel runs can be told apart
+    fn run_prefixed(mut cmd: Command, prefix: &str) -> Result<ExitStatus> {
+        let program = cmd.get_program().to_string_lossy().to_string();
+        let mut child = cmd
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| KayfabeError::Other(format!("Failed to run {}: {}", program, e)))?;
+
+        let stdout = child.stdout.take().unwrap();
+        let stderr = child.stderr.take().unwrap();
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| Self::forward(stdout, prefix, false));
+            scope.spawn(|| Self::forward(stderr, prefix, true));
+        });
+
+        Ok(child.wait()?)
+    }
+
+    fn forward(stream: impl Read, prefix: &str, is_stderr: bool) {
+        for line in BufReader::new(stream).lines().map_while(|line| line.ok()) {
+            if is_stderr {
+                eprintln!("{} {}", prefix, line);
+            } else {
+                println!("{} {}", prefix, line);
+            }
+        }
+    }
+
+    fn summarize(results: &[(String, Result<ExitStatus>)]) -> Result<()> {
+        println!();
+        println!("{}", style("Summary:").bold());
+
+        let mut failed = 0;
+        for (name, result) in results {
+            match result {
+                Ok(status) if status.success() => {
+                    println!("  {} {}", style("✓").green(), style(name).cyan());
+                }
+                Ok(status) => {
+                    failed += 1;
+                    println!("  {} {} ({})", style("✗").red(), style(name).cyan(), status);
+                }
+                Err(e) => {
+                    failed += 1;
+                    println!("  {} {} ({})", style("✗").red(), style(name).cyan(), e);
+                }
+            }
+        }
+
+        if failed > 0 {
+            return Err(KayfabeError::Other(format!(
+                "Command failed in {} of {} worktrees",
+                failed,
+                results.len()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+
+    #[test]
+    fn test_targets() {
+        let worktrees: Vec<(String, PathBuf)> = ["main", "wt/feat-login", "wt/fix/typo"]
+            .iter()
+            .map(|path| {
+                let name = path.trim_start_matches("wt/").to_string();
+                (name, PathBuf::from("/repo").join(path))
+            })
+            .collect();
+        let names = |filter| -> Vec<String> {
+            ExecCommand::targets(worktrees.clone(), filter)
+                .unwrap()
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect()
+        };
+
+        assert_eq!(names(None), vec!["main", "feat-login", "fix/typo"]);
+        assert_eq!(names(Some("feat-*")), vec!["feat-login"]);
+        assert!(names(Some("release-*")).is_empty());
+        assert_eq!(
+            ExecCommand::targets(worktrees.clone(), Some("fix/*")).unwrap(),
+            vec![("fix/typo".to_string(), PathBuf::from("/repo/wt/fix/typo"))]
+        );
+        assert!(ExecCommand::targets(worktrees, Some("[")).is_err());
+    }
+
+    #[test]
+    fn test_summarize_counts_failures() {
+        let ok = || Ok(ExitStatus::from_raw(0));
+        assert!(
+            ExecCommand::summarize(&[("a".to_string(), ok()), ("b".to_string(), ok())]).is_ok()
+        );
+
+        let results = vec![
+            ("a".to_string(), ok()),
+            ("b".to_string(), Ok(ExitStatus::from_raw(1 << 8))),
+            (
+                "c".to_string(),
+                Err(KayfabeError::Other("Failed to run nope".to_string())),
+            ),
+        ];
+        let err = ExecCommand::summarize(&results).unwrap_err();
+        assert_eq!(err.to_string(), "Command failed in 2 of 3 worktrees");
+    }
+}
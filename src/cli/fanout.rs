@@ -0,0 +1,285 @@
+use super::agent::TASK_FILE;
+use super::{AgentCommand, WorktreeCommand};
+use crate::agents::{AgentRunner, AgentStatus};
+use crate::config::{ProjectConfig, WorktreeMetadata};
+use crate::error::{KayfabeError, Result};
+use crate::git::GitRepo;
+use crate::provision;
+use console::style;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+pub struct FanoutCommand;
+
+impl FanoutCommand {
+    /// Create `<task>-1..<task>-N` from the same commit, each with the task
+    /// prompt in `.kayfabe/task.md`, and optionally start an agent in each
+    pub fn fanout(
+        task: String,
+        count: usize,
+        base: Option<String>,
+        prompt: Option<PathBuf>,
+        message: Option<String>,
+        agents: Vec<String>,
+    ) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+
+        if count == 0 {
+            return Err(KayfabeError::Other("-n must be at least 1".to_string()));
+        }
+        let names: Vec<String> = (1..=count).map(|i| format!("{}-{}", task, i)).collect();
+        for name in &names {
+            if repo.worktree_path(name).exists() || repo.branch_exists(name)? {
+                return Err(KayfabeError::WorktreeExists(name.clone()));
+            }
+        }
+
+        let task_prompt = match (prompt, message) {
+            (Some(file), _) => std::fs::read_to_string(&file).map_err(|e| {
+                KayfabeError::Other(format!("Failed to read {}: {}", file.display(), e))
+            })?,
+            (None, Some(message)) => format!("{}\n", message.trim_end()),
+            (None, None) if agents.is_empty() => format!("# {}\n", task),
+            (None, None) => {
+                return Err(KayfabeError::Other(
+                    "Agents need a task: pass --prompt <file> or --message <text>".to_string(),
+                ))
+            }
+        };
+
+        // Pin the base to a commit so every copy starts from the same tree
+        let base = base.unwrap_or_else(|| {
+            repo.get_default_branch()
+                .unwrap_or_else(|_| "main".to_string())
+        });
+        let _ = repo.fetch();
+        let base_commit = repo
+            .resolve_commit(&base)
+            .map_err(|e| KayfabeError::Other(format!("Failed to resolve base {}: {}", base, e)))?;
+
+        for name in &names {
+            WorktreeCommand::create(name.clone(), Some(base_commit.clone()), None, true)?;
+
+            let wt_path = repo.worktree_path(name);
+            let task_file = wt_path.join(TASK_FILE);
+            if let Some(parent) = task_file.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&task_file, &task_prompt)?;
+            repo.add_exclude(&format!("/{}", TASK_FILE))?;
+
+            let mut metadata = WorktreeMetadata::load(repo.layout_root(), name)?;
+            metadata.base = Some(base_commit.clone());
+            metadata.save(repo.layout_root())?;
+            println!();
+        }
+
+        for (name, agent) in names.iter().zip(agents.iter().cycle()) {
+            if let Err(e) = AgentCommand::run(name.clone(), Some(agent.clone()), None) {
+                println!(
+                    "{} {} not started in {}: {}",
+                    style("✗").red(),
+                    agent,
+                    style(name).cyan(),
+                    e
+                );
+            }
+        }
+
+        println!(
+            "{} Created {} worktrees for {} from {}",
+            style("✓").green(),
+            count,
+            style(&task).cyan(),
+            &base_commit[..7]
+        );
+        println!(
+            "  {}",
+            style(format!(
+                "Compare them with: kayfabe worktree compare {}",
+                task
+            ))
+            .dim()
+        );
+
+        Ok(())
+    }
+
+    /// Show commits, diffstat and agent status of each `<task>-N` worktree,
+    /// and with `test` run a command in all of them side by side
+    pub fn compare(task: String, test: Option<String>) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let config = ProjectConfig::load(repo.layout_root())?;
+
+        let mut targets: Vec<(usize, String, PathBuf)> = repo
+            .list_worktrees()?
+            .into_iter()
+            .filter_map(|path| {
+                let name = repo.worktree_name(&path);
+                let index = name.strip_prefix(&task)?.strip_prefix('-')?.parse().ok()?;
+                Some((index, name, path))
+            })
+            .collect();
+        targets.sort();
+
+        if targets.is_empty() {
+            return Err(KayfabeError::Other(format!(
+                "No worktrees named {}-N (create them with `kayfabe worktree fanout {}`)",
+                task, task
+            )));
+        }
+
+        // Build commands up front; GitRepo can't be shared with the threads
+        let mut jobs = Vec::new();
+        if let Some(test) = &test {
+            for (_, name, path) in &targets {
+                let env = provision::worktree_env(repo.layout_root(), name, &config)?;
+                let log_dir = AgentRunner::log_dir(repo.layout_root(), name);
+                std::fs::create_dir_all(&log_dir)?;
+                let log = log_dir.join("compare-test.log");
+                let log_file = std::fs::File::create(&log)?;
+
+                let mut command = Command::new("sh");
+                command
+                    .args(["-c", test])
+                    .current_dir(path)
+                    .envs(env)
+                    .stdin(Stdio::null())
+                    .stdout(log_file.try_clone()?)
+                    .stderr(log_file);
+                jobs.push(command);
+            }
+            println!(
+                "{}",
+                style(format!(
+                    "Running `{}` in {} worktrees...",
+                    test,
+                    targets.len()
+                ))
+                .cyan()
+            );
+        }
+        let results: Vec<Option<(bool, Duration)>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = jobs
+                .into_iter()
+                .map(|mut command| {
+                    scope.spawn(move || {
+                        let started = Instant::now();
+                        let passed = command.status().is_ok_and(|status| status.success());
+                        (passed, started.elapsed())
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().ok()).collect()
+        });
+
+        println!(
+            "  {}",
+            style(format!(
+                "{:<20} {:<10} {:<8} {:>7}  {:<22} {}",
+                "WORKTREE", "AGENT", "STATUS", "COMMITS", "CHANGES", "TESTS"
+            ))
+            .dim()
+        );
+        for (i, (_, name, path)) in targets.iter().enumerate() {
+            let metadata = WorktreeMetadata::load(repo.layout_root(), name)?;
+            let base = match &metadata.base {
+                Some(base) => base.clone(),
+                None => {
+                    GitRepo::git_output(path, &["merge-base", "HEAD", &repo.get_default_branch()?])?
+                }
+            };
+
+            let commits =
+                GitRepo::git_output(path, &["rev-list", "--count", &format!("{}..HEAD", base)])?;
+            let (files, insertions, deletions) =
+                parse_shortstat(&GitRepo::git_output(path, &["diff", "--shortstat", &base])?);
+            let untracked =
+                GitRepo::git_output(path, &["ls-files", "--others", "--exclude-standard"])?
+                    .lines()
+                    .count();
+            let mut changes = format!("{} files +{} -{}", files, insertions, deletions);
+            if untracked > 0 {
+                changes.push_str(&format!(" ?{}", untracked));
+            }
+
+            let (agent, status) = match &metadata.agent {
+                Some(process) => (
+                    process.agent.as_str(),
+                    match AgentRunner::status(process) {
+                        AgentStatus::Running => style(format!("{:<8}", "running")).green(),
+                        AgentStatus::Exited(Some(0)) => style(format!("{:<8}", "done")).dim(),
+                        AgentStatus::Exited(_) => style(format!("{:<8}", "failed")).red(),
+                    },
+                ),
+                None => ("-", style(format!("{:<8}", "-")).dim()),
+            };
+
+            let tests = match results.get(i) {
+                Some(Some((true, elapsed))) => {
+                    style(format!("passed ({}s)", elapsed.as_secs())).green()
+                }
+                Some(Some((false, elapsed))) => {
+                    style(format!("failed ({}s)", elapsed.as_secs())).red()
+                }
+                Some(None) => style("error".to_string()).red(),
+                None => style("-".to_string()).dim(),
+            };
+
+            println!(
+                "  {} {:<10} {} {:>7}  {:<22} {}",
+                style(format!("{:<20}", name)).cyan(),
+                agent,
+                status,
+                commits,
+                changes,
+                tests
+            );
+        }
+
+        if test.is_some() {
+            println!(
+                "  {}",
+                style("Test output: .kayfabe/logs/<worktree>/compare-test.log").dim()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse `git diff --shortstat` output into (files, insertions, deletions)
+fn parse_shortstat(stat: &str) -> (usize, usize, usize) {
+    let mut counts = (0, 0, 0);
+    for part in stat.split(',') {
+        let mut words = part.split_whitespace();
+        let Some(Ok(count)) = words.next().map(str::parse) else {
+            continue;
+        };
+        match words.next() {
+            Some(word) if word.starts_with("file") => counts.0 = count,
+            Some(word) if word.starts_with("insertion") => counts.1 = count,
+            Some(word) if word.starts_with("deletion") => counts.2 = count,
+            _ => {}
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_shortstat() {
+        assert_eq!(
+            parse_shortstat(" 3 files changed, 40 insertions(+), 2 deletions(-)"),
+            (3, 40, 2)
+        );
+        assert_eq!(parse_shortstat(" 1 file changed, 1 deletion(-)"), (1, 0, 1));
+        assert_eq!(parse_shortstat(""), (0, 0, 0));
+    }
+}
@@ -0,0 +1,206 @@
+use super::AgentCommand;
+use crate::agents::AgentRunner;
+use crate::error::{KayfabeError, Result};
+use crate::git::GitRepo;
+use crate::handoff::{GitSnapshot, Handoff, HandoffStore};
+use console::style;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+pub struct HandoffCommand;
+
+impl HandoffCommand {
+    /// Write a handoff for a worktree (default: the current one). The body
+    /// comes from `from` (`-` for stdin), or else is left as section headings.
+    pub fn create(
+        description: String,
+        worktree: Option<String>,
+        from: Option<PathBuf>,
+    ) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let (name, wt_path) = Self::resolve_worktree(&repo, worktree)?;
+
+        let body = match from {
+            Some(path) if path.as_os_str() == "-" => {
+                let mut body = String::new();
+                std::io::stdin().read_to_string(&mut body)?;
+                body
+            }
+            Some(path) => std::fs::read_to_string(&path).map_err(|e| {
+                KayfabeError::Other(format!("Failed to read {}: {}", path.display(), e))
+            })?,
+            None => String::new(),
+        };
+
+        let wt_repo = GitRepo::discover(&wt_path)?;
+        let snapshot = GitSnapshot {
+            branch: wt_repo.current_branch(),
+            commit: wt_repo.resolve_commit("HEAD").ok(),
+            status: GitRepo::git_output(&wt_path, &["status", "--short"]).unwrap_or_default(),
+        };
+        let repository = repo
+            .layout_root()
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let store = HandoffStore::new(repo.layout_root());
+        let path = store.create(&name, &repository, &description, &body, &snapshot)?;
+
+        println!(
+            "{} Created handoff for {}",
+            style("✓").green(),
+            style(&name).cyan()
+        );
+        println!("  {}", style(path.display()).dim());
+        if body.trim().is_empty() {
+            println!(
+                "  {}",
+                style("Fill in the sections before handing off").dim()
+            );
+        }
+        println!(
+            "  {}",
+            style(format!("Resume with: kayfabe handoff resume {}", name)).dim()
+        );
+
+        Ok(())
+    }
+
+    pub fn list(worktree: Option<String>) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let store = HandoffStore::new(repo.layout_root());
+        let handoffs = store.list(worktree.as_deref())?;
+
+        if handoffs.is_empty() {
+            println!("{}", style("No handoffs found").yellow());
+            return Ok(());
+        }
+
+        println!(
+            "  {}",
+            style(format!(
+                "{:<16}  {:<20} {:<8} {}",
+                "DATE", "WORKTREE", "COMMIT", "TOPIC"
+            ))
+            .dim()
+        );
+        for handoff in &handoffs {
+            let date = chrono::DateTime::parse_from_rfc3339(&handoff.date)
+                .map(|date| date.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|_| handoff.date.clone());
+            let commit = handoff
+                .commit
+                .as_deref()
+                .map(|commit| &commit[..commit.len().min(7)])
+                .unwrap_or("-");
+
+            println!(
+                "  {:<16}  {} {:<8} {}",
+                date,
+                style(format!("{:<20}", handoff.worktree)).cyan(),
+                commit,
+                handoff.topic
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Print a handoff (default: the current worktree's latest)
+    pub fn show(id: Option<String>) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let store = HandoffStore::new(repo.layout_root());
+
+        let handoff = match id {
+            Some(id) => store.find(&id)?,
+            None => {
+                let (name, _) = Self::resolve_worktree(&repo, None)?;
+                Self::latest(&store, &name)?
+            }
+        };
+
+        print!("{}", std::fs::read_to_string(&handoff.path)?);
+        Ok(())
+    }
+
+    /// Print a prompt that primes a new agent session with the worktree's
+    /// latest handoff, or with `agent`, start that agent on it
+    pub fn resume(worktree: Option<String>, agent: Option<String>) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let (name, wt_path) = Self::resolve_worktree(&repo, worktree)?;
+        let store = HandoffStore::new(repo.layout_root());
+        let handoff = Self::latest(&store, &name)?;
+        let prompt = Self::resume_prompt(&handoff, &wt_path)?;
+
+        match agent {
+            Some(agent) => {
+                let log_dir = AgentRunner::log_dir(repo.layout_root(), &name);
+                std::fs::create_dir_all(&log_dir)?;
+                let prompt_file = log_dir.join("resume.md");
+                std::fs::write(&prompt_file, prompt)?;
+                AgentCommand::run(name, Some(agent), Some(prompt_file))
+            }
+            None => {
+                print!("{}", prompt);
+                Ok(())
+            }
+        }
+    }
+
+    fn resume_prompt(handoff: &Handoff, wt_path: &Path) -> Result<String> {
+        let content = std::fs::read_to_string(&handoff.path)?;
+
+        let mut prompt = format!(
+            "Resume the work described in the handoff document {}.",
+            handoff.path.display()
+        );
+        if let Some(commit) = &handoff.commit {
+            let since = GitRepo::git_output(
+                wt_path,
+                &["rev-list", "--count", &format!("{}..HEAD", commit)],
+            )
+            .unwrap_or_default();
+            if !since.is_empty() && since != "0" {
+                prompt.push_str(&format!(
+                    " It was written at commit {} and there have been {} commits since, so check what changed first.",
+                    &commit[..commit.len().min(7)],
+                    since
+                ));
+            }
+        }
+        prompt.push_str(
+            " Read the files it references, verify the current state, then continue with its action items.\n\n",
+        );
+        prompt.push_str(&content);
+
+        Ok(prompt)
+    }
+
+    fn latest(store: &HandoffStore, worktree: &str) -> Result<Handoff> {
+        store.latest(worktree)?.ok_or_else(|| {
+            KayfabeError::Other(format!(
+                "No handoffs for {} (create one with `kayfabe handoff create`)",
+                worktree
+            ))
+        })
+    }
+
+    fn resolve_worktree(repo: &GitRepo, name: Option<String>) -> Result<(String, PathBuf)> {
+        match name {
+            Some(name) => {
+                let path = repo.find_worktree(&name)?;
+                Ok((name, path))
+            }
+            None => {
+                let path = repo.root().to_path_buf();
+                let name = repo.worktree_name(&path);
+                Ok((name, path))
+            }
+        }
+    }
+}
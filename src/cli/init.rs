@@ -1,3 +1,6 @@
+use crate::agents::managed;
+use crate::agents::manifest::{EntryKind, ManifestEntry, KAYFABE};
+use crate::agents::{Manifest, Scope};
 use crate::config::ProjectConfig;
 use crate::error::Result;
 use crate::git::GitRepo;
@@ -34,6 +37,17 @@ impl InitCommand {
         println!("{}", style("Creating project configuration...").cyan());
         let config = ProjectConfig::default();
         config.save(repo.layout_root())?;
+        // Recorded so `kayfabe uninstall --layout` knows it may remove it
+        let path = ProjectConfig::path(repo.layout_root());
+        let mut manifest = Manifest::load(Manifest::project_path(repo.layout_root()))?;
+        manifest.record(ManifestEntry::new(
+            path.clone(),
+            KAYFABE,
+            Scope::Project,
+            EntryKind::File,
+            managed::hash(&std::fs::read_to_string(&path)?),
+        ));
+        manifest.save()?;
         println!("{}", style("✓ Created .kayfabe/config.toml").green());
 
         println!(
@@ -1,9 +1,12 @@
-use crate::agents::AgentInstaller;
-use crate::config::GlobalConfig;
-use crate::error::Result;
+use super::PromptsCommand;
+use crate::agents::installer::AGENTS;
+use crate::agents::{AgentInstaller, Manifest, Scope};
+use crate::config::{AgentConfig, GlobalConfig};
+use crate::error::{KayfabeError, Result};
+use crate::git::GitRepo;
 use console::style;
 use dialoguer::MultiSelect;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub struct InstallCommand;
 
@@ -12,53 +15,126 @@ impl InstallCommand {
         target_dir: Option<PathBuf>,
         non_interactive: bool,
         agents: Option<Vec<String>>,
+        scope: String,
+        dry_run: bool,
     ) -> Result<()> {
-        let target = target_dir.unwrap_or_else(|| std::env::current_dir().unwrap());
-
-        let available_agents = vec!["windsurf"];
+        let scope = Scope::parse(&scope)?;
+        let target = match target_dir {
+            Some(dir) => dir,
+            None => std::env::current_dir()?,
+        };
+        let mut config = GlobalConfig::load()?;
 
         let selected_agents = if non_interactive {
             // In non-interactive mode, use provided agents or default to all
             if let Some(agent_list) = agents {
                 agent_list
             } else {
-                available_agents.iter().map(|s| s.to_string()).collect()
+                AGENTS.iter().map(|s| s.to_string()).collect()
             }
         } else {
-            // Interactive mode
-            let defaults = vec![true];
+            // Interactive mode, starting from the agents already enabled
+            let defaults: Vec<bool> = AGENTS
+                .iter()
+                .map(|agent| config.agents.get(*agent).is_some_and(|agent| agent.enabled))
+                .collect();
             let selections = MultiSelect::new()
-                .with_prompt("Select agents to install globally")
-                .items(&available_agents)
+                .with_prompt(format!("Select agents to install ({} scope)", scope))
+                .items(AGENTS)
                 .defaults(&defaults)
                 .interact()?;
 
             selections
                 .iter()
-                .map(|&idx| available_agents[idx].to_string())
+                .map(|&idx| AGENTS[idx].to_string())
                 .collect()
         };
 
-        // Install selected agents globally
+        let (root, manifest_path) = Self::resolve(scope, &target)?;
+        let mut manifest = Manifest::load(manifest_path)?;
+        let vars = PromptsCommand::variables_at(&root)?;
+        let installer = AgentInstaller::new(scope, root, &vars, dry_run);
         for agent in &selected_agents {
-            AgentInstaller::install_global(agent, &target)?;
+            installer.install(agent, &mut manifest)?;
+        }
+        if dry_run {
+            return Ok(());
         }
+        manifest.save()?;
 
         // Update global config - mark agents as enabled
-        let mut config = GlobalConfig::load()?;
         for agent in &selected_agents {
-            if let Some(agent_config) = config.agents.get_mut(agent) {
-                agent_config.enabled = true;
-            }
+            let location = AgentInstaller::rules_location(agent, scope)
+                .unwrap_or_default()
+                .to_string();
+            let agent_config = config
+                .agents
+                .entry(agent.clone())
+                .or_insert_with(|| AgentConfig {
+                    enabled: true,
+                    template: "default".to_string(),
+                    location: location.clone(),
+                });
+            agent_config.enabled = true;
+            agent_config.location = location;
         }
         GlobalConfig::save(&config)?;
 
         println!(
             "{}",
-            style("✓ Global agent installation complete!")
+            style(format!("✓ Agent installation complete ({} scope)!", scope))
                 .green()
                 .bold()
         );
+        println!(
+            "{}",
+            style(format!("Recorded in {}", manifest.path().display())).dim()
+        );
         Ok(())
     }
+
+    /// The manifests covering `target`: the user one, and the one for the
+    /// layout containing `target`, limited to `scope` if given
+    pub fn manifests(target: &Path, scope: Option<Scope>) -> Result<Vec<Manifest>> {
+        let mut manifests = Vec::new();
+        if matches!(scope, None | Some(Scope::User)) {
+            manifests.push(Manifest::load(Manifest::user_path()?)?);
+        }
+        if scope != Some(Scope::User) {
+            let layout_root = match GitRepo::discover(target) {
+                Ok(repo) => repo.layout_root().to_path_buf(),
+                Err(_) => target.canonicalize()?,
+            };
+            manifests.push(Manifest::load(Manifest::project_path(&layout_root))?);
+        }
+        Ok(manifests)
+    }
+
+    /// Root directory to install under and the manifest to record it in.
+    /// Project scope is the main checkout of a worktree layout; worktree
+    /// scope is the checkout containing `target`.
+    pub fn resolve(scope: Scope, target: &Path) -> Result<(PathBuf, PathBuf)> {
+        if scope == Scope::User {
+            let home = dirs::home_dir()
+                .ok_or_else(|| KayfabeError::Other("Could not find home directory".to_string()))?;
+            return Ok((home, Manifest::user_path()?));
+        }
+
+        match GitRepo::discover(target) {
+            Ok(repo) => {
+                let root = if scope == Scope::Project && repo.is_worktree_layout() {
+                    repo.layout_root().join("main")
+                } else {
+                    repo.root().to_path_buf()
+                };
+                Ok((root, Manifest::project_path(repo.layout_root())))
+            }
+            // Outside a repository the directory itself is both
+            Err(_) => {
+                let dir = target.canonicalize()?;
+                let manifest = Manifest::project_path(&dir);
+                Ok((dir, manifest))
+            }
+        }
+    }
 }
@@ -1,11 +1,37 @@
+pub mod agent;
 pub mod config;
+pub mod exec;
+pub mod fanout;
+pub mod handoff;
 pub mod init;
 pub mod install;
+pub mod pick;
+pub mod plan;
+pub mod prompts;
+pub mod rules;
+pub mod session;
+pub mod shell;
 pub mod status;
+pub mod thoughts;
+pub mod uninstall;
+pub mod upgrade;
 pub mod worktree;
 
+pub use agent::AgentCommand;
 pub use config::ConfigCommand;
+pub use exec::ExecCommand;
+pub use fanout::FanoutCommand;
+pub use handoff::HandoffCommand;
 pub use init::InitCommand;
 pub use install::InstallCommand;
+pub use pick::PickCommand;
+pub use plan::PlanCommand;
+pub use prompts::PromptsCommand;
+pub use rules::RulesCommand;
+pub use session::SessionCommand;
+pub use shell::ShellCommand;
 pub use status::StatusCommand;
+pub use thoughts::ThoughtsCommand;
+pub use uninstall::UninstallCommand;
+pub use upgrade::UpgradeAgentsCommand;
 pub use worktree::WorktreeCommand;
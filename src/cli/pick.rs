@@ -0,0 +1,225 @@
+use crate::cli::{ShellCommand, WorktreeCommand};
+use crate::config::GlobalConfig;
+use crate::error::{KayfabeError, Result};
+use crate::git::{GitRepo, Worktree, WorktreeInfo};
+use crate::ide::IDELauncher;
+use console::style;
+use dialoguer::{Confirm, FuzzySelect, Select};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Open,
+    Cd,
+    Sync,
+    Remove,
+    Cancel,
+}
+
+impl Action {
+    const ALL: [Action; 5] = [
+        Action::Open,
+        Action::Cd,
+        Action::Sync,
+        Action::Remove,
+        Action::Cancel,
+    ];
+
+    /// Actions offered for `info`. The anchor can't be removed and is the
+    /// source for synced files.
+    fn for_worktree(info: &WorktreeInfo) -> Vec<Action> {
+        Action::ALL
+            .into_iter()
+            .filter(|action| !(info.is_main && matches!(action, Action::Remove | Action::Sync)))
+            .collect()
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Action::Open => "Open in IDE",
+            Action::Cd => "cd",
+            Action::Sync => "Sync local files",
+            Action::Remove => "Remove",
+            Action::Cancel => "Cancel",
+        }
+    }
+}
+
+pub struct PickCommand;
+
+impl PickCommand {
+    pub fn execute() -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let base_branch = repo
+            .get_default_branch()
+            .unwrap_or_else(|_| "main".to_string());
+
+        let worktrees = repo
+            .list_worktrees()?
+            .iter()
+            .map(|path| Worktree::get_info(path, &base_branch))
+            .collect::<Result<Vec<_>>>()?;
+
+        if worktrees.is_empty() {
+            println!("{}", style("No worktrees found").yellow());
+            return Ok(());
+        }
+
+        let names: Vec<String> = worktrees
+            .iter()
+            .map(|info| repo.worktree_name(&info.path))
+            .collect();
+        let width = names.iter().map(String::len).max().unwrap_or(0);
+        let items: Vec<String> = worktrees
+            .iter()
+            .zip(&names)
+            .map(|(info, name)| Self::describe(info, name, width))
+            .collect();
+
+        // FuzzySelect highlights matches itself, so items stay unstyled
+        let Some(index) = FuzzySelect::new()
+            .with_prompt("Worktree")
+            .items(&items)
+            .default(0)
+            .interact_opt()?
+        else {
+            return Ok(());
+        };
+
+        let info = &worktrees[index];
+        let name = names[index].clone();
+
+        let actions = Action::for_worktree(info);
+        let labels: Vec<&str> = actions.iter().map(Action::label).collect();
+        let Some(choice) = Select::new()
+            .with_prompt(format!("{}", style(&name).cyan()))
+            .items(&labels)
+            .default(0)
+            .interact_opt()?
+        else {
+            return Ok(());
+        };
+
+        match actions[choice] {
+            Action::Open => {
+                let global = GlobalConfig::load()?;
+                let available = IDELauncher::detect_available(&global);
+                if available.is_empty() {
+                    return Err(KayfabeError::Other(
+                        "No known IDE found on PATH (see [ide.<name>] in the global config)"
+                            .to_string(),
+                    ));
+                }
+                let labels: Vec<&str> = available.iter().map(|ide| ide.name.as_str()).collect();
+                let default = labels
+                    .iter()
+                    .position(|name| *name == global.defaults.ide)
+                    .unwrap_or(0);
+                let choice = Select::new()
+                    .with_prompt("IDE")
+                    .items(&labels)
+                    .default(default)
+                    .interact()?;
+                WorktreeCommand::open(vec![name], Some(labels[choice].to_string()), None, true)?;
+            }
+            Action::Cd => {
+                let handled = ShellCommand::request_cd(&info.path)?;
+                if !handled {
+                    println!("{}", info.path.display());
+                    println!(
+                        "{}",
+                        style("Run kayfabe through `kf` (see `kayfabe shell-init`) to cd automatically")
+                            .dim()
+                    );
+                }
+            }
+            Action::Sync => WorktreeCommand::refresh_files(Some(name), false, false)?,
+            Action::Remove => {
+                let confirmed = Confirm::new()
+                    .with_prompt(format!("Remove worktree {}?", name))
+                    .default(false)
+                    .interact()?;
+                if confirmed {
+                    WorktreeCommand::remove(name, false)?;
+                }
+            }
+            Action::Cancel => {}
+        }
+
+        Ok(())
+    }
+
+    fn describe(info: &WorktreeInfo, name: &str, width: usize) -> String {
+        let branch = info.branch.as_deref().unwrap_or("(detached)");
+        let age = match info.staleness_days {
+            _ if info.is_main => "anchor".to_string(),
+            Some(days) => format!("{:.0}d", days),
+            None => "new".to_string(),
+        };
+        let dirty = if info.safety.has_uncommitted_changes {
+            "dirty"
+        } else {
+            ""
+        };
+
+        format!(
+            "{:<width$}  {:<30} {:>6}  {}",
+            name,
+            branch,
+            age,
+            dirty,
+            width = width
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::SafetyCheck;
+    use std::path::PathBuf;
+
+    fn info(path: &str, branch: Option<&str>, is_main: bool, days: Option<f64>) -> WorktreeInfo {
+        WorktreeInfo {
+            path: PathBuf::from(path),
+            branch: branch.map(str::to_string),
+            is_main,
+            staleness_days: days,
+            safety: SafetyCheck {
+                has_uncommitted_changes: false,
+                has_unmerged_commits: false,
+                is_safe_to_remove: true,
+            },
+        }
+    }
+
+    #[test]
+    fn test_anchor_cannot_be_removed_or_synced() {
+        let anchor = info("/repo/main", Some("main"), true, None);
+        assert_eq!(
+            Action::for_worktree(&anchor),
+            vec![Action::Open, Action::Cd, Action::Cancel]
+        );
+        let feature = info("/repo/wt/feature", Some("feature"), false, Some(2.0));
+        assert_eq!(Action::for_worktree(&feature), Action::ALL.to_vec());
+    }
+
+    #[test]
+    fn test_describe() {
+        let anchor = info("/repo/main", Some("main"), true, Some(40.0));
+        assert_eq!(
+            PickCommand::describe(&anchor, "main", 7).trim_end(),
+            format!("main     {:<30} anchor", "main")
+        );
+
+        let mut detached = info("/repo/wt/spike", None, false, Some(3.4));
+        detached.safety.has_uncommitted_changes = true;
+        assert_eq!(
+            PickCommand::describe(&detached, "spike", 7),
+            format!("spike    {:<30}     3d  dirty", "(detached)")
+        );
+
+        let new = info("/repo/wt/fresh", Some("fresh"), false, None);
+        assert!(PickCommand::describe(&new, "fresh", 5).ends_with("   new  "));
+    }
+}
@@ -0,0 +1,204 @@
+use crate::config::WorktreeMetadata;
+use crate::error::{KayfabeError, Result};
+use crate::git::GitRepo;
+use crate::plan::{Plan, PlanStore};
+use console::style;
+use std::path::Path;
+
+pub struct PlanCommand;
+
+impl PlanCommand {
+    pub fn create(
+        title: String,
+        ticket: Option<String>,
+        phases: usize,
+        worktree: Option<String>,
+    ) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let store = PlanStore::new(repo.layout_root());
+        if let Some(name) = &worktree {
+            repo.find_worktree(name)?;
+        }
+
+        let path = store.create(&title, ticket.as_deref(), phases)?;
+        println!("{} Created plan", style("✓").green());
+        println!("  {}", style(path.display()).dim());
+
+        if let Some(worktree) = worktree {
+            Self::link_path(&repo, &path, &worktree)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn list() -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let plans = PlanStore::new(repo.layout_root()).list()?;
+
+        if plans.is_empty() {
+            println!("{}", style("No plans found").yellow());
+            return Ok(());
+        }
+
+        let metadata = WorktreeMetadata::list(repo.layout_root())?;
+        println!(
+            "  {}",
+            style(format!(
+                "{:<40} {:<8} {:<16} {}",
+                "PLAN", "PHASES", "WORKTREE", "TITLE"
+            ))
+            .dim()
+        );
+        for plan in plans {
+            let file_name = plan.path.file_name().map(|name| name.to_string_lossy());
+            let worktrees: Vec<&str> = metadata
+                .iter()
+                .filter(|metadata| metadata.plan.as_deref() == file_name.as_deref())
+                .map(|metadata| metadata.name.as_str())
+                .collect();
+            let worktrees = if worktrees.is_empty() {
+                "-".to_string()
+            } else {
+                worktrees.join(",")
+            };
+
+            println!(
+                "  {} {:<8} {:<16} {}",
+                style(format!("{:<40}", plan.id())).cyan(),
+                format!("{}/{}", plan.completed_phases(), plan.phases.len()),
+                worktrees,
+                plan.title
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Per-phase completion of a plan (default: the current worktree's)
+    pub fn status(plan: Option<String>) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let store = PlanStore::new(repo.layout_root());
+
+        let plan = match plan {
+            Some(id) => store.find(&id)?,
+            None => {
+                let name = repo.worktree_name(repo.root());
+                Self::linked(repo.layout_root(), &name)?.ok_or_else(|| {
+                    KayfabeError::Other(format!(
+                        "No plan linked to {} (use `kayfabe plan link <plan>`)",
+                        name
+                    ))
+                })?
+            }
+        };
+
+        println!("{}", style(&plan.title).bold());
+        println!("  {}", style(plan.path.display()).dim());
+        println!();
+
+        if plan.phases.is_empty() {
+            println!("{}", style("No `## Phase N:` sections found").yellow());
+            return Ok(());
+        }
+
+        for (i, phase) in plan.phases.iter().enumerate() {
+            let (mark, counts) = if phase.is_complete() {
+                (
+                    style("✓").green(),
+                    style(format!("{}/{}", phase.done, phase.total)).green(),
+                )
+            } else if phase.done > 0 {
+                (
+                    style("◐").yellow(),
+                    style(format!("{}/{}", phase.done, phase.total)).yellow(),
+                )
+            } else {
+                (
+                    style("○").dim(),
+                    style(format!("{}/{}", phase.done, phase.total)).dim(),
+                )
+            };
+            println!("  {} Phase {}: {}  {}", mark, i + 1, phase.name, counts);
+        }
+        println!();
+        println!(
+            "{} of {} phases complete",
+            plan.completed_phases(),
+            plan.phases.len()
+        );
+
+        Ok(())
+    }
+
+    /// Record that `worktree` (default: the current one) implements `plan`
+    pub fn link(plan: String, worktree: Option<String>) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let plan = PlanStore::new(repo.layout_root()).find(&plan)?;
+
+        let worktree = match worktree {
+            Some(name) => {
+                repo.find_worktree(&name)?;
+                name
+            }
+            None => repo.worktree_name(repo.root()),
+        };
+
+        Self::link_path(&repo, &plan.path, &worktree)
+    }
+
+    /// The plan linked to a worktree, if it still exists
+    pub fn linked(layout_root: &Path, worktree: &str) -> Result<Option<Plan>> {
+        let metadata = WorktreeMetadata::load(layout_root, worktree)?;
+        let Some(file_name) = metadata.plan else {
+            return Ok(None);
+        };
+
+        let path = PlanStore::new(layout_root).path(&file_name);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        Plan::load(&path).map(Some)
+    }
+
+    /// Compact per-phase progress, e.g. `P1 ✓ P2 1/3 P3 0/2`
+    pub fn progress(plan: &Plan) -> String {
+        plan.phases
+            .iter()
+            .enumerate()
+            .map(|(i, phase)| {
+                if phase.is_complete() {
+                    style(format!("P{} ✓", i + 1)).green().to_string()
+                } else if phase.done > 0 {
+                    style(format!("P{} {}/{}", i + 1, phase.done, phase.total))
+                        .yellow()
+                        .to_string()
+                } else {
+                    style(format!("P{} {}/{}", i + 1, phase.done, phase.total))
+                        .dim()
+                        .to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn link_path(repo: &GitRepo, plan: &Path, worktree: &str) -> Result<()> {
+        let file_name = PlanStore::new(repo.layout_root()).file_name(plan)?;
+
+        let mut metadata = WorktreeMetadata::load(repo.layout_root(), worktree)?;
+        metadata.plan = Some(file_name.clone());
+        metadata.save(repo.layout_root())?;
+
+        println!(
+            "{} Linked {} to {}",
+            style("✓").green(),
+            style(&file_name).cyan(),
+            style(worktree).cyan()
+        );
+        Ok(())
+    }
+}
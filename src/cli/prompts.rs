@@ -0,0 +1,250 @@
+use super::InstallCommand;
+use crate::agents::managed;
+use crate::agents::manifest::{EntryKind, ManifestEntry};
+use crate::agents::prompts::variables;
+use crate::agents::{Manifest, ProjectContext, PromptLibrary, PromptSource, Scope};
+use crate::config::{ProjectConfig, PromptsConfig};
+use crate::error::{KayfabeError, Result};
+use crate::git::GitRepo;
+use crate::ui::Output;
+use console::style;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub struct PromptsCommand;
+
+impl PromptsCommand {
+    pub fn list() -> Result<()> {
+        let library = PromptLibrary::load()?;
+
+        println!(
+            "  {}",
+            style(format!(
+                "{:<28} {:<9} {:<9} {}",
+                "NAME", "KIND", "SOURCE", "DESCRIPTION"
+            ))
+            .dim()
+        );
+        for prompt in library.list()? {
+            let source = match prompt.source {
+                PromptSource::Builtin => style(prompt.source.to_string()).dim(),
+                _ => style(prompt.source.to_string()).yellow(),
+            };
+            let description = prompt.description.unwrap_or_default();
+            let description: String = if description.chars().count() > 60 {
+                format!("{}…", description.chars().take(59).collect::<String>())
+            } else {
+                description
+            };
+            println!(
+                "  {} {:<9} {} {}",
+                style(format!("{:<28}", prompt.name)).cyan(),
+                prompt.kind.to_string(),
+                style(format!("{:<9}", source)),
+                description
+            );
+        }
+        println!();
+        println!(
+            "Overrides: {}",
+            style(library.override_dir().display()).dim()
+        );
+
+        Ok(())
+    }
+
+    /// Print a prompt rendered for the current checkout, or its template
+    pub fn show(name: String, raw: bool) -> Result<()> {
+        let prompt = PromptLibrary::load()?.get(&name)?;
+        if raw {
+            print!("{}", prompt.template);
+        } else {
+            print!("{}", prompt.render(&Self::variables()?)?);
+        }
+        Ok(())
+    }
+
+    pub fn vars() -> Result<()> {
+        for (key, value) in Self::variables()? {
+            println!("  {} {}", style(format!("{:<16}", key)).cyan(), value);
+        }
+        Ok(())
+    }
+
+    /// Render prompts into `.claude/commands/` and `.claude/agents/` of the
+    /// current checkout (or `dir`). Existing files are kept unless `force`.
+    pub fn install(names: Vec<String>, dir: Option<PathBuf>, force: bool) -> Result<()> {
+        let library = PromptLibrary::load()?;
+        let vars = Self::variables()?;
+        let current_dir = std::env::current_dir()?;
+        let (root, manifest_path) = InstallCommand::resolve(Scope::Worktree, &current_dir)?;
+        let mut manifest = Manifest::load(manifest_path)?;
+        // Absolute, as recorded in the manifest
+        let dir = current_dir.join(dir.unwrap_or_else(|| root.join(".claude")));
+
+        let prompts = if names.is_empty() {
+            library.list()?
+        } else {
+            names
+                .iter()
+                .map(|name| library.get(name))
+                .collect::<Result<Vec<_>>>()?
+        };
+
+        let (mut written, mut skipped) = (0, 0);
+        for prompt in prompts {
+            let path = dir
+                .join(prompt.kind.install_dir())
+                .join(format!("{}.md", prompt.name));
+            if path.exists() && !force {
+                println!(
+                    "  {} {} {}",
+                    style("-").dim(),
+                    prompt.name,
+                    style("(exists, use --force to overwrite)").dim()
+                );
+                skipped += 1;
+                continue;
+            }
+
+            let content = prompt.render(&vars)?;
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, &content)?;
+            manifest.record(ManifestEntry::new(
+                path.clone(),
+                "claude",
+                Scope::Worktree,
+                EntryKind::File,
+                managed::hash(&content),
+            ));
+            println!(
+                "  {} {} {}",
+                style("✓").green(),
+                prompt.name,
+                style(format!("({})", prompt.kind)).dim()
+            );
+            written += 1;
+        }
+
+        manifest.save()?;
+
+        println!();
+        println!(
+            "{} Installed {} prompts into {}{}",
+            style("✓").green(),
+            written,
+            style(dir.display()).cyan(),
+            if skipped > 0 {
+                format!(", skipped {}", skipped)
+            } else {
+                String::new()
+            }
+        );
+        Ok(())
+    }
+
+    /// Open a user copy of a prompt, starting from the built-in if there is
+    /// no copy yet
+    pub fn edit(name: String) -> Result<()> {
+        let library = PromptLibrary::load()?;
+        let name = name.trim_end_matches(".md");
+        let path = library.override_path(name);
+
+        if !path.exists() {
+            let template = PromptLibrary::builtin(name)
+                .map(|prompt| prompt.template)
+                .unwrap_or_else(|| "---\ndescription: \n---\n\n".to_string());
+            std::fs::create_dir_all(library.override_dir())?;
+            std::fs::write(&path, template)?;
+            println!(
+                "{} Created {}",
+                style("✓").green(),
+                style(path.display()).cyan()
+            );
+        }
+
+        let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vim".to_string());
+        std::process::Command::new(editor).arg(&path).status()?;
+        Ok(())
+    }
+
+    /// Show how user overrides differ from the built-ins they shadow
+    pub fn diff(name: Option<String>) -> Result<()> {
+        let library = PromptLibrary::load()?;
+        let prompts = match name {
+            Some(name) => {
+                let prompt = library.get(&name)?;
+                if prompt.source != PromptSource::Override {
+                    return Err(KayfabeError::Other(format!(
+                        "{} is not overriding a built-in prompt",
+                        prompt.name
+                    )));
+                }
+                vec![prompt]
+            }
+            None => library
+                .list()?
+                .into_iter()
+                .filter(|prompt| prompt.source == PromptSource::Override)
+                .collect(),
+        };
+
+        if prompts.is_empty() {
+            println!("{}", style("No overridden prompts").yellow());
+            return Ok(());
+        }
+
+        for prompt in prompts {
+            let Some(builtin) = PromptLibrary::builtin(&prompt.name) else {
+                continue;
+            };
+            if builtin.template == prompt.template {
+                println!(
+                    "{} {}",
+                    style(&prompt.name).cyan(),
+                    style("(same as built-in)").dim()
+                );
+                continue;
+            }
+
+            Output::diff(
+                &builtin.template,
+                &prompt.template,
+                &format!("builtin/{}.md", prompt.name),
+                &format!("override/{}.md", prompt.name),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Variables for the current checkout, or defaults outside a repository
+    pub fn variables() -> Result<BTreeMap<String, String>> {
+        Self::variables_at(&std::env::current_dir()?)
+    }
+
+    /// Variables for the project containing `dir`
+    pub fn variables_at(dir: &Path) -> Result<BTreeMap<String, String>> {
+        let (context, config) = match GitRepo::discover(dir) {
+            Ok(repo) => {
+                let config = ProjectConfig::load(repo.layout_root())?;
+                let mut context = ProjectContext::detect(repo.root());
+                // Without a manifest name, the checkout directory is `main`
+                // or the worktree name; the layout root names the project
+                let dir_name = repo.root().file_name().map(|name| name.to_string_lossy());
+                if let Some(name) = config.project.name {
+                    context.name = name;
+                } else if dir_name.as_deref() == Some(context.name.as_str()) {
+                    if let Some(name) = repo.layout_root().file_name() {
+                        context.name = name.to_string_lossy().to_string();
+                    }
+                }
+                (context, config.prompts)
+            }
+            Err(_) => (ProjectContext::detect(dir), PromptsConfig::default()),
+        };
+        Ok(variables(&context, &config))
+    }
+}
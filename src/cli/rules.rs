@@ -0,0 +1,309 @@
+use super::PromptsCommand;
+use crate::agents::managed;
+use crate::agents::rules::{self, RuleSet, RULES_DIR, TARGETS_FILE};
+use crate::config::{GlobalConfig, ProjectConfig};
+use crate::error::{KayfabeError, Result};
+use crate::git::GitRepo;
+use console::style;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Starting point when there is no existing rule file to import
+const STARTER: &str = "\
+# {{ project_name }}
+
+## Commands
+{% if build_cmd %}
+- Build: `{{ build_cmd }}`{% endif %}{% if test_cmd %}
+- Test: `{{ test_cmd }}`{% endif %}{% if lint_cmd %}
+- Lint: `{{ lint_cmd }}`{% endif %}
+
+## Conventions
+
+- Follow the existing style of the surrounding code
+{% if agent == \"claude\" %}
+## Claude
+
+- Use the codebase-locator subagent before broad searches
+{% endif %}
+";
+
+pub struct RulesCommand;
+
+impl RulesCommand {
+    /// Create `.kayfabe/rules/`, importing every hand-written rule file
+    /// found so nothing is lost on the first sync, and record the agents to
+    /// generate for in `agents.toml`
+    pub fn init() -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let dir = RuleSet::dir(repo.root());
+        if dir.exists() {
+            return Err(KayfabeError::Other(format!(
+                "{} already exists",
+                dir.display()
+            )));
+        }
+        std::fs::create_dir_all(&dir)?;
+
+        // Agents sharing the same rules (e.g. AGENTS.md copied to CLAUDE.md)
+        // get one imported file between them
+        let mut imported: Vec<(Vec<String>, Vec<PathBuf>, String)> = Vec::new();
+        for agent in rules::known_agents() {
+            let Some(location) = rules::default_location(agent) else {
+                continue;
+            };
+            let path = repo.root().join(location);
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let content = Self::without_install_block(&content)?;
+            if rules::is_generated(&content) || content.trim().is_empty() {
+                continue;
+            }
+            let body = rules::body(&content).trim().to_string();
+            match imported
+                .iter_mut()
+                .find(|(_, _, existing)| *existing == body)
+            {
+                Some((agents, sources, _)) => {
+                    agents.push(agent.to_string());
+                    sources.push(path);
+                }
+                None => imported.push((vec![agent.to_string()], vec![path], body)),
+            }
+        }
+
+        if imported.is_empty() {
+            std::fs::write(dir.join("project.md"), STARTER)?;
+            println!(
+                "{} Created {}",
+                style("✓").green(),
+                style(format!("{}/project.md", RULES_DIR)).cyan()
+            );
+        }
+        let single = imported.len() == 1;
+        for (agents, sources, body) in &imported {
+            // Imported rules may contain text that looks like Tera syntax
+            let raw = format!("{{% raw %}}\n{}\n{{% endraw %}}\n", body);
+            let (name, content) = if single {
+                ("project".to_string(), raw)
+            } else {
+                (
+                    agents[0].clone(),
+                    format!("---\nagents: [{}]\n---\n\n{}", agents.join(", "), raw),
+                )
+            };
+            std::fs::write(dir.join(format!("{}.md", name)), content)?;
+            let sources: Vec<String> = sources
+                .iter()
+                .map(|source| {
+                    source
+                        .strip_prefix(repo.root())
+                        .unwrap_or(source)
+                        .display()
+                        .to_string()
+                })
+                .collect();
+            println!(
+                "{} Imported {} into {}",
+                style("✓").green(),
+                style(sources.join(", ")).cyan(),
+                style(format!("{}/{}.md", RULES_DIR, name)).cyan()
+            );
+        }
+
+        let agents = Self::initial_targets(&repo, &imported)?;
+        // Fixed now so rendering doesn't depend on where the repo is cloned
+        let project_name = PromptsCommand::variables()?
+            .remove("project_name")
+            .unwrap_or_default();
+        let vars = BTreeMap::from([("project_name".to_string(), project_name)]);
+        RuleSet::write_targets(repo.root(), &agents, &vars)?;
+        println!(
+            "{} Generating for {} (listed in {})",
+            style("✓").green(),
+            agents.join(", "),
+            style(format!("{}/{}", RULES_DIR, TARGETS_FILE)).cyan()
+        );
+        println!(
+            "Edit them, commit {}, then run {} to generate each agent's rule file.",
+            RULES_DIR,
+            style("kayfabe rules sync --force").cyan()
+        );
+
+        Ok(())
+    }
+
+    /// `[rules] agents` if set, else the agents enabled in the global config
+    /// and any whose rules were imported, else every agent rules can be
+    /// generated for
+    fn initial_targets(
+        repo: &GitRepo,
+        imported: &[(Vec<String>, Vec<PathBuf>, String)],
+    ) -> Result<Vec<String>> {
+        if let Some(agents) = ProjectConfig::load(repo.layout_root())?.rules.agents {
+            return Ok(agents);
+        }
+
+        let global = GlobalConfig::load()?;
+        let mut agents: Vec<String> = rules::known_agents()
+            .filter(|agent| {
+                global
+                    .agents
+                    .get(*agent)
+                    .is_some_and(|config| config.enabled)
+                    || imported
+                        .iter()
+                        .any(|(agents, _, _)| agents.iter().any(|a| a == agent))
+            })
+            .map(str::to_string)
+            .collect();
+        if agents.is_empty() {
+            agents = rules::known_agents().map(str::to_string).collect();
+        }
+        Ok(agents)
+    }
+
+    /// Regenerate every target agent's rule file. Files that were not
+    /// generated by kayfabe are left alone unless `force`. The block
+    /// `kayfabe install` adds is kept at the end of the file.
+    pub fn sync(force: bool) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let rule_set = RuleSet::load(repo.root())?;
+        let vars = RuleSet::variables(repo.root())?;
+
+        let mut skipped = 0;
+        for (agent, location) in Self::targets(&repo)? {
+            let path = repo.root().join(&location);
+            let content = rule_set.render(&agent, &vars)?;
+            let existing = std::fs::read_to_string(&path).ok();
+            let ours = existing
+                .as_deref()
+                .map(Self::without_install_block)
+                .transpose()?;
+
+            if ours.as_deref() == Some(content.as_str()) {
+                println!(
+                    "  {} {} {}",
+                    style("✓").green(),
+                    location.display(),
+                    style("(up to date)").dim()
+                );
+                continue;
+            }
+            if let Some(ours) = &ours {
+                let hand_written = !ours.trim().is_empty() && !rules::is_generated(ours);
+                if hand_written && !force {
+                    println!(
+                        "  {} {} {}",
+                        style("⚠").yellow(),
+                        location.display(),
+                        style("(not generated by kayfabe; use --force to replace)").dim()
+                    );
+                    skipped += 1;
+                    continue;
+                }
+            }
+
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, managed::keep_block(existing.as_deref(), &content)?)?;
+            println!(
+                "  {} {} {}",
+                style("✓").green(),
+                location.display(),
+                style(format!("({})", agent)).dim()
+            );
+        }
+
+        if skipped > 0 {
+            println!();
+            println!(
+                "{}",
+                style(format!("Skipped {} hand-written files", skipped)).yellow()
+            );
+        }
+        Ok(())
+    }
+
+    /// Fail if any generated file is missing or differs from its sources
+    pub fn check() -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let rule_set = RuleSet::load(repo.root())?;
+        let vars = RuleSet::variables(repo.root())?;
+
+        let mut stale = 0;
+        for (agent, location) in Self::targets(&repo)? {
+            let content = rule_set.render(&agent, &vars)?;
+            let status = match std::fs::read_to_string(repo.root().join(&location)) {
+                Ok(existing) if Self::without_install_block(&existing)? == content => None,
+                Ok(_) => Some("out of date"),
+                Err(_) => Some("missing"),
+            };
+
+            match status {
+                None => println!("  {} {}", style("✓").green(), location.display()),
+                Some(status) => {
+                    stale += 1;
+                    println!(
+                        "  {} {} {}",
+                        style("✗").red(),
+                        location.display(),
+                        style(format!("({})", status)).red()
+                    );
+                }
+            }
+        }
+
+        if stale > 0 {
+            return Err(KayfabeError::Other(format!(
+                "{} rule files are out of date with {} (run `kayfabe rules sync`)",
+                stale, RULES_DIR
+            )));
+        }
+        Ok(())
+    }
+
+    /// A rule file without the block `kayfabe install` adds to it, which
+    /// `sync` keeps as it is
+    fn without_install_block(content: &str) -> Result<String> {
+        Ok(managed::without_block(content)?
+            .map(|(rest, _)| rest)
+            .unwrap_or_else(|| content.to_string()))
+    }
+
+    /// (agent, rule file relative to the checkout) for each agent listed in
+    /// `.kayfabe/rules/agents.toml`, or in `[rules] agents` without one
+    fn targets(repo: &GitRepo) -> Result<Vec<(String, PathBuf)>> {
+        let agents = match RuleSet::targets(repo.root())? {
+            Some(agents) => agents,
+            None => ProjectConfig::load(repo.layout_root())?
+                .rules
+                .agents
+                .ok_or_else(|| {
+                    KayfabeError::Other(format!(
+                        "No agents to generate rules for (list them in {}/{})",
+                        RULES_DIR, TARGETS_FILE
+                    ))
+                })?,
+        };
+
+        agents
+            .into_iter()
+            .map(|agent| {
+                let location = rules::default_location(&agent).ok_or_else(|| {
+                    KayfabeError::Other(format!(
+                        "No rule file known for agent {} (expected one of: {})",
+                        agent,
+                        rules::known_agents().collect::<Vec<_>>().join(", ")
+                    ))
+                })?;
+                Ok((agent, PathBuf::from(location)))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,95 @@
+use crate::config::ProjectConfig;
+use crate::error::Result;
+use crate::git::GitRepo;
+use crate::provision;
+use crate::session::SessionManager;
+use console::style;
+
+pub struct SessionCommand;
+
+impl SessionCommand {
+    /// Attach to a worktree's session (default: the current worktree),
+    /// creating it with the configured panes if it isn't running
+    pub fn attach(name: Option<String>, detach: bool) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let config = ProjectConfig::load(repo.layout_root())?;
+        let manager = SessionManager::new(&config.session, repo.layout_root());
+
+        let (name, wt_path) = match name {
+            Some(name) => {
+                let path = repo.find_worktree(&name)?;
+                (name, path)
+            }
+            None => {
+                let path = repo.root().to_path_buf();
+                let name = repo.worktree_name(&path);
+                (name, path)
+            }
+        };
+        let env = provision::worktree_env(repo.layout_root(), &name, &config)?;
+
+        if detach {
+            let session = manager.session_name(&name);
+            if manager.start(&name, &wt_path, &env)? {
+                println!(
+                    "{} Started session {}",
+                    style("✓").green(),
+                    style(session).cyan()
+                );
+            } else {
+                println!("Session {} is already running", style(session).cyan());
+            }
+            return Ok(());
+        }
+
+        manager.attach(&name, &wt_path, &env)
+    }
+
+    pub fn list() -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let config = ProjectConfig::load(repo.layout_root())?;
+        let manager = SessionManager::new(&config.session, repo.layout_root());
+        let running = manager.running()?;
+
+        let mut any = false;
+        for wt_path in repo.list_worktrees()? {
+            let name = repo.worktree_name(&wt_path);
+            let session = manager.session_name(&name);
+            if running.contains(&session) {
+                any = true;
+                println!(
+                    "  {} → {}",
+                    style(&name).cyan(),
+                    style(format!("session {}", session)).dim()
+                );
+            }
+        }
+
+        if !any {
+            println!("{}", style("No worktree sessions running").yellow());
+        }
+
+        Ok(())
+    }
+
+    pub fn kill(name: String) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let config = ProjectConfig::load(repo.layout_root())?;
+        let manager = SessionManager::new(&config.session, repo.layout_root());
+
+        if manager.kill(&name)? {
+            println!(
+                "{} Killed session {}",
+                style("✓").green(),
+                style(manager.session_name(&name)).cyan()
+            );
+        } else {
+            println!("No session running for {}", style(&name).cyan());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+use crate::error::{KayfabeError, Result};
+use crate::git::GitRepo;
+use clap_complete::env::Shells;
+use clap_complete::{CompletionCandidate, Shell};
+use std::io::Write;
+use std::path::Path;
+
+/// Environment variable the shell sets when asking kayfabe for completions
+pub const COMPLETE_VAR: &str = "COMPLETE";
+
+/// File the `kf` wrapper passes in; a directory written here is `cd`'d into
+/// once kayfabe exits
+pub const CD_FILE_VAR: &str = "KAYFABE_CD_FILE";
+
+const POSIX_WRAPPER: &str = r#"kf() {
+    if [ "$1" = "cd" ]; then
+        shift
+        local dir
+        dir="$(command kayfabe worktree path "$@")" && cd "$dir"
+    else
+        local cd_file rc
+        cd_file="$(mktemp)"
+        KAYFABE_CD_FILE="$cd_file" command kayfabe "$@"
+        rc=$?
+        [ -s "$cd_file" ] && cd "$(cat "$cd_file")"
+        rm -f "$cd_file"
+        return $rc
+    fi
+}
+"#;
+
+const FISH_WRAPPER: &str = r#"function kf
+    if test "$argv[1]" = cd
+        set -l dir (command kayfabe worktree path $argv[2..-1]); and cd $dir
+    else
+        set -l cd_file (mktemp)
+        KAYFABE_CD_FILE=$cd_file command kayfabe $argv
+        set -l rc $status
+        test -s $cd_file; and cd (cat $cd_file)
+        rm -f $cd_file
+        return $rc
+    end
+end
+"#;
+
+pub struct ShellCommand;
+
+impl ShellCommand {
+    /// Print the script that registers dynamic completions for `kayfabe`
+    pub fn completions(shell: Shell) -> Result<()> {
+        Self::write_registration(&shell.to_string(), "kayfabe", &mut std::io::stdout())
+    }
+
+    /// Print the `kf` wrapper (which can `cd` the calling shell) along with
+    /// completions for both `kayfabe` and `kf`
+    pub fn init(shell: &str) -> Result<()> {
+        Self::write_init(shell, &mut std::io::stdout())
+    }
+
+    fn write_init(shell: &str, out: &mut impl Write) -> Result<()> {
+        let wrapper = match shell {
+            "bash" | "zsh" => POSIX_WRAPPER,
+            "fish" => FISH_WRAPPER,
+            _ => {
+                return Err(KayfabeError::Other(format!(
+                    "Unsupported shell: {} (expected bash, zsh or fish)",
+                    shell
+                )))
+            }
+        };
+
+        write!(out, "{}", wrapper)?;
+        Self::write_registration(shell, "kayfabe", out)?;
+        Self::write_registration(shell, "kf", out)
+    }
+
+    /// `kayfabe cd` only exists so `kf cd` gets completions; the `kf`
+    /// wrapper handles it before kayfabe is ever called
+    pub fn cd() -> Result<()> {
+        Err(KayfabeError::Other(
+            "kayfabe can't change your shell's directory on its own. \
+             Add `eval \"$(kayfabe shell-init bash)\"` (or zsh/fish) to your shell rc and use `kf cd <name>`"
+                .to_string(),
+        ))
+    }
+
+    /// Ask the `kf` wrapper to change directory once kayfabe exits. Returns
+    /// `false` when kayfabe wasn't started through the wrapper.
+    pub fn request_cd(path: &Path) -> Result<bool> {
+        match std::env::var_os(CD_FILE_VAR) {
+            Some(file) if !file.is_empty() => {
+                std::fs::write(file, path.to_string_lossy().as_bytes())?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Print the current worktree, branch and dirty state for use in PS1.
+    /// Prints nothing outside a worktree layout so it is safe to call from
+    /// every prompt.
+    pub fn prompt(format: &str) -> Result<()> {
+        let Ok(current_dir) = std::env::current_dir() else {
+            return Ok(());
+        };
+        let Ok(repo) = GitRepo::discover(&current_dir) else {
+            return Ok(());
+        };
+        if !repo.is_worktree_layout() {
+            return Ok(());
+        }
+
+        let worktree = repo.worktree_name(repo.root());
+        let branch = repo.current_branch().unwrap_or_default();
+        let dirty = if repo.is_dirty().unwrap_or(false) {
+            "*"
+        } else {
+            ""
+        };
+
+        println!(
+            "{}",
+            format
+                .replace("{worktree}", &worktree)
+                .replace("{branch}", &branch)
+                .replace("{dirty}", dirty)
+        );
+
+        Ok(())
+    }
+
+    /// Completion candidates for arguments that take a worktree name
+    pub fn worktree_candidates() -> Vec<CompletionCandidate> {
+        let names = || -> Result<Vec<String>> {
+            let repo = GitRepo::discover(&std::env::current_dir()?)?;
+            Ok(repo
+                .list_worktrees()?
+                .iter()
+                .map(|path| repo.worktree_name(path))
+                .collect())
+        };
+
+        names()
+            .unwrap_or_default()
+            .into_iter()
+            .map(CompletionCandidate::new)
+            .collect()
+    }
+
+    fn write_registration(shell: &str, bin: &str, out: &mut impl Write) -> Result<()> {
+        let shells = Shells::builtins();
+        let completer = shells.completer(shell).ok_or_else(|| {
+            let supported: Vec<_> = shells.names().collect();
+            KayfabeError::Other(format!(
+                "Unsupported shell: {} (expected one of {})",
+                shell,
+                supported.join(", ")
+            ))
+        })?;
+
+        completer.write_registration(COMPLETE_VAR, bin, bin, "kayfabe", out)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init(shell: &str) -> Result<String> {
+        let mut out = Vec::new();
+        ShellCommand::write_init(shell, &mut out)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_init_bash() {
+        let script = init("bash").unwrap();
+        assert!(script.starts_with(POSIX_WRAPPER));
+        assert!(script.contains("-F _clap_complete_kayfabe kayfabe\n"));
+        assert!(script.contains("-F _clap_complete_kf kf\n"));
+    }
+
+    #[test]
+    fn test_init_zsh() {
+        let script = init("zsh").unwrap();
+        assert!(script.starts_with(POSIX_WRAPPER));
+        assert!(script.contains("compdef _clap_dynamic_completer_kayfabe kayfabe\n"));
+        assert!(script.contains("compdef _clap_dynamic_completer_kf kf\n"));
+    }
+
+    #[test]
+    fn test_init_fish() {
+        let script = init("fish").unwrap();
+        assert!(script.starts_with(FISH_WRAPPER));
+        assert!(script.contains("--command kayfabe --arguments \"(COMPLETE=fish kayfabe -- "));
+        assert!(script.contains("--command kf --arguments \"(COMPLETE=fish kayfabe -- "));
+    }
+
+    #[test]
+    fn test_init_unsupported_shell() {
+        assert!(init("powershell").is_err());
+    }
+}
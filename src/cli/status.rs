@@ -1,11 +1,19 @@
+use super::upgrade::Outdated;
+use super::{InstallCommand, UpgradeAgentsCommand};
+use crate::agents::PromptLibrary;
+use crate::config::ProjectConfig;
 use crate::error::Result;
-use crate::git::GitRepo;
+use crate::git::{DiskUsage, GitRepo};
+use crate::session::SessionManager;
 use console::style;
+use indicatif::HumanBytes;
 
 pub struct StatusCommand;
 
 impl StatusCommand {
-    pub fn execute() -> Result<()> {
+    /// Summarize the repository. Measuring disk usage walks every worktree,
+    /// so it only happens with `size`.
+    pub fn execute(size: bool) -> Result<()> {
         let current_dir = std::env::current_dir()?;
         let repo = GitRepo::discover(&current_dir)?;
 
@@ -27,6 +35,98 @@ impl StatusCommand {
         let worktrees = repo.list_worktrees()?;
         println!("  Worktrees: {}", style(worktrees.len()).cyan());
 
+        let config = ProjectConfig::load(repo.layout_root())?;
+        if size {
+            let artifact_patterns = config.worktree.artifact_patterns();
+            let (mut total, mut artifacts) = (0, 0);
+            for wt_path in &worktrees {
+                let usage = DiskUsage::measure(wt_path, &artifact_patterns)?;
+                total += usage.total;
+                artifacts += usage.artifacts;
+            }
+            println!(
+                "  Disk usage: {} {}",
+                style(HumanBytes(total)).cyan(),
+                style(format!("({} build artifacts)", HumanBytes(artifacts))).dim()
+            );
+        }
+
+        Self::print_agent_files(&repo)?;
+
+        let manager = SessionManager::new(&config.session, repo.layout_root());
+        let running = manager.running()?;
+        let sessions: Vec<String> = worktrees
+            .iter()
+            .map(|path| repo.worktree_name(path))
+            .filter(|name| running.contains(&manager.session_name(name)))
+            .collect();
+        if !sessions.is_empty() {
+            println!("  Sessions: {}", style(sessions.join(", ")).cyan());
+        }
+
+        Ok(())
+    }
+
+    /// Installed agent files this version of kayfabe would write differently
+    fn print_agent_files(repo: &GitRepo) -> Result<()> {
+        // A broken prompt override shouldn't stop the rest of the status
+        let (installed, outdated) = match Self::outdated_agent_files(repo) {
+            Ok(files) => files,
+            Err(e) => {
+                let e = e.to_string();
+                println!(
+                    "  Agent files: {} {}",
+                    style("could not check").red(),
+                    style(format!("({})", e.lines().next().unwrap_or_default().trim())).dim()
+                );
+                return Ok(());
+            }
+        };
+        if installed == 0 {
+            return Ok(());
+        }
+
+        if outdated.is_empty() {
+            println!("  Agent files: {}", style("up to date").green());
+            return Ok(());
+        }
+        println!(
+            "  Agent files: {} {}",
+            style(format!("{} outdated", outdated.len())).yellow(),
+            style("(run `kayfabe upgrade-agents`)").dim()
+        );
+        for file in &outdated {
+            let path = file
+                .entry
+                .path
+                .strip_prefix(repo.layout_root())
+                .unwrap_or(&file.entry.path);
+            if file.changed_locally {
+                println!(
+                    "    {} {}",
+                    path.display(),
+                    style("(changed locally)").dim()
+                );
+            } else {
+                println!("    {}", path.display());
+            }
+        }
         Ok(())
     }
+
+    /// How many agent files are installed, and which of them are outdated
+    fn outdated_agent_files(repo: &GitRepo) -> Result<(usize, Vec<Outdated>)> {
+        let library = PromptLibrary::load()?;
+        let (mut installed, mut outdated) = (0, Vec::new());
+        let manifests = InstallCommand::manifests(repo.root(), None)?;
+        for manifest in &manifests {
+            installed += manifest.files.len();
+            outdated.extend(UpgradeAgentsCommand::outdated(manifest, &library)?);
+        }
+        for (_, legacy) in UpgradeAgentsCommand::legacy(repo.root(), &manifests)? {
+            installed += 1;
+            outdated.push(legacy);
+        }
+        Ok((installed, outdated))
+    }
 }
@@ -0,0 +1,64 @@
+use crate::error::{KayfabeError, Result};
+use crate::git::GitRepo;
+use crate::thoughts::Thoughts;
+use console::style;
+
+pub struct ThoughtsCommand;
+
+impl ThoughtsCommand {
+    /// Create the store and link it into every existing worktree
+    pub fn init() -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let thoughts = Thoughts::new(repo.layout_root());
+
+        if thoughts.init()? {
+            println!(
+                "{} Created {}",
+                style("✓").green(),
+                style(thoughts.root().display()).cyan()
+            );
+        }
+
+        for wt_path in repo.list_worktrees()? {
+            let name = repo.worktree_name(&wt_path);
+            if let Some(action) = thoughts.link(&repo, &wt_path)? {
+                println!("  {}: {}", style(&name).cyan(), style(action).dim());
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn search(terms: Vec<String>) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let thoughts = Thoughts::new(repo.layout_root());
+
+        if !thoughts.root().is_dir() {
+            return Err(KayfabeError::Other(
+                "No thoughts/ directory yet (create it with `kayfabe thoughts init`)".to_string(),
+            ));
+        }
+
+        let hits = thoughts.search(&terms)?;
+        if hits.is_empty() {
+            println!("{}", style("No matches").yellow());
+            return Ok(());
+        }
+
+        for hit in &hits {
+            println!(
+                "{}",
+                style(format!("thoughts/{}", hit.path.display())).cyan()
+            );
+            for (number, line) in &hit.lines {
+                println!("  {} {}", style(format!("{:>4}:", number)).dim(), line);
+            }
+        }
+        println!();
+        println!("{} matching files", hits.len());
+
+        Ok(())
+    }
+}
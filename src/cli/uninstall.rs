@@ -0,0 +1,249 @@
+use super::InstallCommand;
+use crate::agents::managed::{self, ManagedFile};
+use crate::agents::manifest::{EntryKind, ManifestEntry, KAYFABE};
+use crate::agents::Scope;
+use crate::error::{KayfabeError, Result};
+use crate::git::GitRepo;
+use console::style;
+use dialoguer::Confirm;
+use std::path::{Path, PathBuf};
+
+pub struct UninstallCommand;
+
+impl UninstallCommand {
+    /// Remove what `install`, `init` and `prompts install` recorded in their
+    /// manifests. Files changed since they were written are only removed
+    /// after confirmation, or with `force`. kayfabe's own files go only with
+    /// `layout`, which also moves `main/` back to the repository root.
+    pub fn execute(
+        target_dir: Option<PathBuf>,
+        agents: Option<Vec<String>>,
+        scope: Option<String>,
+        layout: bool,
+        force: bool,
+    ) -> Result<()> {
+        let scope = scope.as_deref().map(Scope::parse).transpose()?;
+        let target = match target_dir {
+            Some(dir) => dir,
+            None => std::env::current_dir()?,
+        };
+        let repo = GitRepo::discover(&target).ok();
+        if layout {
+            let Some(repo) = &repo else {
+                return Err(KayfabeError::Other(format!(
+                    "{} is not in a git repository",
+                    target.display()
+                )));
+            };
+            // Find out before anything is removed that main/ can move back
+            if repo.is_worktree_layout() {
+                repo.check_revert_worktree_layout()?;
+            }
+        }
+
+        let manifests = InstallCommand::manifests(&target, scope)?;
+        let (mut removed, mut kept) = (0, 0);
+        for mut manifest in manifests {
+            let entries: Vec<ManifestEntry> = manifest
+                .files
+                .iter()
+                .filter(|entry| scope.is_none() || scope == Some(entry.scope))
+                .filter(|entry| {
+                    if entry.agent == KAYFABE {
+                        layout
+                    } else {
+                        match &agents {
+                            Some(agents) => agents.contains(&entry.agent),
+                            None => true,
+                        }
+                    }
+                })
+                .cloned()
+                .collect();
+
+            for entry in entries {
+                match Self::remove(&entry, force) {
+                    Ok(true) => {
+                        manifest.forget(&entry.path);
+                        removed += 1;
+                    }
+                    Ok(false) => kept += 1,
+                    // Keep what was already removed out of the manifest
+                    Err(e) => {
+                        manifest.save()?;
+                        return Err(e);
+                    }
+                }
+            }
+            manifest.save()?;
+        }
+
+        println!();
+        println!(
+            "{} Removed {} installed files{}",
+            style("✓").green(),
+            removed,
+            if kept > 0 {
+                format!(", kept {} you changed", kept)
+            } else {
+                String::new()
+            }
+        );
+
+        if let Some(repo) = repo.filter(|_| layout) {
+            if repo.is_worktree_layout() {
+                repo.revert_worktree_layout()?;
+                // Left empty once the config and manifest are gone
+                std::fs::remove_dir(repo.layout_root().join(".kayfabe")).ok();
+                println!(
+                    "{} Moved main/ back to {}",
+                    style("✓").green(),
+                    style(repo.layout_root().display()).cyan()
+                );
+            } else {
+                println!(
+                    "{}",
+                    style("Repository is not in the worktree layout").dim()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove one recorded file, or kayfabe's block from it. Returns `false`
+    /// if it was kept because it had been changed.
+    fn remove(entry: &ManifestEntry, force: bool) -> Result<bool> {
+        let path = &entry.path;
+        if !path.exists() {
+            println!(
+                "  {} {} {}",
+                style("-").dim(),
+                path.display(),
+                style("(already gone)").dim()
+            );
+            return Ok(true);
+        }
+
+        match entry.kind {
+            EntryKind::File => {
+                let changed = managed::hash(&std::fs::read_to_string(path)?) != entry.hash;
+                if changed && !Self::confirm(path, force)? {
+                    Self::report_kept(path);
+                    return Ok(false);
+                }
+                std::fs::remove_file(path)?;
+                Self::prune_empty_parents(path);
+                println!("  {} removed {}", style("✓").green(), path.display());
+            }
+            EntryKind::Block => {
+                let file = ManagedFile::new(path.clone());
+                let Some((content, edited)) = file.without_block()? else {
+                    file.forget()?;
+                    return Ok(true);
+                };
+                if edited && !Self::confirm(path, force)? {
+                    Self::report_kept(path);
+                    return Ok(false);
+                }
+                if content.trim().is_empty() {
+                    std::fs::remove_file(path)?;
+                    Self::prune_empty_parents(path);
+                    println!("  {} removed {}", style("✓").green(), path.display());
+                } else {
+                    std::fs::write(path, content)?;
+                    println!(
+                        "  {} removed kayfabe block from {}",
+                        style("✓").green(),
+                        path.display()
+                    );
+                }
+                file.forget()?;
+            }
+        }
+        Ok(true)
+    }
+
+    fn confirm(path: &Path, force: bool) -> Result<bool> {
+        if force {
+            return Ok(true);
+        }
+        Ok(Confirm::new()
+            .with_prompt(format!(
+                "{} was changed since kayfabe installed it. Remove it anyway?",
+                path.display()
+            ))
+            .default(false)
+            .interact()?)
+    }
+
+    fn report_kept(path: &Path) {
+        println!(
+            "  {} {} {}",
+            style("-").dim(),
+            path.display(),
+            style("(kept, changed since installed)").dim()
+        );
+    }
+
+    /// Remove directories kayfabe created that are now empty, such as
+    /// `.claude/commands/`. Stops at the first one that isn't.
+    fn prune_empty_parents(path: &Path) {
+        for dir in path.ancestors().skip(1) {
+            if std::fs::remove_dir(dir).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(path: PathBuf, kind: EntryKind, content: &str) -> ManifestEntry {
+        ManifestEntry::new(path, "claude", Scope::Project, kind, managed::hash(content))
+    }
+
+    #[test]
+    fn test_remove_files_and_blocks() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::write(root.join("README.md"), "keep\n").unwrap();
+
+        // A whole file goes, with the directories kayfabe made for it
+        let command = root.join(".claude").join("commands").join("plan.md");
+        std::fs::create_dir_all(command.parent().unwrap()).unwrap();
+        std::fs::write(&command, "plan\n").unwrap();
+        let installed = entry(command.clone(), EntryKind::File, "plan\n");
+        assert!(UninstallCommand::remove(&installed, false).unwrap());
+        assert!(!root.join(".claude").exists());
+        assert!(root.join("README.md").exists());
+
+        // ... even when changed, if forced
+        std::fs::write(root.join("rules.md"), "edited\n").unwrap();
+        let installed = entry(root.join("rules.md"), EntryKind::File, "rules\n");
+        assert!(UninstallCommand::remove(&installed, true).unwrap());
+        assert!(!root.join("rules.md").exists());
+
+        // Only the block goes from a file with the user's own content
+        let block = managed::merge(None, "kayfabe rules\n", None)
+            .unwrap()
+            .content;
+        let claude = root.join("CLAUDE.md");
+        std::fs::write(&claude, format!("# Mine\n\n{}\nAfter\n", block)).unwrap();
+        let installed = entry(claude.clone(), EntryKind::Block, "kayfabe rules\n");
+        assert!(UninstallCommand::remove(&installed, false).unwrap());
+        assert_eq!(
+            std::fs::read_to_string(&claude).unwrap(),
+            "# Mine\n\nAfter\n"
+        );
+
+        // ... and the file too once nothing else is left in it
+        std::fs::write(&claude, &block).unwrap();
+        assert!(UninstallCommand::remove(&installed, false).unwrap());
+        assert!(!claude.exists());
+
+        assert!(UninstallCommand::remove(&installed, false).unwrap());
+    }
+}
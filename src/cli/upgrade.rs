@@ -0,0 +1,377 @@
+use super::{InstallCommand, PromptsCommand};
+use crate::agents::installer::AGENTS;
+use crate::agents::managed::{self, ManagedFile, Outcome};
+use crate::agents::manifest::{EntryKind, ManifestEntry};
+use crate::agents::{AgentInstaller, Manifest, PromptLibrary, Scope};
+use crate::error::{KayfabeError, Result};
+use crate::ui::Output;
+use console::style;
+use dialoguer::Confirm;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A recorded file whose kayfabe content has changed since it was installed
+pub struct Outdated {
+    pub entry: ManifestEntry,
+    /// The rules block body or whole file kayfabe writes now
+    pub content: String,
+    /// A whole file that was also edited by hand, so it can't be replaced
+    pub changed_locally: bool,
+}
+
+pub struct UpgradeAgentsCommand;
+
+impl UpgradeAgentsCommand {
+    /// Bring every installed agent file up to date with this version of
+    /// kayfabe, after showing the changes. Edits inside rules blocks are
+    /// merged; whole files edited by hand are left alone.
+    pub fn execute(dry_run: bool, yes: bool) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let library = PromptLibrary::load()?;
+        let mut manifests = InstallCommand::manifests(&current_dir, None)?;
+
+        let mut planned = Vec::new();
+        for (index, manifest) in manifests.iter().enumerate() {
+            for outdated in Self::outdated(manifest, &library)? {
+                planned.push((index, outdated));
+            }
+        }
+        planned.extend(Self::legacy(&current_dir, &manifests)?);
+        if planned.is_empty() {
+            println!(
+                "{} All installed agent files are up to date",
+                style("✓").green()
+            );
+            return Ok(());
+        }
+
+        for (_, outdated) in &planned {
+            Self::preview(outdated)?;
+        }
+        let applicable = planned
+            .iter()
+            .filter(|(_, outdated)| !outdated.changed_locally)
+            .count();
+        if dry_run || applicable == 0 {
+            if applicable > 0 {
+                println!();
+                println!("To apply: {}", style("kayfabe upgrade-agents").cyan());
+            }
+            return Ok(());
+        }
+
+        if !yes {
+            println!();
+            if !Confirm::new()
+                .with_prompt(format!("Apply {} updates?", applicable))
+                .default(true)
+                .interact()?
+            {
+                return Err(KayfabeError::Cancelled);
+            }
+        }
+
+        println!();
+        for (index, outdated) in planned {
+            if !outdated.changed_locally {
+                Self::apply(&outdated, &mut manifests[index])?;
+            }
+        }
+        for manifest in &manifests {
+            manifest.save()?;
+        }
+        println!();
+        println!(
+            "{}",
+            style(format!("✓ Upgraded {} agent files", applicable))
+                .green()
+                .bold()
+        );
+        Ok(())
+    }
+
+    /// Files recorded in `manifest` that kayfabe would now write differently
+    pub fn outdated(manifest: &Manifest, library: &PromptLibrary) -> Result<Vec<Outdated>> {
+        let mut outdated = Vec::new();
+        for entry in &manifest.files {
+            if !entry.path.exists() {
+                continue;
+            }
+            let vars = Self::variables(entry)?;
+            let Some(content) = AgentInstaller::expected(entry, library, &vars)? else {
+                continue;
+            };
+            if AgentInstaller::content_hash(entry.kind, &content) == entry.hash {
+                continue;
+            }
+
+            let changed_locally = entry.kind == EntryKind::File
+                && managed::hash(&std::fs::read_to_string(&entry.path)?) != entry.hash;
+            outdated.push(Outdated {
+                entry: entry.clone(),
+                content,
+                changed_locally,
+            });
+        }
+        Ok(outdated)
+    }
+
+    /// Rule files written by a kayfabe that predates the manifest, found at
+    /// the project and worktree rule locations for `target`, each paired
+    /// with the index of the manifest in `manifests` to record it in
+    pub fn legacy(target: &Path, manifests: &[Manifest]) -> Result<Vec<(usize, Outdated)>> {
+        let mut legacy: Vec<(usize, Outdated)> = Vec::new();
+        for scope in [Scope::Project, Scope::Worktree] {
+            let (root, manifest_path) = InstallCommand::resolve(scope, target)?;
+            let Some(index) = manifests
+                .iter()
+                .position(|manifest| manifest.path() == manifest_path)
+            else {
+                continue;
+            };
+            for agent in AGENTS {
+                let Some(location) = AgentInstaller::rules_location(agent, scope) else {
+                    continue;
+                };
+                let path = root.join(location);
+                let seen = manifests
+                    .iter()
+                    .any(|manifest| manifest.get(&path).is_some())
+                    || legacy.iter().any(|(_, found)| found.entry.path == path);
+                if seen {
+                    continue;
+                }
+                let Ok(existing) = std::fs::read_to_string(&path) else {
+                    continue;
+                };
+                if !managed::is_legacy(&existing) {
+                    continue;
+                }
+                legacy.push((
+                    index,
+                    Outdated {
+                        entry: ManifestEntry::new(
+                            path,
+                            agent,
+                            scope,
+                            EntryKind::Block,
+                            managed::hash(&existing),
+                        ),
+                        content: AgentInstaller::rules(agent).unwrap_or_default(),
+                        changed_locally: false,
+                    },
+                ));
+            }
+        }
+        Ok(legacy)
+    }
+
+    /// Prompt variables for where the file lives, as they are now: from the
+    /// home directory for user scope, else from the file's checkout. They
+    /// aren't recorded at install time, so changing `[prompts.vars]` shows
+    /// every prompt using them as outdated.
+    fn variables(entry: &ManifestEntry) -> Result<BTreeMap<String, String>> {
+        let dir = match entry.scope {
+            Scope::User => dirs::home_dir()
+                .ok_or_else(|| KayfabeError::Other("Could not find home directory".to_string()))?,
+            _ => entry
+                .path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+        };
+        PromptsCommand::variables_at(&dir)
+    }
+
+    fn preview(outdated: &Outdated) -> Result<()> {
+        let path = &outdated.entry.path;
+        let label = path.display().to_string();
+        let existing = std::fs::read_to_string(path)?;
+
+        if outdated.changed_locally {
+            println!(
+                "{} {}",
+                style(&label).cyan(),
+                style("(changed locally, keeping your version)").yellow()
+            );
+            Output::diff(&existing, &outdated.content, &label, "kayfabe");
+            return Ok(());
+        }
+
+        let new = match outdated.entry.kind {
+            EntryKind::Block => {
+                let merge = ManagedFile::new(path.clone()).plan(&outdated.content)?;
+                println!("{} would be {}:", style(&label).cyan(), merge.outcome);
+                merge.content
+            }
+            EntryKind::File => {
+                println!("{} would be {}:", style(&label).cyan(), Outcome::Updated);
+                outdated.content.clone()
+            }
+        };
+        Output::diff(&existing, &new, &label, &label);
+        Ok(())
+    }
+
+    fn apply(outdated: &Outdated, manifest: &mut Manifest) -> Result<()> {
+        let entry = &outdated.entry;
+        let (outcome, backup) = match entry.kind {
+            EntryKind::Block => {
+                let file = ManagedFile::new(entry.path.clone());
+                let merge = file.plan(&outdated.content)?;
+                let backup = file.apply(&outdated.content, &merge)?;
+                (merge.outcome, backup)
+            }
+            EntryKind::File => {
+                std::fs::write(&entry.path, &outdated.content)?;
+                (Outcome::Updated, None)
+            }
+        };
+        manifest.record(ManifestEntry::new(
+            entry.path.clone(),
+            &entry.agent,
+            entry.scope,
+            entry.kind,
+            AgentInstaller::content_hash(entry.kind, &outdated.content),
+        ));
+
+        println!(
+            "  {} {} {}",
+            style("✓").green(),
+            entry.path.display(),
+            style(format!("({})", outcome)).dim()
+        );
+        if let Some(backup) = backup {
+            println!(
+                "    {}",
+                style(format!("previous version kept as {}", backup.display())).dim()
+            );
+        }
+        if outcome == Outcome::Conflict {
+            Output::warning(&format!(
+                "Resolve the <<<<<<< / >>>>>>> conflict markers in {}",
+                entry.path.display()
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(path: &Path, kind: EntryKind, content: &str) -> ManifestEntry {
+        ManifestEntry::new(
+            path.to_path_buf(),
+            "claude",
+            Scope::Worktree,
+            kind,
+            AgentInstaller::content_hash(kind, content),
+        )
+    }
+
+    #[test]
+    fn test_outdated() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let library = PromptLibrary::new(root.join("overrides"));
+        let commands = root.join(".claude").join("commands");
+        std::fs::create_dir_all(&commands).unwrap();
+        let mut manifest = Manifest::load(Manifest::project_path(root)).unwrap();
+
+        // Up to date: the current rules block and the current prompt
+        let rules = AgentInstaller::rules("claude").unwrap();
+        let claude = root.join("CLAUDE.md");
+        std::fs::write(&claude, managed::merge(None, &rules, None).unwrap().content).unwrap();
+        manifest.record(entry(&claude, EntryKind::Block, &rules));
+        let commit = commands.join("commit.md");
+        let current = AgentInstaller::expected(
+            &entry(&commit, EntryKind::File, ""),
+            &library,
+            &UpgradeAgentsCommand::variables(&entry(&commit, EntryKind::File, "")).unwrap(),
+        )
+        .unwrap()
+        .unwrap();
+        std::fs::write(&commit, &current).unwrap();
+        manifest.record(entry(&commit, EntryKind::File, &current));
+        assert!(UpgradeAgentsCommand::outdated(&manifest, &library)
+            .unwrap()
+            .is_empty());
+
+        // Outdated: written by an older kayfabe and not touched since
+        let plan = commands.join("create_plan.md");
+        std::fs::write(&plan, "old plan\n").unwrap();
+        manifest.record(entry(&plan, EntryKind::File, "old plan\n"));
+
+        // Outdated and changed locally: edited by hand since
+        let review = commands.join("local_review.md");
+        std::fs::write(&review, "my review\n").unwrap();
+        manifest.record(entry(&review, EntryKind::File, "old review\n"));
+
+        let outdated = UpgradeAgentsCommand::outdated(&manifest, &library).unwrap();
+        let found: Vec<(&Path, bool)> = outdated
+            .iter()
+            .map(|file| (file.entry.path.as_path(), file.changed_locally))
+            .collect();
+        assert_eq!(
+            found,
+            vec![(plan.as_path(), false), (review.as_path(), true)]
+        );
+        assert!(outdated[0].content.starts_with("---"));
+    }
+
+    #[test]
+    fn test_apply_block() {
+        let temp_dir = TempDir::new().unwrap();
+        let claude = temp_dir.path().join("CLAUDE.md");
+        let old = "# Old rules\n";
+        let installed = managed::merge(Some("# Mine\n"), old, None).unwrap().content;
+        std::fs::write(&claude, installed).unwrap();
+
+        let mut manifest = Manifest::load(Manifest::project_path(temp_dir.path())).unwrap();
+        manifest.record(entry(&claude, EntryKind::Block, old));
+        let rules = AgentInstaller::rules("claude").unwrap();
+        let outdated = Outdated {
+            entry: manifest.files[0].clone(),
+            content: rules.clone(),
+            changed_locally: false,
+        };
+        UpgradeAgentsCommand::apply(&outdated, &mut manifest).unwrap();
+
+        let content = std::fs::read_to_string(&claude).unwrap();
+        assert!(content.starts_with("# Mine\n\n<!-- kayfabe:begin"));
+        assert!(content.contains(rules.trim()));
+        assert!(!content.contains("# Old rules"));
+        assert_eq!(
+            manifest.get(&claude).unwrap().hash,
+            AgentInstaller::content_hash(EntryKind::Block, &rules)
+        );
+        ManagedFile::new(claude).forget().unwrap();
+    }
+
+    #[test]
+    fn test_legacy_windsurf_rules_are_replaced() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let rules = root.join(".windsurfrules");
+        std::fs::write(&rules, managed::tests::LEGACY_WINDSURF).unwrap();
+        let mut manifests = vec![Manifest::load(Manifest::project_path(&root)).unwrap()];
+
+        let legacy = UpgradeAgentsCommand::legacy(&root, &manifests).unwrap();
+        assert_eq!(legacy.len(), 1);
+        let (index, outdated) = &legacy[0];
+        assert_eq!(outdated.entry.path, rules);
+        UpgradeAgentsCommand::apply(outdated, &mut manifests[*index]).unwrap();
+
+        let content = std::fs::read_to_string(&rules).unwrap();
+        assert!(content.starts_with("<!-- kayfabe:begin"));
+        assert_eq!(content.matches("Kayfabe Commands").count(), 1);
+        assert!(manifests[0].get(&rules).is_some());
+        assert!(UpgradeAgentsCommand::legacy(&root, &manifests)
+            .unwrap()
+            .is_empty());
+        ManagedFile::new(rules).forget().unwrap();
+    }
+}
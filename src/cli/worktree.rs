@@ -1,9 +1,17 @@
-use crate::config::ProjectConfig;
+use super::PlanCommand;
+use crate::agents::{AgentRunner, AgentStatus};
+use crate::config::{GlobalConfig, ProjectConfig, ProjectState, WorktreeConfig, WorktreeMetadata};
 use crate::error::{KayfabeError, Result};
-use crate::git::{GitRepo, Worktree};
+use crate::git::{CleanupOptions, CleanupPlan, CleanupPlanner, DiskUsage, GitRepo, Worktree};
 use crate::ide::{IDELauncher, IDE};
+use crate::provision::{self, BuildCache, LocalFiles, Resources};
+use crate::session::SessionManager;
+use crate::thoughts::Thoughts;
 use console::style;
 use dialoguer::Confirm;
+use indicatif::HumanBytes;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub struct WorktreeCommand;
 
@@ -34,13 +42,13 @@ impl WorktreeCommand {
             repo.convert_to_worktree_layout()?;
         }
 
-        println!("{}", style("[1/4] Fetching latest refs...").cyan());
+        println!("{}", style("[1/5] Fetching latest refs...").cyan());
         let _ = repo.fetch();
 
         println!(
             "{}",
             style(format!(
-                "[2/4] Creating worktree from base: {}",
+                "[2/5] Creating worktree from base: {}",
                 base_branch
             ))
             .cyan()
@@ -51,45 +59,180 @@ impl WorktreeCommand {
         println!("  Path: {}", style(wt_path.display()).cyan());
         println!("  Branch: {}", style(&name).cyan());
 
-        println!("{}", style("[3/4] Running post-create hooks...").cyan());
         let config = ProjectConfig::load(repo.layout_root())?;
-        for hook in &config.hooks.post_create {
+
+        println!("{}", style("[3/5] Provisioning worktree...").cyan());
+        let mut actions = BuildCache::apply(&repo, &wt_path, &name, &config.worktree.cache)?;
+        actions.extend(LocalFiles::sync(
+            &repo.anchor_path()?,
+            &wt_path,
+            &config.worktree.copy,
+            &config.worktree.link,
+            false,
+        )?);
+        if config.thoughts.enabled {
+            let thoughts = Thoughts::new(repo.layout_root());
+            thoughts.init()?;
+            thoughts.link(&repo, &repo.anchor_path()?)?;
+            actions.extend(thoughts.link(&repo, &wt_path)?);
+        }
+        let metadata = Resources::allocate(repo.layout_root(), &name, &config.resources)?;
+        if let Some(ports) = metadata.ports {
+            actions.push(format!("leased ports {}-{}", ports.start, ports.end));
+        }
+        let hook_env = provision::worktree_env(repo.layout_root(), &name, &config)?;
+        provision::write_env_file(&repo, &wt_path, &hook_env)?;
+        actions.push(format!("wrote {}", provision::ENV_FILE));
+
+        for action in actions {
+            println!("  {}", style(action).dim());
+        }
+
+        println!("{}", style("[4/5] Running post-create hooks...").cyan());
+        Self::run_hooks(
+            "Post-create",
+            &config.hooks.post_create,
+            &wt_path,
+            &hook_env,
+        )?;
+
+        if config.session.on_create {
+            let manager = SessionManager::new(&config.session, repo.layout_root());
+            match manager.start(&name, &wt_path, &hook_env) {
+                Ok(_) => println!(
+                    "  {}",
+                    style(format!("started session {}", manager.session_name(&name))).dim()
+                ),
+                Err(e) => println!(
+                    "  {}",
+                    style(format!("session not started: {}", e)).yellow()
+                ),
+            }
+        }
+
+        if !no_open {
+            Self::open_new_worktree(&wt_path, open, &config, &hook_env)?;
+        }
+
+        Self::maybe_auto_cleanup(&repo, &wt_path);
+
+        Ok(())
+    }
+
+    /// Launch `--open`, or else the global `defaults.ide`. A missing default
+    /// IDE is not an error since it was never asked for explicitly.
+    fn open_new_worktree(
+        wt_path: &Path,
+        open: Option<String>,
+        config: &ProjectConfig,
+        hook_env: &[(String, String)],
+    ) -> Result<()> {
+        let global = GlobalConfig::load()?;
+        let explicit = open.is_some();
+        let ide_name = open.unwrap_or_else(|| global.defaults.ide.clone());
+
+        match IDE::resolve(&ide_name, &global) {
+            Some(ide) if ide.is_available() => {
+                println!(
+                    "{}",
+                    style(format!("[5/5] Launching {}...", ide.name)).cyan()
+                );
+                Self::run_hooks("Pre-open", &config.hooks.pre_open, wt_path, hook_env)?;
+                IDELauncher::launch(&ide, wt_path, None, true)?;
+                println!("{}", style(format!("✓ {} launched", ide.name)).green());
+            }
+            _ if explicit => return Err(KayfabeError::IdeNotFound(ide_name)),
+            _ => println!(
+                "{}",
+                style(format!(
+                    "[5/5] Default IDE {} not found, skipping (set defaults.ide or pass --open)",
+                    ide_name
+                ))
+                .dim()
+            ),
+        }
+
+        Ok(())
+    }
+
+    /// Open existing worktrees in an IDE, running `pre_open` hooks in each
+    /// first. Several worktrees each get their own window.
+    pub fn open(
+        names: Vec<String>,
+        ide: Option<String>,
+        file: Option<PathBuf>,
+        new_window: bool,
+    ) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let config = ProjectConfig::load(repo.layout_root())?;
+        let global = GlobalConfig::load()?;
+
+        let ide_name = ide.unwrap_or_else(|| global.defaults.ide.clone());
+        let ide = IDE::resolve(&ide_name, &global)
+            .filter(|ide| ide.is_available())
+            .ok_or(KayfabeError::IdeNotFound(ide_name))?;
+
+        let targets = Self::find_all(&repo, &names)?;
+        let new_window = new_window || targets.len() > 1;
+
+        for (name, wt_path) in targets {
+            let hook_env = provision::worktree_env(repo.layout_root(), name, &config)?;
+            Self::run_hooks("Pre-open", &config.hooks.pre_open, &wt_path, &hook_env)?;
+
+            let file = file.as_ref().map(|file| wt_path.join(file));
+            IDELauncher::launch(&ide, &wt_path, file.as_deref(), new_window)?;
+            println!(
+                "{} Opened {} in {}",
+                style("✓").green(),
+                style(name).cyan(),
+                ide.name
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Look up every named worktree, so nothing opens if one is missing
+    fn find_all<'a>(repo: &GitRepo, names: &'a [String]) -> Result<Vec<(&'a String, PathBuf)>> {
+        names
+            .iter()
+            .map(|name| Ok((name, repo.find_worktree(name)?)))
+            .collect()
+    }
+
+    fn run_hooks(
+        kind: &str,
+        hooks: &[String],
+        wt_path: &Path,
+        hook_env: &[(String, String)],
+    ) -> Result<()> {
+        for hook in hooks {
             let output = std::process::Command::new("sh")
                 .arg("-c")
                 .arg(hook)
-                .current_dir(&wt_path)
+                .current_dir(wt_path)
+                .envs(hook_env.iter().cloned())
                 .output()?;
 
             if !output.status.success() {
                 let stderr = String::from_utf8_lossy(&output.stderr);
                 return Err(KayfabeError::Other(format!(
-                    "Post-create hook failed: {}",
-                    stderr
+                    "{} hook failed: {}",
+                    kind, stderr
                 )));
             }
         }
 
-        if !no_open {
-            if let Some(ide_name) = open {
-                if let Some(ide) = IDE::parse(&ide_name) {
-                    println!(
-                        "{}",
-                        style(format!("[4/4] Launching {}...", ide_name)).cyan()
-                    );
-                    IDELauncher::launch(ide, &wt_path)?;
-                    println!("{}", style(format!("✓ {} launched", ide_name)).green());
-                } else {
-                    return Err(KayfabeError::IdeNotFound(ide_name));
-                }
-            }
-        }
-
         Ok(())
     }
 
-    pub fn list(stale: Option<u64>) -> Result<()> {
+    pub fn list(stale: Option<u64>, size: bool) -> Result<()> {
         let current_dir = std::env::current_dir()?;
         let repo = GitRepo::discover(&current_dir)?;
+        let artifact_patterns = ProjectConfig::load(repo.layout_root())?
+            .worktree
+            .artifact_patterns();
 
         let worktrees = repo.list_worktrees()?;
 
@@ -112,7 +255,7 @@ impl WorktreeCommand {
         for wt_path in worktrees {
             let info = Worktree::get_info(&wt_path, &base_branch)?;
 
-            let name = wt_path.file_name().unwrap().to_string_lossy();
+            let name = repo.worktree_name(&wt_path);
             let branch = info.branch.as_deref().unwrap_or("(detached)");
 
             if let Some(stale_days) = stale {
@@ -134,12 +277,48 @@ impl WorktreeCommand {
                     style("(new)".to_string()).dim()
                 };
 
+                let ports = WorktreeMetadata::load(repo.layout_root(), &name)?
+                    .ports
+                    .map(|ports| {
+                        format!(
+                            " {}",
+                            style(format!(":{}-{}", ports.start, ports.end)).magenta()
+                        )
+                    })
+                    .unwrap_or_default();
+
+                let size = if size {
+                    let usage = DiskUsage::measure(&wt_path, &artifact_patterns)?;
+                    format!(
+                        " {}",
+                        style(format!(
+                            "[{}, {} artifacts]",
+                            HumanBytes(usage.total),
+                            HumanBytes(usage.artifacts)
+                        ))
+                        .dim()
+                    )
+                } else {
+                    String::new()
+                };
+
                 println!(
-                    "  {} → {} {}",
-                    style(name).cyan(),
+                    "  {} → {} {}{}{}",
+                    style(&name).cyan(),
                     style(branch).white(),
-                    status
+                    status,
+                    ports,
+                    size
                 );
+
+                if let Some(plan) = PlanCommand::linked(repo.layout_root(), &name)? {
+                    println!(
+                        "      {} {}  {}",
+                        style("plan").dim(),
+                        plan.id(),
+                        PlanCommand::progress(&plan)
+                    );
+                }
             }
         }
 
@@ -172,11 +351,7 @@ impl WorktreeCommand {
         let current_dir = std::env::current_dir()?;
         let repo = GitRepo::discover(&current_dir)?;
 
-        let wt_path = if repo.is_worktree_layout() {
-            repo.root().join("wt").join(&name)
-        } else {
-            repo.root().join(&name)
-        };
+        let wt_path = repo.worktree_path(&name);
 
         if !wt_path.exists() {
             return Err(KayfabeError::WorktreeNotFound(name));
@@ -200,52 +375,130 @@ impl WorktreeCommand {
             }
         }
 
+        Self::remove_and_release(&repo, &wt_path, force)?;
+
+        println!("{}", style(format!("✓ Removed worktree: {}", name)).green());
+
+        Ok(())
+    }
+
+    /// Remove a worktree and drop the metadata (port and resource leases)
+    /// kayfabe keeps for it
+    fn remove_and_release(repo: &GitRepo, wt_path: &Path, force: bool) -> Result<()> {
+        let name = repo.worktree_name(wt_path);
+        // Nothing is stopped or released unless git actually removed it
         if force {
-            repo.remove_worktree_force(&wt_path)?;
+            repo.remove_worktree_force(wt_path)?;
         } else {
-            repo.remove_worktree(&wt_path)?;
+            repo.remove_worktree(wt_path)?;
         }
 
-        println!("{}", style(format!("✓ Removed worktree: {}", name)).green());
+        if let Some(agent) = WorktreeMetadata::load(repo.layout_root(), &name)?.agent {
+            AgentRunner::stop(&agent)?;
+        }
+        let config = ProjectConfig::load(repo.layout_root())?;
+        SessionManager::new(&config.session, repo.layout_root()).kill(&name)?;
+        BuildCache::remove(repo.layout_root(), &name, &config.worktree.cache)?;
+        WorktreeMetadata::remove(repo.layout_root(), &name)
+    }
+
+    /// Re-sync `[worktree.copy]`/`[worktree.link]` files from the anchor into
+    /// one worktree (default: the current one) or all of them.
+    pub fn refresh_files(name: Option<String>, all: bool, overwrite: bool) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let config = ProjectConfig::load(repo.layout_root())?;
+        let anchor = repo.anchor_path()?;
+
+        let targets = if all {
+            repo.list_worktrees()?
+                .into_iter()
+                .filter(|path| *path != anchor)
+                .collect()
+        } else {
+            vec![Self::resolve_worktree(&repo, name.as_deref())?]
+        };
+
+        for wt_path in targets {
+            let wt_name = repo.worktree_name(&wt_path);
+            let actions = LocalFiles::sync(
+                &anchor,
+                &wt_path,
+                &config.worktree.copy,
+                &config.worktree.link,
+                overwrite,
+            )?;
+
+            if actions.is_empty() {
+                println!(
+                    "{} {} up to date",
+                    style("✓").green(),
+                    style(wt_name).cyan()
+                );
+            } else {
+                println!("{} {}", style("✓").green(), style(wt_name).cyan());
+                for action in actions {
+                    println!("  {}", style(action).dim());
+                }
+            }
+        }
 
         Ok(())
     }
 
+    /// Path of the named worktree, or of the worktree containing the current
+    /// directory when no name is given.
+    fn resolve_worktree(repo: &GitRepo, name: Option<&str>) -> Result<PathBuf> {
+        match name {
+            Some(name) => {
+                let wt_path = repo.worktree_path(name);
+                if !wt_path.exists() {
+                    return Err(KayfabeError::WorktreeNotFound(name.to_string()));
+                }
+                Ok(wt_path)
+            }
+            None if repo.root() != repo.anchor_path()? => Ok(repo.root().to_path_buf()),
+            None => Err(KayfabeError::Other(
+                "Not inside a worktree; pass a worktree name".to_string(),
+            )),
+        }
+    }
+
     pub fn cleanup(
         older_than: u64,
         dry_run: bool,
         force: bool,
         include_unmerged: bool,
+        explain: bool,
+        artifacts_only: bool,
     ) -> Result<()> {
         let current_dir = std::env::current_dir()?;
         let repo = GitRepo::discover(&current_dir)?;
+        let config = ProjectConfig::load(repo.layout_root())?;
 
-        let worktrees = repo.list_worktrees()?;
-        let mut to_remove = Vec::new();
-        let mut skipped = Vec::new();
-
-        let base_branch = repo
-            .get_default_branch()
-            .unwrap_or_else(|_| "main".to_string());
-
-        for wt_path in worktrees {
-            let info = Worktree::get_info(&wt_path, &base_branch)?;
-
-            if info.is_main {
-                continue;
-            }
+        if artifacts_only {
+            return Self::clear_artifacts(&repo, &config, older_than, dry_run, force);
+        }
 
-            if let Some(days) = info.staleness_days {
-                if days >= older_than as f64 {
-                    if info.safety.is_safe_to_remove || include_unmerged {
-                        to_remove.push((wt_path, info));
-                    } else {
-                        skipped.push((wt_path, info));
-                    }
-                }
-            }
+        let plan = CleanupPlanner::plan(
+            &repo,
+            &CleanupOptions {
+                older_than,
+                include_unmerged,
+                policy: &config.cleanup,
+            },
+        )?;
+        Self::print_cleanup_warnings(&plan);
+
+        if explain {
+            Self::explain_cleanup(&plan, older_than);
+            return Ok(());
         }
 
+        let CleanupPlan {
+            to_remove, skipped, ..
+        } = plan;
+
         if to_remove.is_empty() {
             println!("{}", style("No stale worktrees to clean up").green());
             return Ok(());
@@ -260,29 +513,27 @@ impl WorktreeCommand {
         }
 
         println!("{}", style("Would remove:").bold());
-        for (path, info) in &to_remove {
-            let name = path.file_name().unwrap().to_string_lossy();
-            let days = info.staleness_days.unwrap_or(0.0);
-            let status = if info.safety.is_safe_to_remove {
-                "merged"
-            } else {
-                "unmerged"
-            };
+        for entry in &to_remove {
+            let days = entry.info.staleness_days.unwrap_or(0.0);
             println!(
                 "  {} ({:.0} days stale, {})",
-                style(name).cyan(),
+                style(&entry.name).cyan(),
                 days,
-                status
+                entry.reason
             );
         }
 
         if !skipped.is_empty() {
             println!();
-            println!("{}", style("Skipping (unmerged work):").bold());
-            for (path, info) in &skipped {
-                let name = path.file_name().unwrap().to_string_lossy();
-                let days = info.staleness_days.unwrap_or(0.0);
-                println!("  {} ({:.0} days stale)", style(name).yellow(), days);
+            println!("{}", style("Skipping:").bold());
+            for entry in &skipped {
+                let days = entry.info.staleness_days.unwrap_or(0.0);
+                println!(
+                    "  {} ({:.0} days stale, {})",
+                    style(&entry.name).yellow(),
+                    days,
+                    entry.reason
+                );
             }
         }
 
@@ -304,14 +555,10 @@ impl WorktreeCommand {
         }
 
         println!();
-        for (path, info) in to_remove {
-            let name = path.file_name().unwrap().to_string_lossy();
-            if info.safety.is_safe_to_remove {
-                repo.remove_worktree(&path)?;
-            } else {
-                repo.remove_worktree_force(&path)?;
-            }
-            println!("{}", style(format!("✓ Removed {}", name)).green());
+        for entry in to_remove {
+            let force = entry.info.safety.has_uncommitted_changes;
+            Self::remove_and_release(&repo, &entry.path, force)?;
+            println!("{}", style(format!("✓ Removed {}", entry.name)).green());
         }
 
         println!();
@@ -319,4 +566,407 @@ impl WorktreeCommand {
 
         Ok(())
     }
+
+    /// Print a worktree's path and nothing else, for `kf cd` and scripts
+    pub fn path(name: Option<String>) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let path = match name {
+            Some(name) => repo.find_worktree(&name)?,
+            None => repo.anchor_path()?,
+        };
+        println!("{}", path.display());
+        Ok(())
+    }
+
+    pub fn du() -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let artifact_patterns = ProjectConfig::load(repo.layout_root())?
+            .worktree
+            .artifact_patterns();
+
+        let mut rows = Vec::new();
+        for wt_path in repo.list_worktrees()? {
+            let usage = DiskUsage::measure(&wt_path, &artifact_patterns)?;
+            let name = repo.worktree_name(&wt_path);
+            rows.push((name, usage));
+        }
+        rows.sort_by_key(|(_, usage)| std::cmp::Reverse(usage.total));
+
+        println!("{}", style("Worktree disk usage:").bold());
+        println!();
+        println!(
+            "  {}",
+            style(format!(
+                "{:<30} {:>12} {:>12} {:>12}",
+                "WORKTREE", "TOTAL", "SOURCE", "ARTIFACTS"
+            ))
+            .dim()
+        );
+
+        let (mut total, mut artifacts) = (0, 0);
+        for (name, usage) in &rows {
+            total += usage.total;
+            artifacts += usage.artifacts;
+            println!(
+                "  {} {:>12} {:>12} {:>12}",
+                style(format!("{:<30}", name)).cyan(),
+                HumanBytes(usage.total).to_string(),
+                HumanBytes(usage.source()).to_string(),
+                HumanBytes(usage.artifacts).to_string()
+            );
+        }
+
+        println!();
+        println!(
+            "  {} {:>12} {:>12} {:>12}",
+            style(format!("{:<30}", "total")).bold(),
+            HumanBytes(total).to_string(),
+            HumanBytes(total - artifacts).to_string(),
+            HumanBytes(artifacts).to_string()
+        );
+        println!();
+        println!(
+            "Reclaim artifact space with {}",
+            style("kayfabe worktree cleanup --artifacts-only").cyan()
+        );
+
+        Ok(())
+    }
+
+    /// Delete build artifacts from stale worktrees but keep the worktrees.
+    /// Merge state doesn't matter here since no source files are touched.
+    fn clear_artifacts(
+        repo: &GitRepo,
+        config: &ProjectConfig,
+        older_than: u64,
+        dry_run: bool,
+        force: bool,
+    ) -> Result<()> {
+        let plan = CleanupPlanner::plan(
+            repo,
+            &CleanupOptions {
+                older_than,
+                include_unmerged: true,
+                policy: &config.cleanup,
+            },
+        )?;
+        Self::print_cleanup_warnings(&plan);
+
+        let artifact_patterns = config.worktree.artifact_patterns();
+        let mut targets = Vec::new();
+        for entry in plan.to_remove {
+            let usage = DiskUsage::measure(&entry.path, &artifact_patterns)?;
+            if usage.artifacts > 0 {
+                targets.push((entry, usage));
+            }
+        }
+
+        if targets.is_empty() {
+            println!("{}", style("No build artifacts to clear").green());
+            return Ok(());
+        }
+
+        if dry_run {
+            println!(
+                "{}",
+                style("DRY RUN - No changes will be made").bold().yellow()
+            );
+            println!();
+        }
+
+        println!("{}", style("Would clear build artifacts:").bold());
+        for (entry, usage) in &targets {
+            println!(
+                "  {} ({} in {} dirs)",
+                style(&entry.name).cyan(),
+                HumanBytes(usage.artifacts),
+                usage.artifact_dirs.len()
+            );
+        }
+
+        if dry_run {
+            println!();
+            println!(
+                "To execute: {}",
+                style("kayfabe worktree cleanup --artifacts-only").cyan()
+            );
+            return Ok(());
+        }
+
+        if !force {
+            println!();
+            if !Confirm::new()
+                .with_prompt("Clear build artifacts?")
+                .default(false)
+                .interact()?
+            {
+                return Err(KayfabeError::Cancelled);
+            }
+        }
+
+        println!();
+        let mut reclaimed = 0;
+        for (entry, usage) in targets {
+            reclaimed += usage.clear_artifacts()?;
+            println!("{}", style(format!("✓ Cleared {}", entry.name)).green());
+        }
+
+        println!();
+        println!(
+            "{}",
+            style(format!("Reclaimed {}", HumanBytes(reclaimed)))
+                .bold()
+                .green()
+        );
+
+        Ok(())
+    }
+
+    fn explain_cleanup(plan: &CleanupPlan, older_than: u64) {
+        println!("{}", style("Cleanup policy decisions:").bold());
+        println!();
+
+        let decisions = [
+            (&plan.to_remove, style("remove").red()),
+            (&plan.skipped, style("skip").yellow()),
+            (&plan.kept, style("keep").green()),
+        ];
+        for (entries, decision) in decisions {
+            for entry in entries {
+                let days = entry.info.staleness_days.unwrap_or(0.0);
+                println!(
+                    "  {} {} ({:.0} days)",
+                    decision,
+                    style(&entry.name).cyan(),
+                    days
+                );
+                println!(
+                    "      matched: {}",
+                    style(entry.policy.describe(older_than)).dim()
+                );
+                println!("      reason:  {}", style(&entry.reason).dim());
+            }
+        }
+    }
+
+    fn print_cleanup_warnings(plan: &CleanupPlan) {
+        for warning in &plan.warnings {
+            println!("{} {}", style("⚠").yellow(), style(warning).dim());
+        }
+    }
+
+    /// Non-interactive safe cleanup driven by the global `[worktree]` config,
+    /// for use from cron or systemd timers. Every run is appended to
+    /// `log_file` (default `.kayfabe/cleanup.log`).
+    pub fn auto_cleanup(dry_run: bool, log_file: Option<PathBuf>) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let repo = GitRepo::discover(&current_dir)?;
+        let config = GlobalConfig::load()?;
+
+        let log_file =
+            log_file.unwrap_or_else(|| repo.layout_root().join(".kayfabe").join("cleanup.log"));
+        let removed =
+            Self::run_auto_cleanup(&repo, &config.worktree, dry_run, None, Some(&log_file))?;
+
+        if removed.is_empty() {
+            println!("{}", style("No stale worktrees to clean up").green());
+        } else {
+            let verb = if dry_run { "Would remove" } else { "Removed" };
+            for name in &removed {
+                println!("{}", style(format!("✓ {} {}", verb, name)).green());
+            }
+        }
+        println!("  Log: {}", style(log_file.display()).dim());
+
+        Ok(())
+    }
+
+    /// Run auto cleanup after another command if `auto_cleanup` is enabled
+    /// and the last run is older than `auto_cleanup_interval_hours`. Failures
+    /// are reported but never fail the calling command. `keep`, the worktree
+    /// the command is working on, is always excluded.
+    fn maybe_auto_cleanup(repo: &GitRepo, keep: &Path) {
+        let result = GlobalConfig::load().and_then(|config| {
+            let settings = config.worktree;
+            if !settings.auto_cleanup {
+                return Ok(());
+            }
+
+            let mut state = ProjectState::load(repo.layout_root())?;
+            let now = Self::unix_now();
+            let interval = settings.auto_cleanup_interval_hours * 3600;
+            if state
+                .last_auto_cleanup
+                .is_some_and(|last| now.saturating_sub(last) < interval)
+            {
+                return Ok(());
+            }
+
+            state.last_auto_cleanup = Some(now);
+            state.save(repo.layout_root())?;
+
+            let log_file = repo.layout_root().join(".kayfabe").join("cleanup.log");
+            for name in Self::run_auto_cleanup(repo, &settings, false, Some(keep), Some(&log_file))?
+            {
+                println!(
+                    "{}",
+                    style(format!("✓ Auto-cleanup removed {}", name)).green()
+                );
+            }
+            Ok(())
+        });
+
+        if let Err(e) = result {
+            println!(
+                "{} Auto-cleanup skipped: {}",
+                style("⚠").yellow(),
+                style(e).dim()
+            );
+        }
+    }
+
+    /// Remove worktrees that are merged and older than `stale_days`. Unmerged
+    /// worktrees are only considered when `protect_unmerged` is off, and
+    /// worktrees with uncommitted changes, a running agent or a session are
+    /// never touched.
+    fn run_auto_cleanup(
+        repo: &GitRepo,
+        settings: &WorktreeConfig,
+        dry_run: bool,
+        keep: Option<&Path>,
+        log_file: Option<&Path>,
+    ) -> Result<Vec<String>> {
+        let config = ProjectConfig::load(repo.layout_root())?;
+        let plan = CleanupPlanner::plan(
+            repo,
+            &CleanupOptions {
+                older_than: settings.stale_days,
+                include_unmerged: !settings.protect_unmerged,
+                policy: &config.cleanup,
+            },
+        )?;
+        let sessions = SessionManager::new(&config.session, repo.layout_root());
+        let mut log = plan.warnings.clone();
+        let mut removed = Vec::new();
+
+        for entry in plan.to_remove {
+            if keep.is_some_and(|keep| entry.is_at(keep)) {
+                continue;
+            }
+            if entry.info.safety.has_uncommitted_changes {
+                log.push(format!("skipped {} (uncommitted changes)", entry.name));
+                continue;
+            }
+            let agent = WorktreeMetadata::load(repo.layout_root(), &entry.name)?.agent;
+            if agent.is_some_and(|agent| AgentRunner::status(&agent) == AgentStatus::Running) {
+                log.push(format!("skipped {} (agent running)", entry.name));
+                continue;
+            }
+            if sessions.exists(&entry.name) {
+                log.push(format!("skipped {} (session running)", entry.name));
+                continue;
+            }
+
+            if !dry_run {
+                Self::remove_and_release(repo, &entry.path, false)?;
+            }
+            log.push(format!(
+                "{} {} ({}, {:.0} days stale)",
+                if dry_run { "would remove" } else { "removed" },
+                entry.name,
+                entry.reason,
+                entry.info.staleness_days.unwrap_or(0.0)
+            ));
+            removed.push(entry.name);
+        }
+
+        for entry in plan.skipped {
+            log.push(format!("skipped {} ({})", entry.name, entry.reason));
+        }
+
+        if let Some(log_file) = log_file {
+            Self::append_cleanup_log(log_file, &log)?;
+        }
+
+        Ok(removed)
+    }
+
+    fn append_cleanup_log(log_file: &Path, entries: &[String]) -> Result<()> {
+        use std::io::Write;
+
+        if let Some(parent) = log_file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log_file)?;
+        let timestamp = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+
+        if entries.is_empty() {
+            writeln!(file, "{} nothing to clean up", timestamp)?;
+        }
+        for entry in entries {
+            writeln!(file, "{} {}", timestamp, entry)?;
+        }
+
+        Ok(())
+    }
+
+    fn unix_now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::testing::{git, init_repo};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_open_finds_every_worktree_first() {
+        let temp_dir = TempDir::new().unwrap();
+        let main = temp_dir.path().join("main");
+        init_repo(&main);
+        let feature = temp_dir.path().join("wt").join("feature");
+        git(
+            &main,
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "feature",
+                feature.to_str().unwrap(),
+            ],
+        );
+        let repo = GitRepo::discover(&main).unwrap();
+
+        let names = vec!["feature".to_string(), "main".to_string()];
+        let found: Vec<PathBuf> = WorktreeCommand::find_all(&repo, &names)
+            .unwrap()
+            .into_iter()
+            .map(|(_, path)| path.canonicalize().unwrap())
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                feature.canonicalize().unwrap(),
+                main.canonicalize().unwrap()
+            ]
+        );
+
+        let names = vec!["feature".to_string(), "missing".to_string()];
+        assert!(matches!(
+            WorktreeCommand::find_all(&repo, &names),
+            Err(KayfabeError::WorktreeNotFound(name)) if name == "missing"
+        ));
+    }
 }
@@ -0,0 +1,151 @@
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Per-worktree bookkeeping stored in `.kayfabe/worktrees/<name>.toml`, with
+/// `/` in the name written as `%2F` so every worktree's file sits side by side
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct WorktreeMetadata {
+    #[serde(skip)]
+    pub name: String,
+    /// Allocation slot; ports and `{slot}` resources derive from it
+    pub slot: Option<u32>,
+    pub ports: Option<PortLease>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub resources: BTreeMap<String, String>,
+    /// Commit a `worktree fanout` worktree was branched from
+    pub base: Option<String>,
+    /// Plan implemented here, by file name in `thoughts/shared/plans`
+    pub plan: Option<String>,
+    /// Most recent agent started with `kayfabe agent run`
+    pub agent: Option<AgentProcess>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortLease {
+    pub start: u16,
+    pub end: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentProcess {
+    pub agent: String,
+    /// Process group leader; stopping the agent signals the whole group
+    pub pid: u32,
+    pub log: PathBuf,
+    /// Unix timestamp
+    pub started_at: u64,
+}
+
+impl WorktreeMetadata {
+    pub fn dir(repo_root: &Path) -> PathBuf {
+        repo_root.join(".kayfabe").join("worktrees")
+    }
+
+    pub fn path(repo_root: &Path, name: &str) -> PathBuf {
+        let key = name.replace('%', "%25").replace('/', "%2F");
+        Self::dir(repo_root).join(format!("{}.toml", key))
+    }
+
+    pub fn load(repo_root: &Path, name: &str) -> Result<Self> {
+        let path = Self::path(repo_root, name);
+
+        if !path.exists() {
+            return Ok(Self {
+                name: name.to_string(),
+                ..Self::default()
+            });
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+        let mut metadata: WorktreeMetadata = toml::from_str(&content).map_err(|e| {
+            crate::error::KayfabeError::Other(format!(
+                "Failed to parse worktree metadata {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        metadata.name = name.to_string();
+
+        Ok(metadata)
+    }
+
+    pub fn save(&self, repo_root: &Path) -> Result<()> {
+        let path = Self::path(repo_root, &self.name);
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let content = toml::to_string_pretty(self).map_err(|e| {
+            crate::error::KayfabeError::Other(format!(
+                "Failed to serialize worktree metadata: {}",
+                e
+            ))
+        })?;
+
+        std::fs::write(&path, content)?;
+
+        Ok(())
+    }
+
+    pub fn remove(repo_root: &Path, name: &str) -> Result<()> {
+        let path = Self::path(repo_root, name);
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+        Ok(())
+    }
+
+    pub fn list(repo_root: &Path) -> Result<Vec<Self>> {
+        let dir = Self::dir(repo_root);
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut all = Vec::new();
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "toml") {
+                let key = path.file_stem().unwrap().to_string_lossy();
+                let name = key.replace("%2F", "/").replace("%25", "%");
+                all.push(Self::load(repo_root, &name)?);
+            }
+        }
+        all.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(all)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_names_with_slashes_are_listed_and_removed() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for name in ["feat/y", "y", "100%"] {
+            let metadata = WorktreeMetadata {
+                name: name.to_string(),
+                slot: Some(1),
+                ..WorktreeMetadata::default()
+            };
+            metadata.save(root).unwrap();
+        }
+
+        let names: Vec<String> = WorktreeMetadata::list(root)
+            .unwrap()
+            .into_iter()
+            .map(|metadata| metadata.name)
+            .collect();
+        assert_eq!(names, vec!["100%", "feat/y", "y"]);
+
+        WorktreeMetadata::remove(root, "feat/y").unwrap();
+        assert_eq!(WorktreeMetadata::list(root).unwrap().len(), 2);
+        assert!(WorktreeMetadata::load(root, "y").unwrap().slot.is_some());
+    }
+}
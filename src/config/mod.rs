@@ -1,7 +1,15 @@
 pub mod global;
+pub mod metadata;
 pub mod project;
 pub mod schema;
+pub mod state;
 
 pub use global::GlobalConfig;
-pub use project::ProjectConfig;
-pub use schema::{AgentConfig, Config, UIConfig, WorktreeConfig};
+pub use metadata::{AgentProcess, PortLease, WorktreeMetadata};
+pub use project::{
+    BuildCacheConfig, CargoTargetStrategy, CleanupPolicy, CleanupRule, ConflictPolicy,
+    LocalFilesConfig, Multiplexer, PaneConfig, PolicyMatch, PortsConfig, ProjectConfig,
+    PromptsConfig, ResourcesConfig, RulesConfig, SeedMode, SessionConfig, ThoughtsConfig,
+};
+pub use schema::{AgentCliConfig, AgentConfig, Config, IdeConfig, UIConfig, WorktreeConfig};
+pub use state::ProjectState;
@@ -1,5 +1,6 @@
 use crate::error::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -12,6 +13,18 @@ pub struct ProjectConfig {
     pub agents: ProjectAgentsConfig,
     #[serde(default)]
     pub hooks: ProjectHooks,
+    #[serde(default)]
+    pub cleanup: CleanupPolicy,
+    #[serde(default)]
+    pub resources: ResourcesConfig,
+    #[serde(default)]
+    pub session: SessionConfig,
+    #[serde(default)]
+    pub thoughts: ThoughtsConfig,
+    #[serde(default)]
+    pub prompts: PromptsConfig,
+    #[serde(default)]
+    pub rules: RulesConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -23,6 +36,101 @@ pub struct ProjectInfo {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ProjectWorktreeConfig {
     pub base_branch: Option<String>,
+    /// Build artifact directories (globs relative to the worktree root)
+    pub artifact_dirs: Option<Vec<String>>,
+    #[serde(default)]
+    pub cache: BuildCacheConfig,
+    /// Untracked files (e.g. `.env`) copied from the anchor into new worktrees
+    #[serde(default)]
+    pub copy: LocalFilesConfig,
+    /// Untracked files symlinked from the anchor into new worktrees
+    #[serde(default)]
+    pub link: LocalFilesConfig,
+}
+
+/// Globs, relative to the anchor checkout, of gitignored files a worktree
+/// needs, e.g.
+///
+/// ```toml
+/// [worktree.copy]
+/// files = [".env", ".envrc", ".vscode/settings.json"]
+/// on_conflict = "backup"
+///
+/// [worktree.link]
+/// files = ["secrets/*.pem"]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct LocalFilesConfig {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
+    #[serde(default)]
+    pub on_conflict: ConflictPolicy,
+}
+
+/// What to do when a destination file already exists and differs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictPolicy {
+    #[default]
+    Skip,
+    Overwrite,
+    /// Move the existing file to `<name>.kayfabe-bak` first
+    Backup,
+}
+
+/// Opt-in strategies for warming build caches in new worktrees, e.g.
+///
+/// ```toml
+/// [worktree.cache]
+/// cargo_target_dir = "per-worktree"
+/// seed = ["target", "web/node_modules"]
+/// seed_mode = "reflink"
+/// symlink = [".gradle/caches"]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct BuildCacheConfig {
+    /// Point `CARGO_TARGET_DIR` into a pool outside the worktree
+    pub cargo_target_dir: Option<CargoTargetStrategy>,
+    /// Pool location, relative to the layout root (default `.kayfabe/cache/target`)
+    pub target_pool: Option<PathBuf>,
+    /// Directories copied from the anchor checkout at create time
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub seed: Vec<String>,
+    #[serde(default)]
+    pub seed_mode: SeedMode,
+    /// Directories symlinked to the anchor checkout's copy
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub symlink: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CargoTargetStrategy {
+    /// `<pool>/<worktree>`: isolated, and deleted with the worktree
+    PerWorktree,
+    /// `<pool>/shared`: every worktree shares one target dir
+    Shared,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum SeedMode {
+    /// Copy-on-write clone where the filesystem supports it, else a plain copy
+    #[default]
+    Reflink,
+    /// Hard links, falling back to copies across filesystems
+    Hardlink,
+}
+
+impl ProjectWorktreeConfig {
+    pub fn artifact_patterns(&self) -> Vec<String> {
+        self.artifact_dirs.clone().unwrap_or_else(|| {
+            crate::git::DEFAULT_ARTIFACT_DIRS
+                .iter()
+                .map(|dir| dir.to_string())
+                .collect()
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -38,6 +146,289 @@ pub struct ProjectHooks {
     pub pre_open: Vec<String>,
 }
 
+/// Non-overlapping resources leased to each worktree, e.g.
+///
+/// ```toml
+/// [resources.ports]
+/// base = 4000
+/// block_size = 10
+///
+/// [resources.named]
+/// DATABASE_NAME = "myapp_{worktree}"
+/// REDIS_DB = "{slot}"
+/// ```
+///
+/// Named values may use `{worktree}` (sanitized to `[A-Za-z0-9_]`) and `{slot}`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ResourcesConfig {
+    pub ports: Option<PortsConfig>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub named: BTreeMap<String, String>,
+}
+
+impl ResourcesConfig {
+    pub fn is_empty(&self) -> bool {
+        self.ports.is_none() && self.named.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortsConfig {
+    #[serde(default = "default_port_base")]
+    pub base: u16,
+    #[serde(default = "default_port_block_size")]
+    pub block_size: u16,
+    #[serde(default = "default_port_max_slots")]
+    pub max_slots: u32,
+}
+
+fn default_port_base() -> u16 {
+    4000
+}
+
+fn default_port_block_size() -> u16 {
+    10
+}
+
+fn default_port_max_slots() -> u32 {
+    100
+}
+
+/// Terminal multiplexer session per worktree, e.g.
+///
+/// ```toml
+/// [session]
+/// multiplexer = "tmux"
+/// layout = "main-vertical"
+/// on_create = true
+///
+/// [[session.panes]]
+/// name = "agent"
+/// command = "claude"
+///
+/// [[session.panes]]
+/// name = "tests"
+/// command = "cargo watch -x test"
+///
+/// [[session.panes]]
+/// name = "shell"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionConfig {
+    #[serde(default)]
+    pub multiplexer: Multiplexer,
+    /// Session name; `{worktree}` and `{project}` are substituted
+    #[serde(default = "default_session_name")]
+    pub name: String,
+    /// Private tmux server socket (`tmux -L`), e.g. to keep sessions apart
+    pub socket: Option<String>,
+    /// tmux layout applied once panes are split (default `tiled`)
+    pub layout: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub panes: Vec<PaneConfig>,
+    /// Start a detached session when a worktree is created
+    #[serde(default)]
+    pub on_create: bool,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            multiplexer: Multiplexer::default(),
+            name: default_session_name(),
+            socket: None,
+            layout: None,
+            panes: Vec::new(),
+            on_create: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Multiplexer {
+    #[default]
+    Tmux,
+    Zellij,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PaneConfig {
+    pub name: Option<String>,
+    /// Typed into the pane's shell, so the pane survives the command exiting
+    pub command: Option<String>,
+}
+
+/// Prefixed with the project so two projects' `api` worktrees don't share a
+/// session
+fn default_session_name() -> String {
+    "{project}-{worktree}".to_string()
+}
+
+/// The shared `thoughts/` directory at the layout root
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThoughtsConfig {
+    /// Symlink `thoughts/` into every new worktree
+    #[serde(default = "default_thoughts_enabled")]
+    pub enabled: bool,
+}
+
+impl Default for ThoughtsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_thoughts_enabled(),
+        }
+    }
+}
+
+fn default_thoughts_enabled() -> bool {
+    true
+}
+
+/// Variables the bundled prompts are rendered with, e.g.
+///
+/// ```toml
+/// [prompts]
+/// ticket_prefix = "PLAT"
+///
+/// [prompts.vars]
+/// test_cmd = "just test"
+/// ```
+///
+/// `vars` overrides anything detected from the project.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptsConfig {
+    #[serde(default = "default_ticket_prefix")]
+    pub ticket_prefix: String,
+    /// Where prompts tell agents to read and write notes
+    #[serde(default = "default_prompts_thoughts_dir")]
+    pub thoughts_dir: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub vars: BTreeMap<String, String>,
+}
+
+impl Default for PromptsConfig {
+    fn default() -> Self {
+        Self {
+            ticket_prefix: default_ticket_prefix(),
+            thoughts_dir: default_prompts_thoughts_dir(),
+            vars: BTreeMap::new(),
+        }
+    }
+}
+
+/// Agents `kayfabe rules sync` writes rule files for when the checkout has
+/// no `.kayfabe/rules/agents.toml`, e.g.
+///
+/// ```toml
+/// [rules]
+/// agents = ["claude", "codex", "cursor", "windsurf"]
+/// ```
+///
+/// `kayfabe rules init` also starts `agents.toml` from this list.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RulesConfig {
+    pub agents: Option<Vec<String>>,
+}
+
+fn default_ticket_prefix() -> String {
+    "ENG".to_string()
+}
+
+fn default_prompts_thoughts_dir() -> String {
+    crate::thoughts::store::DIR.to_string()
+}
+
+/// Declarative cleanup policy, e.g.
+///
+/// ```toml
+/// [cleanup]
+/// max_worktrees = 10
+/// max_disk_mb = 20000
+/// protected_branches = ["release/*"]
+///
+/// [[cleanup.rules]]
+/// pattern = "agent-*"
+/// expire_days = 3
+///
+/// [[cleanup.rules]]
+/// pattern = "release-*"   # no expire_days: never expires
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CleanupPolicy {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<CleanupRule>,
+    pub max_worktrees: Option<usize>,
+    pub max_disk_mb: Option<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub protected_branches: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CleanupRule {
+    /// Glob matched against the worktree name and its branch
+    pub pattern: String,
+    /// Days of inactivity before the worktree expires; `None` means never
+    pub expire_days: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyMatch {
+    Protected(String),
+    Rule(CleanupRule),
+    Default,
+}
+
+impl PolicyMatch {
+    /// Expiry threshold in days, or `None` when the worktree never expires
+    pub fn expire_days(&self, default_days: u64) -> Option<u64> {
+        match self {
+            PolicyMatch::Protected(_) => None,
+            PolicyMatch::Rule(rule) => rule.expire_days,
+            PolicyMatch::Default => Some(default_days),
+        }
+    }
+
+    pub fn describe(&self, default_days: u64) -> String {
+        match self {
+            PolicyMatch::Protected(pattern) => format!("protected branch `{}`", pattern),
+            PolicyMatch::Rule(CleanupRule {
+                pattern,
+                expire_days: Some(days),
+            }) => format!("rule `{}` (expires after {} days)", pattern, days),
+            PolicyMatch::Rule(rule) => format!("rule `{}` (never expires)", rule.pattern),
+            PolicyMatch::Default => format!("default (expires after {} days)", default_days),
+        }
+    }
+}
+
+impl CleanupPolicy {
+    /// Protected branches win over rules; otherwise the first rule whose
+    /// pattern matches the worktree name or branch applies.
+    pub fn match_worktree(&self, name: &str, branch: Option<&str>) -> PolicyMatch {
+        if let Some(pattern) = branch.and_then(|branch| {
+            self.protected_branches
+                .iter()
+                .find(|pattern| glob_matches(pattern, branch))
+        }) {
+            return PolicyMatch::Protected(pattern.clone());
+        }
+
+        self.rules
+            .iter()
+            .find(|rule| {
+                glob_matches(&rule.pattern, name)
+                    || branch.is_some_and(|branch| glob_matches(&rule.pattern, branch))
+            })
+            .map(|rule| PolicyMatch::Rule(rule.clone()))
+            .unwrap_or(PolicyMatch::Default)
+    }
+}
+
+fn glob_matches(pattern: &str, candidate: &str) -> bool {
+    glob::Pattern::new(pattern).is_ok_and(|glob| glob.matches(candidate))
+}
+
 impl ProjectConfig {
     pub fn path(repo_root: &Path) -> PathBuf {
         repo_root.join(".kayfabe").join("config.toml")
@@ -54,10 +445,37 @@ impl ProjectConfig {
         let config: ProjectConfig = toml::from_str(&content).map_err(|e| {
             crate::error::KayfabeError::Other(format!("Failed to parse project config: {}", e))
         })?;
+        config.validate()?;
 
         Ok(config)
     }
 
+    /// Reject values that parse but can't work
+    fn validate(&self) -> Result<()> {
+        if let Some(ports) = &self.resources.ports {
+            if ports.block_size == 0 {
+                return Err(crate::error::KayfabeError::Other(
+                    "Invalid project config: [resources.ports] block_size must be at least 1"
+                        .to_string(),
+                ));
+            }
+        }
+        let patterns = self
+            .cleanup
+            .protected_branches
+            .iter()
+            .chain(self.cleanup.rules.iter().map(|rule| &rule.pattern));
+        for pattern in patterns {
+            glob::Pattern::new(pattern).map_err(|e| {
+                crate::error::KayfabeError::Other(format!(
+                    "Invalid project config: cleanup pattern `{}`: {}",
+                    pattern, e
+                ))
+            })?;
+        }
+        Ok(())
+    }
+
     pub fn save(&self, repo_root: &Path) -> Result<()> {
         let path = Self::path(repo_root);
 
@@ -74,3 +492,75 @@ impl ProjectConfig {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> CleanupPolicy {
+        toml::from_str(
+            r#"
+            protected_branches = ["release/*"]
+
+            [[rules]]
+            pattern = "agent-*"
+            expire_days = 3
+
+            [[rules]]
+            pattern = "release-*"
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_policy_first_matching_rule_wins() {
+        let policy = policy();
+        let matched = policy.match_worktree("agent-7", Some("agent-7"));
+        assert_eq!(matched.expire_days(14), Some(3));
+
+        let matched = policy.match_worktree("release-1.2", None);
+        assert_eq!(matched.expire_days(14), None);
+    }
+
+    #[test]
+    fn test_policy_protected_branch_overrides_rules() {
+        let policy = policy();
+        let matched = policy.match_worktree("agent-hotfix", Some("release/1.2"));
+        assert_eq!(matched, PolicyMatch::Protected("release/*".to_string()));
+        assert_eq!(matched.expire_days(14), None);
+    }
+
+    #[test]
+    fn test_policy_falls_back_to_default() {
+        let policy = policy();
+        let matched = policy.match_worktree("feature-auth", Some("feature-auth"));
+        assert_eq!(matched, PolicyMatch::Default);
+        assert_eq!(matched.expire_days(14), Some(14));
+    }
+
+    #[test]
+    fn test_load_rejects_empty_port_blocks() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = ProjectConfig::path(temp_dir.path());
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "[resources.ports]\nblock_size = 0\n").unwrap();
+        assert!(ProjectConfig::load(temp_dir.path()).is_err());
+
+        std::fs::write(&path, "[resources.ports]\nblock_size = 1\n").unwrap();
+        assert!(ProjectConfig::load(temp_dir.path()).is_ok());
+    }
+
+    #[test]
+    fn test_load_rejects_invalid_cleanup_patterns() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = ProjectConfig::path(temp_dir.path());
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "[[cleanup.rules]]\npattern = \"agent-[\"\n").unwrap();
+        let error = ProjectConfig::load(temp_dir.path()).unwrap_err();
+        assert!(error.to_string().contains("`agent-[`"));
+
+        std::fs::write(&path, "[cleanup]\nprotected_branches = [\"release/**x\"]\n").unwrap();
+        assert!(ProjectConfig::load(temp_dir.path()).is_err());
+    }
+}
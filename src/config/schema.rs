@@ -11,6 +11,12 @@ pub struct Config {
     pub agents: HashMap<String, AgentConfig>,
     #[serde(default)]
     pub ui: UIConfig,
+    /// Extra or overridden IDE definitions, keyed by the name used with `--open`
+    #[serde(default)]
+    pub ide: HashMap<String, IdeConfig>,
+    /// Extra or overridden agent CLIs for `kayfabe agent run`
+    #[serde(default)]
+    pub agent_cli: HashMap<String, AgentCliConfig>,
 }
 
 impl Default for Config {
@@ -30,6 +36,8 @@ impl Default for Config {
             worktree: WorktreeConfig::default(),
             agents,
             ui: UIConfig::default(),
+            ide: HashMap::new(),
+            agent_cli: HashMap::new(),
         }
     }
 }
@@ -64,6 +72,8 @@ pub struct WorktreeConfig {
     pub stale_days: u64,
     #[serde(default)]
     pub auto_cleanup: bool,
+    #[serde(default = "default_auto_cleanup_interval_hours")]
+    pub auto_cleanup_interval_hours: u64,
     #[serde(default = "default_true")]
     pub protect_unmerged: bool,
 }
@@ -75,6 +85,7 @@ impl Default for WorktreeConfig {
             naming: default_naming(),
             stale_days: default_stale_days(),
             auto_cleanup: false,
+            auto_cleanup_interval_hours: default_auto_cleanup_interval_hours(),
             protect_unmerged: true,
         }
     }
@@ -89,6 +100,28 @@ pub struct AgentConfig {
     pub location: String,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdeConfig {
+    pub command: String,
+    /// Arguments, with `{path}`, `{file}` and `{name}` substituted
+    #[serde(default = "default_ide_args")]
+    pub args: Vec<String>,
+    /// Run in the foreground and wait for it to exit (terminal editors)
+    #[serde(default)]
+    pub wait: bool,
+    /// Flag that opens a new window instead of reusing the current one
+    #[serde(default)]
+    pub new_window: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentCliConfig {
+    pub command: String,
+    /// Arguments for a non-interactive run, with `{prompt}` substituted
+    #[serde(default = "default_agent_cli_args")]
+    pub args: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UIConfig {
     #[serde(default = "default_true")]
@@ -113,6 +146,14 @@ fn default_ide() -> String {
     "windsurf".to_string()
 }
 
+fn default_ide_args() -> Vec<String> {
+    vec!["{file}".to_string()]
+}
+
+fn default_agent_cli_args() -> Vec<String> {
+    vec!["{prompt}".to_string()]
+}
+
 fn default_base_branch() -> String {
     "main".to_string()
 }
@@ -129,6 +170,10 @@ fn default_stale_days() -> u64 {
     14
 }
 
+fn default_auto_cleanup_interval_hours() -> u64 {
+    24
+}
+
 fn default_template() -> String {
     "default".to_string()
 }
@@ -0,0 +1,48 @@
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Machine-maintained bookkeeping for a layout, kept separate from the
+/// user-edited `config.toml`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ProjectState {
+    /// Unix timestamp of the last automatic cleanup run
+    pub last_auto_cleanup: Option<u64>,
+}
+
+impl ProjectState {
+    pub fn path(repo_root: &Path) -> PathBuf {
+        repo_root.join(".kayfabe").join("state")
+    }
+
+    pub fn load(repo_root: &Path) -> Result<Self> {
+        let path = Self::path(repo_root);
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+        let state: ProjectState = toml::from_str(&content).map_err(|e| {
+            crate::error::KayfabeError::Other(format!("Failed to parse project state: {}", e))
+        })?;
+
+        Ok(state)
+    }
+
+    pub fn save(&self, repo_root: &Path) -> Result<()> {
+        let path = Self::path(repo_root);
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let content = toml::to_string_pretty(self).map_err(|e| {
+            crate::error::KayfabeError::Other(format!("Failed to serialize project state: {}", e))
+        })?;
+
+        std::fs::write(&path, content)?;
+
+        Ok(())
+    }
+}
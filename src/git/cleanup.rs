@@ -0,0 +1,481 @@
+use crate::agents::{AgentRunner, AgentStatus};
+use crate::config::{CleanupPolicy, PolicyMatch, WorktreeMetadata};
+use crate::error::Result;
+use crate::git::{DiskUsage, GitRepo, Worktree, WorktreeInfo};
+use std::path::{Path, PathBuf};
+
+pub struct CleanupOptions<'a> {
+    /// Default expiry in days for worktrees not matched by a policy rule
+    pub older_than: u64,
+    pub include_unmerged: bool,
+    pub policy: &'a CleanupPolicy,
+}
+
+#[derive(Debug, Clone)]
+pub struct CleanupEntry {
+    pub path: PathBuf,
+    pub name: String,
+    pub info: WorktreeInfo,
+    pub policy: PolicyMatch,
+    pub reason: String,
+    /// Whether `max_worktrees`/`max_disk_mb` may evict this worktree
+    evictable: bool,
+    size: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+pub struct CleanupPlan {
+    pub to_remove: Vec<CleanupEntry>,
+    pub skipped: Vec<CleanupEntry>,
+    pub kept: Vec<CleanupEntry>,
+    pub warnings: Vec<String>,
+}
+
+enum CleanupVerdict {
+    Remove(String),
+    Skip(String),
+    Keep(String),
+    /// Kept and exempt from `max_worktrees`/`max_disk_mb` eviction
+    Protect(String),
+}
+
+pub struct CleanupPlanner;
+
+impl CleanupPlanner {
+    /// Decide which worktrees to remove. Each worktree is first judged on its
+    /// own (policy rule, age, merge state and, with the `remote` feature, PR
+    /// state); then `max_worktrees` and `max_disk_mb` evict the stalest
+    /// remaining worktrees that have expired (see `is_evictable`).
+    pub fn plan(repo: &GitRepo, options: &CleanupOptions) -> Result<CleanupPlan> {
+        let current_dir = std::env::current_dir()?;
+        let worktrees = repo.list_worktrees()?;
+        let mut plan = CleanupPlan::default();
+
+        let base_branch = repo
+            .get_default_branch()
+            .unwrap_or_else(|_| "main".to_string());
+
+        #[cfg(feature = "remote")]
+        let forge = Self::forge_client(repo, &mut plan.warnings);
+
+        for wt_path in worktrees {
+            let info = Worktree::get_info(&wt_path, &base_branch)?;
+
+            if info.is_main {
+                continue;
+            }
+
+            let name = repo.worktree_name(&wt_path);
+            let policy = options.policy.match_worktree(&name, info.branch.as_deref());
+            let expire_days = policy.expire_days(options.older_than);
+            let is_current = current_dir.starts_with(&wt_path);
+
+            let verdict = Self::verdict(&info, &policy, expire_days, options.include_unmerged);
+
+            #[cfg(feature = "remote")]
+            let verdict = match forge
+                .as_ref()
+                .filter(|_| expire_days.is_some())
+                .and_then(|forge| Self::remote_status(repo, forge, &info, &mut plan.warnings))
+            {
+                Some(remote) => Self::remote_verdict(
+                    &info,
+                    &remote,
+                    expire_days.unwrap_or_default(),
+                    options.include_unmerged,
+                )
+                .unwrap_or(verdict),
+                None => verdict,
+            };
+
+            let agent_running = WorktreeMetadata::load(repo.layout_root(), &name)?
+                .agent
+                .is_some_and(|agent| AgentRunner::status(&agent) == AgentStatus::Running);
+            let evictable =
+                Self::is_evictable(&info, &verdict, expire_days, is_current || agent_running);
+
+            let mut entry = CleanupEntry {
+                path: wt_path,
+                name,
+                info,
+                policy,
+                reason: String::new(),
+                evictable,
+                size: None,
+            };
+
+            match verdict {
+                CleanupVerdict::Remove(_) if is_current => {
+                    entry.reason = "current worktree".to_string();
+                    plan.skipped.push(entry);
+                }
+                CleanupVerdict::Remove(reason) => {
+                    entry.reason = reason;
+                    plan.to_remove.push(entry);
+                }
+                CleanupVerdict::Skip(reason) => {
+                    entry.reason = reason;
+                    plan.skipped.push(entry);
+                }
+                CleanupVerdict::Protect(reason)
+                    if Self::is_stale(&entry.info, expire_days.unwrap_or(u64::MAX)) =>
+                {
+                    entry.reason = reason;
+                    plan.skipped.push(entry);
+                }
+                CleanupVerdict::Keep(reason) | CleanupVerdict::Protect(reason) => {
+                    entry.reason = reason;
+                    plan.kept.push(entry);
+                }
+            }
+        }
+
+        Self::apply_limits(&mut plan, options.policy)?;
+
+        Ok(plan)
+    }
+
+    fn verdict(
+        info: &WorktreeInfo,
+        policy: &PolicyMatch,
+        expire_days: Option<u64>,
+        include_unmerged: bool,
+    ) -> CleanupVerdict {
+        let Some(expire_days) = expire_days else {
+            return CleanupVerdict::Protect(match policy {
+                PolicyMatch::Protected(_) => "protected branch".to_string(),
+                _ => "never expires".to_string(),
+            });
+        };
+
+        if !Self::is_stale(info, expire_days) {
+            CleanupVerdict::Keep("not stale".to_string())
+        } else if info.safety.is_safe_to_remove {
+            CleanupVerdict::Remove("merged".to_string())
+        } else if include_unmerged {
+            CleanupVerdict::Remove("unmerged".to_string())
+        } else {
+            CleanupVerdict::Skip("unmerged work".to_string())
+        }
+    }
+
+    /// Whether `max_worktrees`/`max_disk_mb` may remove a worktree: only
+    /// once it has expired, even if its commits are unmerged (the branch is
+    /// kept), and never with uncommitted changes or while it is `in_use` as
+    /// the current worktree or by a running agent
+    fn is_evictable(
+        info: &WorktreeInfo,
+        verdict: &CleanupVerdict,
+        expire_days: Option<u64>,
+        in_use: bool,
+    ) -> bool {
+        !in_use
+            && !info.safety.has_uncommitted_changes
+            && !matches!(verdict, CleanupVerdict::Protect(_))
+            && expire_days.is_some_and(|days| Self::is_stale(info, days))
+    }
+
+    fn is_stale(info: &WorktreeInfo, older_than: u64) -> bool {
+        info.staleness_days
+            .is_some_and(|days| days >= older_than as f64)
+    }
+
+    /// Enforce `max_worktrees` and then `max_disk_mb` by moving the stalest
+    /// evictable worktrees from `kept`/`skipped` to `to_remove`.
+    fn apply_limits(plan: &mut CleanupPlan, policy: &CleanupPolicy) -> Result<()> {
+        if let Some(max) = policy.max_worktrees {
+            while plan.kept.len() + plan.skipped.len() > max {
+                let reason = format!("exceeds max_worktrees ({})", max);
+                if !Self::evict_stalest(plan, reason) {
+                    break;
+                }
+            }
+        }
+
+        if let Some(max_mb) = policy.max_disk_mb {
+            let limit = max_mb * 1024 * 1024;
+            for entry in plan.kept.iter_mut().chain(plan.skipped.iter_mut()) {
+                entry.size = Some(DiskUsage::measure(&entry.path, &[])?.total);
+            }
+
+            let total = |plan: &CleanupPlan| -> u64 {
+                plan.kept
+                    .iter()
+                    .chain(plan.skipped.iter())
+                    .filter_map(|entry| entry.size)
+                    .sum()
+            };
+
+            while total(plan) > limit {
+                let reason = format!("exceeds max_disk_mb ({})", max_mb);
+                if !Self::evict_stalest(plan, reason) {
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn evict_stalest(plan: &mut CleanupPlan, reason: String) -> bool {
+        let stalest = |entries: &[CleanupEntry]| {
+            entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| entry.evictable)
+                .max_by(|(_, a), (_, b)| {
+                    let a = a.info.staleness_days.unwrap_or(0.0);
+                    let b = b.info.staleness_days.unwrap_or(0.0);
+                    a.total_cmp(&b)
+                })
+                .map(|(index, entry)| (index, entry.info.staleness_days.unwrap_or(0.0)))
+        };
+
+        let mut entry = match (stalest(&plan.kept), stalest(&plan.skipped)) {
+            (Some((k, kept_days)), Some((_, skipped_days))) if kept_days >= skipped_days => {
+                plan.kept.remove(k)
+            }
+            (_, Some((s, _))) => plan.skipped.remove(s),
+            (Some((k, _)), None) => plan.kept.remove(k),
+            (None, None) => return false,
+        };
+
+        entry.reason = reason;
+        plan.to_remove.push(entry);
+        true
+    }
+
+    /// Refine the local verdict with the forge's view of the branch. Returns
+    /// `None` when the forge has nothing that overrides local state.
+    #[cfg(feature = "remote")]
+    fn remote_verdict(
+        info: &WorktreeInfo,
+        remote: &crate::remote::RemoteStatus,
+        expire_days: u64,
+        include_unmerged: bool,
+    ) -> Option<CleanupVerdict> {
+        use crate::remote::PrState;
+
+        let is_stale = Self::is_stale(info, expire_days);
+        let is_clean = !info.safety.has_uncommitted_changes;
+        match remote.pr_state() {
+            // Open PRs are protected no matter how old they are
+            Some(PrState::Open) => Some(CleanupVerdict::Protect(PrState::Open.label().to_string())),
+            // Merged work still waits out the expiry like a local merge
+            Some(PrState::Merged) if !is_stale => {
+                Some(CleanupVerdict::Keep("not stale".to_string()))
+            }
+            // Squash merges leave base..HEAD non-empty, so trust the forge
+            Some(PrState::Merged) if is_clean || include_unmerged => {
+                Some(CleanupVerdict::Remove(PrState::Merged.label().to_string()))
+            }
+            Some(PrState::Merged) => Some(CleanupVerdict::Skip("uncommitted changes".to_string())),
+            _ if remote.branch_deleted && is_clean && is_stale => {
+                Some(CleanupVerdict::Remove("remote branch deleted".to_string()))
+            }
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "remote")]
+    fn forge_client(
+        repo: &GitRepo,
+        warnings: &mut Vec<String>,
+    ) -> Option<crate::remote::ForgeClient> {
+        let url = repo.remote_url("origin")?;
+        match crate::remote::ForgeClient::from_remote_url(&url) {
+            Ok(client) => Some(client),
+            Err(e) => {
+                warnings.push(format!("{} (using local state only)", e));
+                None
+            }
+        }
+    }
+
+    #[cfg(feature = "remote")]
+    fn remote_status(
+        repo: &GitRepo,
+        forge: &crate::remote::ForgeClient,
+        info: &WorktreeInfo,
+        warnings: &mut Vec<String>,
+    ) -> Option<crate::remote::RemoteStatus> {
+        let branch = info.branch.as_deref()?;
+        match forge.status(branch, repo.was_pushed(branch)) {
+            Ok(status) => Some(status),
+            Err(e) => {
+                warnings.push(format!("{}: {} (using local state only)", branch, e));
+                None
+            }
+        }
+    }
+}
+
+impl CleanupEntry {
+    pub fn is_at(&self, path: &Path) -> bool {
+        path == self.path || path.canonicalize().is_ok_and(|path| path == self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::SafetyCheck;
+
+    fn info(days: f64, merged: bool, uncommitted: bool) -> WorktreeInfo {
+        WorktreeInfo {
+            path: PathBuf::from("/repo/wt/feature"),
+            branch: Some("feature".to_string()),
+            is_main: false,
+            staleness_days: Some(days),
+            safety: SafetyCheck {
+                has_uncommitted_changes: uncommitted,
+                has_unmerged_commits: !merged,
+                is_safe_to_remove: merged && !uncommitted,
+            },
+        }
+    }
+
+    fn entry(name: &str, days: f64, evictable: bool) -> CleanupEntry {
+        CleanupEntry {
+            path: PathBuf::from("/repo/wt").join(name),
+            name: name.to_string(),
+            info: info(days, false, false),
+            policy: PolicyMatch::Default,
+            reason: "unmerged work".to_string(),
+            evictable,
+            size: None,
+        }
+    }
+
+    fn reason(verdict: CleanupVerdict) -> String {
+        match verdict {
+            CleanupVerdict::Remove(reason) => format!("remove: {}", reason),
+            CleanupVerdict::Skip(reason) => format!("skip: {}", reason),
+            CleanupVerdict::Keep(reason) => format!("keep: {}", reason),
+            CleanupVerdict::Protect(reason) => format!("protect: {}", reason),
+        }
+    }
+
+    #[test]
+    fn test_verdict() {
+        let default = PolicyMatch::Default;
+        let verdict = |info: &WorktreeInfo, include_unmerged: bool| {
+            reason(CleanupPlanner::verdict(
+                info,
+                &default,
+                Some(7),
+                include_unmerged,
+            ))
+        };
+
+        assert_eq!(verdict(&info(2.0, true, false), false), "keep: not stale");
+        assert_eq!(verdict(&info(9.0, true, false), false), "remove: merged");
+        assert_eq!(
+            verdict(&info(9.0, false, false), false),
+            "skip: unmerged work"
+        );
+        assert_eq!(verdict(&info(9.0, false, false), true), "remove: unmerged");
+
+        let protected = PolicyMatch::Protected("release/*".to_string());
+        assert_eq!(
+            reason(CleanupPlanner::verdict(
+                &info(90.0, true, false),
+                &protected,
+                None,
+                false
+            )),
+            "protect: protected branch"
+        );
+    }
+
+    #[test]
+    fn test_only_expired_unused_worktrees_are_evictable() {
+        let skip = CleanupVerdict::Skip("unmerged work".to_string());
+        let evictable = |info: &WorktreeInfo, in_use: bool| {
+            CleanupPlanner::is_evictable(info, &skip, Some(7), in_use)
+        };
+
+        assert!(evictable(&info(9.0, false, false), false));
+        assert!(!evictable(&info(2.0, true, false), false));
+        assert!(!evictable(&info(9.0, false, true), false));
+        assert!(!evictable(&info(9.0, false, false), true));
+        assert!(!CleanupPlanner::is_evictable(
+            &info(90.0, false, false),
+            &CleanupVerdict::Protect("open PR".to_string()),
+            None,
+            false
+        ));
+    }
+
+    #[test]
+    fn test_max_worktrees_evicts_stalest_evictable() {
+        let mut plan = CleanupPlan {
+            kept: vec![entry("fresh", 1.0, false), entry("old", 20.0, true)],
+            skipped: vec![entry("older", 30.0, true), entry("dirty", 40.0, false)],
+            ..CleanupPlan::default()
+        };
+        let policy = CleanupPolicy {
+            max_worktrees: Some(1),
+            ..CleanupPolicy::default()
+        };
+
+        CleanupPlanner::apply_limits(&mut plan, &policy).unwrap();
+
+        let removed: Vec<&str> = plan.to_remove.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(removed, vec!["older", "old"]);
+        assert_eq!(plan.to_remove[0].reason, "exceeds max_worktrees (1)");
+        // Over the limit, but nothing else may be evicted
+        assert_eq!(plan.kept.len() + plan.skipped.len(), 2);
+    }
+
+    #[cfg(feature = "remote")]
+    #[test]
+    fn test_remote_verdict() {
+        use crate::remote::{PrState, PullRequest, RemoteStatus};
+
+        let pr = |state| RemoteStatus {
+            pull_request: Some(PullRequest {
+                number: 1,
+                state,
+                url: String::new(),
+            }),
+            branch_deleted: false,
+        };
+        let verdict = |info: &WorktreeInfo, remote: &RemoteStatus| {
+            CleanupPlanner::remote_verdict(info, remote, 7, false).map(reason)
+        };
+
+        // Open PRs are protected however old
+        assert_eq!(
+            verdict(&info(90.0, false, false), &pr(PrState::Open)).as_deref(),
+            Some("protect: PR open")
+        );
+        // Squash-merged: unmerged locally, but the forge says merged
+        assert_eq!(
+            verdict(&info(9.0, false, false), &pr(PrState::Merged)).as_deref(),
+            Some("remove: PR merged")
+        );
+        assert_eq!(
+            verdict(&info(2.0, false, false), &pr(PrState::Merged)).as_deref(),
+            Some("keep: not stale")
+        );
+        assert_eq!(
+            verdict(&info(9.0, false, true), &pr(PrState::Merged)).as_deref(),
+            Some("skip: uncommitted changes")
+        );
+
+        let deleted = RemoteStatus {
+            branch_deleted: true,
+            ..RemoteStatus::default()
+        };
+        assert_eq!(
+            verdict(&info(9.0, false, false), &deleted).as_deref(),
+            Some("remove: remote branch deleted")
+        );
+        assert_eq!(verdict(&info(2.0, false, false), &deleted), None);
+        assert_eq!(
+            verdict(&info(9.0, false, false), &pr(PrState::Closed)),
+            None
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use crate::error::Result;
+use std::path::{Path, PathBuf};
+
+/// Build artifact directories tracked when a project doesn't configure its own
+pub const DEFAULT_ARTIFACT_DIRS: &[&str] = &["target", "**/node_modules"];
+
+#[derive(Debug, Clone, Default)]
+pub struct DiskUsage {
+    /// Total bytes under the worktree, artifacts included
+    pub total: u64,
+    /// Bytes inside build artifact directories
+    pub artifacts: u64,
+    pub artifact_dirs: Vec<PathBuf>,
+}
+
+impl DiskUsage {
+    /// Measure `root` without following symlinks. Directories whose path
+    /// relative to `root` matches one of `artifact_patterns` (globs such as
+    /// `target` or `**/node_modules`) are counted as build artifacts.
+    pub fn measure(root: &Path, artifact_patterns: &[String]) -> Result<Self> {
+        let patterns: Vec<glob::Pattern> = artifact_patterns
+            .iter()
+            .filter_map(|pattern| glob::Pattern::new(pattern.trim_end_matches('/')).ok())
+            .collect();
+
+        let mut usage = Self::default();
+        usage.walk(root, Path::new(""), &patterns)?;
+        Ok(usage)
+    }
+
+    pub fn source(&self) -> u64 {
+        self.total - self.artifacts
+    }
+
+    /// Delete every artifact directory, returning the bytes reclaimed
+    pub fn clear_artifacts(&self) -> Result<u64> {
+        for dir in &self.artifact_dirs {
+            std::fs::remove_dir_all(dir)?;
+        }
+        Ok(self.artifacts)
+    }
+
+    fn walk(&mut self, path: &Path, relative: &Path, patterns: &[glob::Pattern]) -> Result<()> {
+        for entry in std::fs::read_dir(path)? {
+            let entry = entry?;
+            let relative = relative.join(entry.file_name());
+            let metadata = entry.metadata()?;
+
+            if !metadata.is_dir() {
+                self.total += metadata.len();
+            } else if relative == Path::new(".git") {
+                continue;
+            } else if patterns
+                .iter()
+                .any(|pattern| pattern.matches_path(&relative))
+            {
+                let size = Self::size_of(&entry.path())?;
+                self.total += size;
+                self.artifacts += size;
+                self.artifact_dirs.push(entry.path());
+            } else {
+                self.walk(&entry.path(), &relative, patterns)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn size_of(path: &Path) -> Result<u64> {
+        let metadata = std::fs::symlink_metadata(path)?;
+        if !metadata.is_dir() {
+            return Ok(metadata.len());
+        }
+
+        let mut total = 0;
+        for entry in std::fs::read_dir(path)? {
+            total += Self::size_of(&entry?.path())?;
+        }
+        Ok(total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_measure_splits_artifacts() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src").join("main.rs"), "fn main() {}").unwrap();
+        fs::create_dir_all(root.join("target").join("debug")).unwrap();
+        fs::write(root.join("target").join("debug").join("app"), [0u8; 100]).unwrap();
+        fs::create_dir_all(root.join("web").join("node_modules")).unwrap();
+        fs::write(
+            root.join("web").join("node_modules").join("x.js"),
+            [0u8; 50],
+        )
+        .unwrap();
+
+        let patterns: Vec<String> = DEFAULT_ARTIFACT_DIRS
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let usage = DiskUsage::measure(root, &patterns).unwrap();
+
+        assert_eq!(usage.artifacts, 150);
+        assert_eq!(usage.source(), 12);
+        assert_eq!(usage.artifact_dirs.len(), 2);
+    }
+
+    #[test]
+    fn test_clear_artifacts() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        fs::create_dir_all(root.join("node_modules")).unwrap();
+        fs::write(root.join("node_modules").join("x.js"), [0u8; 10]).unwrap();
+        fs::write(root.join("package.json"), "{}").unwrap();
+
+        let usage = DiskUsage::measure(root, &["**/node_modules".to_string()]).unwrap();
+        assert_eq!(usage.clear_artifacts().unwrap(), 10);
+        assert!(!root.join("node_modules").exists());
+        assert!(root.join("package.json").exists());
+    }
+}
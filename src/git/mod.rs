@@ -1,7 +1,13 @@
+pub mod cleanup;
+pub mod disk;
 pub mod repo;
 pub mod root;
+#[cfg(test)]
+pub(crate) mod testing;
 pub mod worktree;
 
+pub use cleanup::{CleanupEntry, CleanupOptions, CleanupPlan, CleanupPlanner};
+pub use disk::{DiskUsage, DEFAULT_ARTIFACT_DIRS};
 pub use repo::GitRepo;
 pub use root::KayfabeRoot;
 pub use worktree::{SafetyCheck, Worktree, WorktreeInfo};
@@ -1,6 +1,7 @@
 use crate::error::{KayfabeError, Result};
-use git2::{BranchType, Repository};
+use git2::{BranchType, Repository, StatusOptions};
 use rand::Rng;
+use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 
 pub struct GitRepo {
@@ -40,7 +41,7 @@ impl GitRepo {
         let layout_root = if is_layout_root {
             path.to_path_buf()
         } else {
-            root.clone()
+            Self::enclosing_layout_root(&root).unwrap_or_else(|| root.clone())
         };
 
         Ok(Self {
@@ -54,6 +55,18 @@ impl GitRepo {
         path.join("main").is_dir() && path.join("wt").is_dir() && path.join(".kayfabe").is_dir()
     }
 
+    /// Find the layout root when discovered from inside `main/` or
+    /// `wt/<name>/`, where the name may itself contain `/`
+    fn enclosing_layout_root(root: &Path) -> Option<PathBuf> {
+        root.ancestors()
+            .skip(1)
+            .find(|ancestor| {
+                (root == ancestor.join("main") || root.starts_with(ancestor.join("wt")))
+                    && Self::is_worktree_layout_root(ancestor)
+            })
+            .map(Path::to_path_buf)
+    }
+
     pub fn get_default_branch(&self) -> Result<String> {
         let main_exists = self.branch_exists("main")?;
         let master_exists = self.branch_exists("master")?;
@@ -114,6 +127,148 @@ impl GitRepo {
         Ok(())
     }
 
+    /// Check that `revert_worktree_layout` can run, without changing
+    /// anything. Returns the layout-root entries (`.kayfabe/`, `thoughts/`)
+    /// that will be carried into the checkout.
+    pub fn check_revert_worktree_layout(&self) -> Result<Vec<OsString>> {
+        if !self.is_worktree_layout() {
+            return Err(KayfabeError::Other(
+                "Repo is not in the worktree layout".to_string(),
+            ));
+        }
+
+        let worktrees = self.list_worktrees()?;
+        if worktrees.len() > 1 {
+            return Err(KayfabeError::Other(format!(
+                "Remove the other {} worktrees first (kayfabe worktree remove <name>)",
+                worktrees.len() - 1
+            )));
+        }
+
+        let root = &self.layout_root;
+        let main = root.join("main");
+        let wt = root.join("wt");
+        if std::fs::read_dir(&wt)?.next().is_some() {
+            return Err(KayfabeError::Other(format!(
+                "{} is not empty",
+                wt.display()
+            )));
+        }
+
+        // Everything else at the layout root must fit into the checkout
+        let mut carried = Vec::new();
+        for entry in std::fs::read_dir(root)? {
+            let name = entry?.file_name();
+            if name == "main" || name == "wt" {
+                continue;
+            }
+            if name != ".kayfabe" && name != "thoughts" {
+                return Err(KayfabeError::Other(format!(
+                    "Move {} out of {} first",
+                    name.to_string_lossy(),
+                    root.display()
+                )));
+            }
+            carried.push(name);
+        }
+
+        for name in &carried {
+            let from = root.join(name);
+            let to = main.join(name);
+            // The store is linked into the checkout and will replace the link
+            if !to.exists() || Self::is_symlink(&to) {
+                continue;
+            }
+            if !from.is_dir() || !to.is_dir() {
+                return Err(KayfabeError::Other(format!(
+                    "{} already exists in the checkout",
+                    name.to_string_lossy()
+                )));
+            }
+            for child in std::fs::read_dir(&from)? {
+                let child = child?.file_name();
+                if to.join(&child).exists() {
+                    return Err(KayfabeError::Other(format!(
+                        "{}/{} already exists in the checkout",
+                        name.to_string_lossy(),
+                        child.to_string_lossy()
+                    )));
+                }
+            }
+        }
+        Ok(carried)
+    }
+
+    /// Undo `convert_to_worktree_layout`: move `main/` back up to the layout
+    /// root, keeping `.kayfabe/` and the `thoughts/` store in it. Refuses
+    /// while any other worktree exists.
+    pub fn revert_worktree_layout(&self) -> Result<()> {
+        let carried = self.check_revert_worktree_layout()?;
+        let root = &self.layout_root;
+
+        let link = root.join("main").join("thoughts");
+        if Self::is_symlink(&link) {
+            std::fs::remove_file(&link)?;
+        }
+
+        let parent = root
+            .parent()
+            .ok_or_else(|| KayfabeError::Other("Cannot get parent directory".to_string()))?;
+        let repo_name = root
+            .file_name()
+            .ok_or_else(|| KayfabeError::Other("Cannot get repo name".to_string()))?;
+
+        let mut rng = rand::thread_rng();
+        let random_num: u32 = rng.gen_range(10000..99999);
+        let tmp_move = parent.join(format!(
+            ".{}.tmp-move.{}",
+            repo_name.to_string_lossy(),
+            random_num
+        ));
+
+        std::fs::rename(root, &tmp_move)?;
+        std::fs::rename(tmp_move.join("main"), root)?;
+        std::fs::remove_dir(tmp_move.join("wt"))?;
+        for name in &carried {
+            let from = tmp_move.join(name);
+            let to = root.join(name);
+            if to.exists() {
+                for child in std::fs::read_dir(&from)? {
+                    let child = child?.file_name();
+                    std::fs::rename(from.join(&child), to.join(&child))?;
+                }
+                std::fs::remove_dir(&from)?;
+            } else {
+                std::fs::rename(&from, &to)?;
+            }
+        }
+        std::fs::remove_dir(&tmp_move)?;
+        Ok(())
+    }
+
+    fn is_symlink(path: &Path) -> bool {
+        path.symlink_metadata()
+            .is_ok_and(|meta| meta.file_type().is_symlink())
+    }
+
+    /// Run `git` in `dir`, returning its output without the trailing newline
+    pub fn git_output(dir: &Path, args: &[&str]) -> Result<String> {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()?;
+        if !output.status.success() {
+            return Err(KayfabeError::Other(format!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .trim_end()
+            .to_string())
+    }
+
     pub fn branch_exists(&self, name: &str) -> Result<bool> {
         Ok(self.repo.find_branch(name, BranchType::Local).is_ok())
     }
@@ -125,6 +280,28 @@ impl GitRepo {
             .is_ok())
     }
 
+    /// Whether `name` was pushed to a branch of the same name on origin,
+    /// judged by its upstream config, which `fetch --prune` leaves in place
+    /// after the remote branch is deleted, or by the remote-tracking ref
+    pub fn was_pushed(&self, name: &str) -> bool {
+        let upstream = self.repo.config().ok().and_then(|config| {
+            let remote = config.get_string(&format!("branch.{}.remote", name)).ok()?;
+            let merge = config.get_string(&format!("branch.{}.merge", name)).ok()?;
+            Some((remote, merge))
+        });
+        match upstream {
+            Some((remote, merge)) => remote == "origin" && merge == format!("refs/heads/{}", name),
+            None => self.remote_branch_exists(name).unwrap_or(false),
+        }
+    }
+
+    pub fn remote_url(&self, name: &str) -> Option<String> {
+        self.repo
+            .find_remote(name)
+            .ok()
+            .and_then(|remote| remote.url().map(|url| url.to_string()))
+    }
+
     pub fn fetch(&self) -> Result<()> {
         let mut remote = self.repo.find_remote("origin").ok();
         if let Some(ref mut remote) = remote {
@@ -133,12 +310,22 @@ impl GitRepo {
         Ok(())
     }
 
-    pub fn create_worktree(&self, name: &str, base_branch: &str) -> Result<PathBuf> {
-        let wt_path = if self.is_worktree_layout() {
+    pub fn worktree_path(&self, name: &str) -> PathBuf {
+        if self.is_worktree_layout() {
             self.layout_root.join("wt").join(name)
         } else {
             self.root.join(name)
-        };
+        }
+    }
+
+    /// Full sha of the commit `rev` points at
+    pub fn resolve_commit(&self, rev: &str) -> Result<String> {
+        let commit = self.repo.revparse_single(rev)?.peel_to_commit()?;
+        Ok(commit.id().to_string())
+    }
+
+    pub fn create_worktree(&self, name: &str, base_branch: &str) -> Result<PathBuf> {
+        let wt_path = self.worktree_path(name);
 
         if wt_path.exists() {
             return Err(KayfabeError::WorktreeExists(name.to_string()));
@@ -187,21 +374,182 @@ impl GitRepo {
         Ok(worktrees)
     }
 
-    pub fn remove_worktree(&self, path: &Path) -> Result<()> {
-        std::process::Command::new("git")
-            .args(["worktree", "remove", path.to_str().unwrap()])
+    /// The main worktree (`main/` in the worktree layout), which git always lists first
+    pub fn anchor_path(&self) -> Result<PathBuf> {
+        self.list_worktrees()?
+            .into_iter()
+            .next()
+            .ok_or_else(|| KayfabeError::Other("No worktrees found".to_string()))
+    }
+
+    /// Look up a worktree by name (`main` for the anchor)
+    pub fn find_worktree(&self, name: &str) -> Result<PathBuf> {
+        self.list_worktrees()?
+            .into_iter()
+            .find(|path| self.worktree_name(path) == name)
+            .ok_or_else(|| KayfabeError::WorktreeNotFound(name.to_string()))
+    }
+
+    /// The name a worktree was created with: its path under `wt/`, which
+    /// contains `/` for names like `feat/login`, else its directory name
+    pub fn worktree_name(&self, path: &Path) -> String {
+        match path.strip_prefix(self.layout_root.join("wt")) {
+            Ok(relative) if !relative.as_os_str().is_empty() => {
+                relative.to_string_lossy().to_string()
+            }
+            _ => path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn current_branch(&self) -> Option<String> {
+        let head = self.repo.head().ok()?;
+        if head.is_branch() {
+            head.shorthand().map(str::to_string)
+        } else {
+            head.target().map(|oid| oid.to_string()[..7].to_string())
+        }
+    }
+
+    /// Whether the worktree has uncommitted changes or untracked files.
+    /// Uses libgit2 directly so it is cheap enough for a shell prompt.
+    pub fn is_dirty(&self) -> Result<bool> {
+        let mut options = StatusOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(false)
+            .exclude_submodules(true);
+        Ok(!self.repo.statuses(Some(&mut options))?.is_empty())
+    }
+
+    /// Add `pattern` to the shared `info/exclude`, which applies to every worktree
+    pub fn add_exclude(&self, pattern: &str) -> Result<()> {
+        let output = std::process::Command::new("git")
+            .args(["rev-parse", "--path-format=absolute", "--git-common-dir"])
             .current_dir(&self.root)
             .output()?;
+        let common_dir = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+        let exclude = common_dir.join("info").join("exclude");
+        let existing = std::fs::read_to_string(&exclude).unwrap_or_default();
+        if existing.lines().any(|line| line.trim() == pattern) {
+            return Ok(());
+        }
+
+        if let Some(parent) = exclude.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut content = existing;
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str(pattern);
+        content.push('\n');
+        std::fs::write(&exclude, content)?;
 
         Ok(())
     }
 
+    pub fn remove_worktree(&self, path: &Path) -> Result<()> {
+        self.git_worktree_remove(path, false)
+    }
+
     pub fn remove_worktree_force(&self, path: &Path) -> Result<()> {
-        std::process::Command::new("git")
-            .args(["worktree", "remove", "--force", path.to_str().unwrap()])
+        self.git_worktree_remove(path, true)
+    }
+
+    fn git_worktree_remove(&self, path: &Path, force: bool) -> Result<()> {
+        let mut args = vec!["worktree", "remove"];
+        if force {
+            args.push("--force");
+        }
+        let output = std::process::Command::new("git")
+            .args(&args)
+            .arg(path)
             .current_dir(&self.root)
             .output()?;
 
+        if !output.status.success() {
+            return Err(KayfabeError::Other(format!(
+                "Failed to remove worktree {}: {}",
+                path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_was_pushed() {
+        let temp_dir = TempDir::new().unwrap();
+        let git = Repository::init(temp_dir.path()).unwrap();
+        let repo = GitRepo::discover(temp_dir.path()).unwrap();
+        let mut config = git.config().unwrap();
+
+        assert!(!repo.was_pushed("feature"));
+
+        // Tracking the base branch isn't a sign the branch was published
+        config.set_str("branch.feature.remote", "origin").unwrap();
+        config
+            .set_str("branch.feature.merge", "refs/heads/main")
+            .unwrap();
+        assert!(!repo.was_pushed("feature"));
+
+        // `push -u`, and the config outlives a pruned remote ref
+        config
+            .set_str("branch.feature.merge", "refs/heads/feature")
+            .unwrap();
+        assert!(repo.was_pushed("feature"));
+    }
+
+    #[test]
+    fn test_revert_worktree_layout() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("proj");
+        std::fs::create_dir(&root).unwrap();
+        Repository::init(&root).unwrap();
+        std::fs::write(root.join("README.md"), "hi\n").unwrap();
+        GitRepo::discover(&root)
+            .unwrap()
+            .convert_to_worktree_layout()
+            .unwrap();
+
+        std::fs::create_dir_all(root.join(".kayfabe")).unwrap();
+        std::fs::write(root.join(".kayfabe").join("config.toml"), "").unwrap();
+        std::fs::create_dir_all(root.join("thoughts").join("shared")).unwrap();
+        std::os::unix::fs::symlink(root.join("thoughts"), root.join("main").join("thoughts"))
+            .unwrap();
+        let repo = GitRepo::discover(&root).unwrap();
+
+        // Nothing moves while something unexpected is in the way
+        std::fs::write(root.join("notes.txt"), "").unwrap();
+        assert!(repo.check_revert_worktree_layout().is_err());
+        assert!(repo.revert_worktree_layout().is_err());
+        assert!(root.join("main").join("README.md").exists());
+        std::fs::remove_file(root.join("notes.txt")).unwrap();
+
+        std::fs::create_dir(root.join("wt").join("feature")).unwrap();
+        assert!(repo.check_revert_worktree_layout().is_err());
+        std::fs::remove_dir(root.join("wt").join("feature")).unwrap();
+
+        let mut carried = repo.check_revert_worktree_layout().unwrap();
+        carried.sort();
+        assert_eq!(carried, vec![".kayfabe", "thoughts"]);
+        assert!(GitRepo::is_symlink(&root.join("main").join("thoughts")));
+
+        repo.revert_worktree_layout().unwrap();
+        assert!(root.join(".git").is_dir());
+        assert!(root.join("README.md").exists());
+        assert!(root.join(".kayfabe").join("config.toml").exists());
+        assert!(root.join("thoughts").join("shared").is_dir());
+        assert!(!root.join("main").exists() && !root.join("wt").exists());
+    }
+}
@@ -0,0 +1,27 @@
+use std::path::Path;
+
+/// Run `git` in `dir` as a fixed test identity, with commits dated 2020
+pub(crate) fn git(dir: &Path, args: &[&str]) {
+    let status = std::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .env("GIT_AUTHOR_DATE", "2020-01-01T00:00:00Z")
+        .env("GIT_COMMITTER_DATE", "2020-01-01T00:00:00Z")
+        .env("GIT_AUTHOR_NAME", "test")
+        .env("GIT_AUTHOR_EMAIL", "test@example.com")
+        .env("GIT_COMMITTER_NAME", "test")
+        .env("GIT_COMMITTER_EMAIL", "test@example.com")
+        .output()
+        .unwrap()
+        .status;
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+/// Create a repository at `main` on branch `main` with one commit
+pub(crate) fn init_repo(main: &Path) {
+    std::fs::create_dir_all(main).unwrap();
+    git(main, &["init", "-q", "-b", "main"]);
+    std::fs::write(main.join("README.md"), "hi\n").unwrap();
+    git(main, &["add", "README.md"]);
+    git(main, &["commit", "-q", "-m", "init"]);
+}
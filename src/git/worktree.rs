@@ -63,30 +63,59 @@ impl Worktree {
         })
     }
 
+    /// Days since the last activity in the worktree: its last commit, or a
+    /// checkout, reset or commit recorded in its `HEAD` and reflog. A worktree
+    /// just created on an old base is therefore fresh. The index is left out,
+    /// as commands that only read the worktree may still rewrite it.
     fn calculate_staleness(path: &Path) -> Result<Option<f64>> {
         let output = std::process::Command::new("git")
             .args(["log", "-1", "--format=%ct"])
             .current_dir(path)
             .output()?;
+        let commit_time = String::from_utf8_lossy(&output.stdout).trim().parse().ok();
 
-        let timestamp_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        if timestamp_str.is_empty() {
+        let output = std::process::Command::new("git")
+            .args(["rev-parse", "--absolute-git-dir"])
+            .current_dir(path)
+            .output()?;
+        let git_dir = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+        let head_time = std::fs::metadata(git_dir.join("HEAD"))
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|since| since.as_secs());
+        let reflog_time = std::fs::read_to_string(git_dir.join("logs").join("HEAD"))
+            .ok()
+            .and_then(|reflog| Self::last_reflog_time(&reflog));
+
+        let Some(last_activity) = [commit_time, head_time, reflog_time]
+            .into_iter()
+            .flatten()
+            .max()
+        else {
             return Ok(None);
-        }
-
-        let timestamp: u64 = timestamp_str.parse().unwrap_or(0);
+        };
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
             .as_secs();
 
-        let days = (now - timestamp) as f64 / 86400.0;
+        let days = now.saturating_sub(last_activity) as f64 / 86400.0;
         Ok(Some(days))
     }
 
+    /// Timestamp of the last reflog entry, which reads
+    /// `<old> <new> <name> <email> <time> <tz>\t<message>`
+    fn last_reflog_time(reflog: &str) -> Option<u64> {
+        let (entry, _) = reflog.lines().last()?.split_once('\t')?;
+        entry.split_whitespace().rev().nth(1)?.parse().ok()
+    }
+
     fn check_safety(path: &Path, base_branch: &str) -> Result<SafetyCheck> {
         let status_output = std::process::Command::new("git")
-            .args(["status", "--porcelain"])
+            // Without optional locks, status only inspects the worktree and
+            // doesn't refresh its index
+            .args(["--no-optional-locks", "status", "--porcelain"])
             .current_dir(path)
             .output()?;
 
@@ -108,3 +137,42 @@ impl Worktree {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::testing::{git, init_repo};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_new_worktree_on_old_base_is_fresh() {
+        let temp_dir = TempDir::new().unwrap();
+        let main = temp_dir.path().join("main");
+        init_repo(&main);
+
+        let wt = temp_dir.path().join("feature");
+        git(
+            &main,
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "feature",
+                wt.to_str().unwrap(),
+            ],
+        );
+
+        let info = Worktree::get_info(&wt, "main").unwrap();
+        assert!(info.staleness_days.unwrap() < 1.0);
+        assert!(info.safety.is_safe_to_remove);
+    }
+
+    #[test]
+    fn test_last_reflog_time() {
+        let reflog = "0000 1111 test <test@example.com> 1577836800 +0000\tcommit (initial): old\n\
+                      1111 2222 test <test@example.com> 1577923200 +0100\tcheckout: moving from main to feature\n";
+        assert_eq!(Worktree::last_reflog_time(reflog), Some(1577923200));
+        assert_eq!(Worktree::last_reflog_time(""), None);
+    }
+}
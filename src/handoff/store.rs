@@ -0,0 +1,300 @@
+use crate::error::{KayfabeError, Result};
+use crate::thoughts::{slugify, Thoughts};
+use chrono::{DateTime, Local, SecondsFormat};
+use std::path::{Path, PathBuf};
+
+/// Section headings of a new handoff, matching `agents/create_handoff.md`
+const SECTIONS: &[&str] = &[
+    "Task(s)",
+    "Critical References",
+    "Recent changes",
+    "Learnings",
+    "Artifacts",
+    "Action Items & Next Steps",
+    "Other Notes",
+];
+
+/// Where the worktree was when a handoff was written
+#[derive(Debug, Clone, Default)]
+pub struct GitSnapshot {
+    pub branch: Option<String>,
+    pub commit: Option<String>,
+    /// `git status --short` output
+    pub status: String,
+}
+
+/// A handoff document, read back from its frontmatter
+#[derive(Debug, Clone)]
+pub struct Handoff {
+    pub path: PathBuf,
+    pub worktree: String,
+    pub date: String,
+    pub topic: String,
+    pub branch: Option<String>,
+    pub commit: Option<String>,
+}
+
+/// Handoffs live under `thoughts/shared/handoffs/<worktree>/`, named `YYYY-MM-DD_HH-MM-SS_<description>.md` so they sort by time
+pub struct HandoffStore {
+    dir: PathBuf,
+}
+
+impl HandoffStore {
+    pub fn new(layout_root: &Path) -> Self {
+        Self {
+            dir: Thoughts::new(layout_root).shared("handoffs"),
+        }
+    }
+
+    /// Write a new handoff. An empty `body` gets the standard section
+    /// headings to fill in.
+    pub fn create(
+        &self,
+        worktree: &str,
+        repository: &str,
+        topic: &str,
+        body: &str,
+        snapshot: &GitSnapshot,
+    ) -> Result<PathBuf> {
+        let now = Local::now();
+        let dir = self.dir.join(worktree);
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(format!(
+            "{}_{}.md",
+            now.format("%Y-%m-%d_%H-%M-%S"),
+            slugify(topic)
+        ));
+        if path.exists() {
+            return Err(KayfabeError::Other(format!(
+                "Handoff already exists: {}",
+                path.display()
+            )));
+        }
+
+        std::fs::write(
+            &path,
+            render(&now, worktree, repository, topic, body, snapshot),
+        )?;
+        Ok(path)
+    }
+
+    /// Handoffs for `worktree` (or every worktree), newest first
+    pub fn list(&self, worktree: Option<&str>) -> Result<Vec<Handoff>> {
+        let dirs: Vec<PathBuf> = match worktree {
+            Some(worktree) => vec![self.dir.join(worktree)],
+            None if self.dir.is_dir() => std::fs::read_dir(&self.dir)?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.is_dir())
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let mut handoffs = Vec::new();
+        for dir in dirs.iter().filter(|dir| dir.is_dir()) {
+            for entry in std::fs::read_dir(dir)? {
+                let path = entry?.path();
+                if path.extension().is_some_and(|ext| ext == "md") {
+                    handoffs.push(Self::read(&path)?);
+                }
+            }
+        }
+
+        // Timestamps only have second precision; break ties by mtime
+        handoffs.sort_by_cached_key(|handoff| {
+            let date = DateTime::parse_from_rfc3339(&handoff.date).ok();
+            let modified = std::fs::metadata(&handoff.path)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            std::cmp::Reverse((date, modified))
+        });
+        Ok(handoffs)
+    }
+
+    pub fn latest(&self, worktree: &str) -> Result<Option<Handoff>> {
+        Ok(self.list(Some(worktree))?.into_iter().next())
+    }
+
+    /// Find a handoff by path, file name or file stem
+    pub fn find(&self, id: &str) -> Result<Handoff> {
+        let path = Path::new(id);
+        if path.is_file() {
+            return Self::read(path);
+        }
+
+        self.list(None)?
+            .into_iter()
+            .find(|handoff| {
+                handoff.path.file_name().is_some_and(|name| name == id)
+                    || handoff.path.file_stem().is_some_and(|stem| stem == id)
+            })
+            .ok_or_else(|| KayfabeError::Other(format!("Handoff not found: {}", id)))
+    }
+
+    pub fn read(path: &Path) -> Result<Handoff> {
+        let content = std::fs::read_to_string(path)?;
+        let field = |key: &str| frontmatter_field(&content, key);
+
+        Ok(Handoff {
+            path: path.to_path_buf(),
+            worktree: field("worktree").unwrap_or_else(|| {
+                path.parent()
+                    .and_then(|dir| dir.file_name())
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            }),
+            date: field("date").unwrap_or_default(),
+            topic: field("topic").unwrap_or_default(),
+            branch: field("branch"),
+            commit: field("git_commit"),
+        })
+    }
+}
+
+fn render(
+    now: &DateTime<Local>,
+    worktree: &str,
+    repository: &str,
+    topic: &str,
+    body: &str,
+    snapshot: &GitSnapshot,
+) -> String {
+    let mut doc = String::from("---\n");
+    doc.push_str(&format!(
+        "date: {}\n",
+        now.to_rfc3339_opts(SecondsFormat::Secs, false)
+    ));
+    doc.push_str(&format!("worktree: {}\n", worktree));
+    if let Some(branch) = &snapshot.branch {
+        doc.push_str(&format!("branch: {}\n", branch));
+    }
+    if let Some(commit) = &snapshot.commit {
+        doc.push_str(&format!("git_commit: {}\n", commit));
+    }
+    doc.push_str(&format!("repository: {}\n", repository));
+    doc.push_str(&format!("topic: {}\n", quote(topic)));
+    doc.push_str("type: handoff\n");
+    doc.push_str("---\n\n");
+    doc.push_str(&format!("# Handoff: {}\n\n", topic));
+
+    if body.trim().is_empty() {
+        for section in SECTIONS {
+            doc.push_str(&format!("## {}\n\n", section));
+        }
+    } else {
+        doc.push_str(body.trim());
+        doc.push_str("\n\n");
+    }
+
+    doc.push_str("## Git Status\n");
+    if snapshot.status.trim().is_empty() {
+        doc.push_str("Clean working tree\n");
+    } else {
+        doc.push_str("```\n");
+        doc.push_str(snapshot.status.trim_end());
+        doc.push_str("\n```\n");
+    }
+
+    doc
+}
+
+/// A double-quoted YAML string, escaped so it stays on one line
+fn quote(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// The value of a string written by `quote`, or `None` if it isn't one
+fn unquote(value: &str) -> Option<String> {
+    let inner = value.strip_prefix('"')?.strip_suffix('"')?;
+    let mut unquoted = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unquoted.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => unquoted.push('\n'),
+            'r' => unquoted.push('\r'),
+            't' => unquoted.push('\t'),
+            c => unquoted.push(c),
+        }
+    }
+    Some(unquoted)
+}
+
+/// `key: value` from the leading `---` block, with quotes removed
+fn frontmatter_field(content: &str, key: &str) -> Option<String> {
+    let mut lines = content.lines();
+    if lines.next()? != "---" {
+        return None;
+    }
+
+    lines
+        .take_while(|line| *line != "---")
+        .filter_map(|line| line.split_once(':'))
+        .find(|(k, _)| k.trim() == key)
+        .map(|(_, value)| {
+            let value = value.trim();
+            unquote(value).unwrap_or_else(|| value.to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_create_and_read_back() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = HandoffStore::new(temp_dir.path());
+        let snapshot = GitSnapshot {
+            branch: Some("feature-auth".to_string()),
+            commit: Some("abc1234".to_string()),
+            status: " M src/lib.rs\n".to_string(),
+        };
+
+        let path = store
+            .create(
+                "feature-auth",
+                "proj",
+                "Token \"refresh\" flow \\ C:\\tmp\n",
+                "",
+                &snapshot,
+            )
+            .unwrap();
+        assert!(path.starts_with(
+            temp_dir
+                .path()
+                .join("thoughts/shared/handoffs/feature-auth")
+        ));
+        assert!(path
+            .to_string_lossy()
+            .ends_with("_token-refresh-flow-c-tmp.md"));
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("## Action Items & Next Steps"));
+        assert!(content.contains(" M src/lib.rs"));
+
+        let handoff = store.latest("feature-auth").unwrap().unwrap();
+        assert_eq!(handoff.topic, "Token \"refresh\" flow \\ C:\\tmp\n");
+        assert_eq!(handoff.branch.as_deref(), Some("feature-auth"));
+        assert_eq!(handoff.commit.as_deref(), Some("abc1234"));
+        assert!(store.latest("other").unwrap().is_none());
+
+        let stem = path.file_stem().unwrap().to_string_lossy().to_string();
+        assert_eq!(store.find(&stem).unwrap().path, path);
+    }
+}
@@ -1,58 +1,287 @@
+use crate::config::{Config, IdeConfig};
 use crate::error::{KayfabeError, Result};
 use std::path::Path;
 use std::process::Command;
 
-#[derive(Debug, Clone, Copy)]
-pub enum IDE {
-    Windsurf,
-    Idea,
-    Code,
+/// A named editor definition, either built in or from `[ide.<name>]` in the
+/// global config
+#[derive(Debug, Clone)]
+pub struct IDE {
+    pub name: String,
+    pub config: IdeConfig,
 }
 
 impl IDE {
-    pub fn parse(s: &str) -> Option<Self> {
-        match s.to_lowercase().as_str() {
-            "windsurf" => Some(IDE::Windsurf),
-            "idea" => Some(IDE::Idea),
-            "code" => Some(IDE::Code),
-            _ => None,
-        }
+    /// Look up `name`, preferring a user definition over the built-in one
+    /// Names match case-insensitively, so `[ide.MyEditor]` is `myeditor`.
+    pub fn resolve(name: &str, config: &Config) -> Option<Self> {
+        let name = name.to_lowercase();
+        let definition = config
+            .ide
+            .iter()
+            .find(|(key, _)| key.to_lowercase() == name)
+            .map(|(_, definition)| definition.clone())
+            .or_else(|| Self::builtin(&name))?;
+
+        Some(Self {
+            name,
+            config: definition,
+        })
     }
 
-    fn command(&self) -> &str {
-        match self {
-            IDE::Windsurf => "windsurf",
-            IDE::Idea => "idea",
-            IDE::Code => "code",
-        }
+    /// Every known IDE, built-ins first, in a stable order
+    pub fn all(config: &Config) -> Vec<Self> {
+        let mut names: Vec<String> = BUILTINS.iter().map(|(name, ..)| name.to_string()).collect();
+        let mut custom: Vec<String> = config
+            .ide
+            .keys()
+            .map(|name| name.to_lowercase())
+            .filter(|name| !names.contains(name))
+            .collect();
+        custom.sort();
+        custom.dedup();
+        names.extend(custom);
+
+        names
+            .iter()
+            .filter_map(|name| Self::resolve(name, config))
+            .collect()
+    }
+
+    fn builtin(name: &str) -> Option<IdeConfig> {
+        BUILTINS.iter().find(|(builtin, ..)| *builtin == name).map(
+            |(_, command, args, wait, new_window)| IdeConfig {
+                command: command.to_string(),
+                args: args.iter().map(|arg| arg.to_string()).collect(),
+                wait: *wait,
+                new_window: new_window.map(str::to_string),
+            },
+        )
     }
 
-    fn is_available(&self) -> bool {
+    pub fn is_available(&self) -> bool {
         Command::new("which")
-            .arg(self.command())
+            .arg(&self.config.command)
             .output()
             .map(|output| output.status.success())
             .unwrap_or(false)
     }
+
+    /// Expand the args template for a worktree. `{file}` falls back to the
+    /// worktree itself when no file is given, except right after `{path}` or
+    /// `--goto`, where it (and the `--goto`) is left out instead.
+    fn args(&self, path: &Path, file: Option<&Path>, new_window: bool) -> Vec<String> {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let path_str = path.to_string_lossy();
+        let file_str = file.map_or_else(|| path_str.clone(), |file| file.to_string_lossy());
+
+        let mut args = Vec::new();
+        if new_window {
+            args.extend(self.config.new_window.clone());
+        }
+        let template = &self.config.args;
+        for (i, arg) in template.iter().enumerate() {
+            let only_for_file = match arg.as_str() {
+                "--goto" => template.get(i + 1).is_some_and(|next| next == "{file}"),
+                "{file}" => i > 0 && (template[i - 1] == "--goto" || template[i - 1] == "{path}"),
+                _ => false,
+            };
+            if file.is_none() && only_for_file {
+                continue;
+            }
+            args.push(
+                arg.replace("{path}", &path_str)
+                    .replace("{file}", &file_str)
+                    .replace("{name}", &name),
+            );
+        }
+        args
+    }
 }
 
+/// (name, command, args, wait, new-window flag)
+type Builtin = (
+    &'static str,
+    &'static str,
+    &'static [&'static str],
+    bool,
+    Option<&'static str>,
+);
+
+const BUILTINS: &[Builtin] = &[
+    (
+        "windsurf",
+        "windsurf",
+        &["{path}", "--goto", "{file}"],
+        false,
+        Some("--new-window"),
+    ),
+    (
+        "code",
+        "code",
+        &["{path}", "--goto", "{file}"],
+        false,
+        Some("--new-window"),
+    ),
+    (
+        "cursor",
+        "cursor",
+        &["{path}", "--goto", "{file}"],
+        false,
+        Some("--new-window"),
+    ),
+    ("zed", "zed", &["{path}", "{file}"], false, Some("--new")),
+    (
+        "sublime",
+        "subl",
+        &["{path}", "{file}"],
+        false,
+        Some("--new-window"),
+    ),
+    ("helix", "hx", &["{file}"], true, None),
+    (
+        "nvim",
+        "tmux",
+        &[
+            "new-window",
+            "-n",
+            "{name}",
+            "-c",
+            "{path}",
+            "nvim",
+            "{file}",
+        ],
+        false,
+        None,
+    ),
+    ("idea", "idea", &["{path}"], false, None),
+    ("pycharm", "pycharm", &["{path}"], false, None),
+    ("webstorm", "webstorm", &["{path}"], false, None),
+    ("goland", "goland", &["{path}"], false, None),
+    ("clion", "clion", &["{path}"], false, None),
+    ("rustrover", "rustrover", &["{path}"], false, None),
+    ("rider", "rider", &["{path}"], false, None),
+    ("phpstorm", "phpstorm", &["{path}"], false, None),
+];
+
 pub struct IDELauncher;
 
 impl IDELauncher {
-    pub fn detect_available() -> Vec<IDE> {
-        vec![IDE::Windsurf, IDE::Idea, IDE::Code]
+    pub fn detect_available(config: &Config) -> Vec<IDE> {
+        IDE::all(config)
             .into_iter()
             .filter(|ide| ide.is_available())
             .collect()
     }
 
-    pub fn launch(ide: IDE, path: &Path) -> Result<()> {
+    /// Open `path` (or `file` inside it) in `ide`. Editors marked `wait` run
+    /// in the foreground; everything else is spawned and left running.
+    pub fn launch(ide: &IDE, path: &Path, file: Option<&Path>, new_window: bool) -> Result<()> {
         if !ide.is_available() {
-            return Err(KayfabeError::IdeNotFound(ide.command().to_string()));
+            return Err(KayfabeError::IdeNotFound(ide.config.command.clone()));
         }
 
-        Command::new(ide.command()).arg(path).spawn()?;
+        let mut command = Command::new(&ide.config.command);
+        command
+            .args(ide.args(path, file, new_window))
+            .current_dir(path);
+
+        if ide.config.wait {
+            command.status()?;
+        } else {
+            command.spawn()?;
+        }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_definition_overrides_builtin() {
+        let mut config = Config::default();
+        config.ide.insert(
+            "code".to_string(),
+            IdeConfig {
+                command: "code-insiders".to_string(),
+                args: vec!["--goto".to_string(), "{file}".to_string()],
+                wait: false,
+                new_window: None,
+            },
+        );
+
+        config.ide.insert(
+            "MyEditor".to_string(),
+            IdeConfig {
+                command: "my-editor".to_string(),
+                args: vec!["{file}".to_string()],
+                wait: true,
+                new_window: None,
+            },
+        );
+        let mine = IDE::resolve("myeditor", &config).unwrap();
+        assert_eq!(mine.config.command, "my-editor");
+        assert!(IDE::all(&config).iter().any(|ide| ide.name == "myeditor"));
+
+        let ide = IDE::resolve("Code", &config).unwrap();
+        assert_eq!(ide.config.command, "code-insiders");
+        assert_eq!(
+            ide.args(Path::new("/repo/wt/a"), Some(Path::new("src/lib.rs")), true),
+            vec!["--goto", "src/lib.rs"]
+        );
+    }
+
+    #[test]
+    fn test_args_template() {
+        let config = Config::default();
+        let nvim = IDE::resolve("nvim", &config).unwrap();
+        assert_eq!(
+            nvim.args(Path::new("/repo/wt/feature"), None, false),
+            vec![
+                "new-window",
+                "-n",
+                "feature",
+                "-c",
+                "/repo/wt/feature",
+                "nvim",
+                "/repo/wt/feature"
+            ]
+        );
+
+        let zed = IDE::resolve("zed", &config).unwrap();
+        assert_eq!(
+            zed.args(Path::new("/repo/wt/feature"), None, true),
+            vec!["--new", "/repo/wt/feature"]
+        );
+        assert_eq!(
+            zed.args(
+                Path::new("/repo/wt/feature"),
+                Some(Path::new("src/lib.rs")),
+                false
+            ),
+            vec!["/repo/wt/feature", "src/lib.rs"]
+        );
+
+        // The file opens in the worktree's window
+        let code = IDE::resolve("code", &config).unwrap();
+        assert_eq!(
+            code.args(
+                Path::new("/repo/wt/feature"),
+                Some(Path::new("src/lib.rs")),
+                false
+            ),
+            vec!["/repo/wt/feature", "--goto", "src/lib.rs"]
+        );
+        assert_eq!(
+            code.args(Path::new("/repo/wt/feature"), None, false),
+            vec!["/repo/wt/feature"]
+        );
+        assert!(IDE::resolve("notepad", &config).is_none());
+    }
+}
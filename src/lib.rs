@@ -3,7 +3,14 @@ pub mod cli;
 pub mod config;
 pub mod error;
 pub mod git;
+pub mod handoff;
 pub mod ide;
+pub mod plan;
+pub mod provision;
+#[cfg(feature = "remote")]
+pub mod remote;
+pub mod session;
+pub mod thoughts;
 pub mod ui;
 
 pub use error::{KayfabeError, Result};
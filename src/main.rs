@@ -1,5 +1,13 @@
-use clap::{Parser, Subcommand};
-use kayfabe::cli::{ConfigCommand, InitCommand, InstallCommand, StatusCommand, WorktreeCommand};
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::{ArgValueCandidates, CompleteEnv, Shell};
+use kayfabe::cli::shell;
+use kayfabe::cli::{
+    AgentCommand, ConfigCommand, ExecCommand, FanoutCommand, HandoffCommand, InitCommand,
+    InstallCommand, PickCommand, PlanCommand, PromptsCommand, RulesCommand, SessionCommand,
+    ShellCommand, StatusCommand, ThoughtsCommand, UninstallCommand, UpgradeAgentsCommand,
+    WorktreeCommand,
+};
+use kayfabe::git::GitRepo;
 use std::path::PathBuf;
 use std::process;
 
@@ -11,7 +19,7 @@ pub use clap::Parser as ClapParser;
 #[command(version)]
 pub struct Cli {
     #[command(subcommand)]
-    command: Commands,
+    command: Option<Commands>,
 
     #[arg(short, long, global = true, help = "Enable verbose output")]
     verbose: bool,
@@ -34,16 +42,64 @@ enum Commands {
         agent: Option<String>,
     },
 
-    #[command(about = "Install kayfabe agents globally")]
+    #[command(about = "Install kayfabe agent rules, commands and subagents")]
     Install {
-        #[arg(help = "Target directory (default: current directory)")]
+        #[arg(help = "Repository for project/worktree scope (default: current directory)")]
         path: Option<PathBuf>,
 
         #[arg(long, help = "Non-interactive mode")]
         non_interactive: bool,
 
-        #[arg(long, help = "Agents to install [windsurf]")]
+        #[arg(long, help = "Agents to install [claude, windsurf]")]
         agents: Option<Vec<String>>,
+
+        #[arg(
+            long,
+            default_value = "user",
+            value_parser = ["user", "project", "worktree"],
+            help = "Install into the home directory, the main checkout, or this checkout"
+        )]
+        scope: String,
+
+        #[arg(long, help = "Show what would change without writing anything")]
+        dry_run: bool,
+    },
+
+    #[command(about = "Remove files written by install, init and prompts install")]
+    Uninstall {
+        #[arg(help = "Repository (default: current directory)")]
+        path: Option<PathBuf>,
+
+        #[arg(long, help = "Only remove files for these agents")]
+        agents: Option<Vec<String>>,
+
+        #[arg(
+            long,
+            value_parser = ["user", "project", "worktree"],
+            help = "Only remove files installed into this scope"
+        )]
+        scope: Option<String>,
+
+        #[arg(
+            long,
+            help = "Also remove the project config and move main/ back to the repository root"
+        )]
+        layout: bool,
+
+        #[arg(
+            long,
+            help = "Remove files changed since they were installed without asking"
+        )]
+        force: bool,
+    },
+
+    #[command(about = "Update installed agent files to this version of kayfabe")]
+    UpgradeAgents {
+        #[arg(long, help = "Show the changes without applying them")]
+        dry_run: bool,
+
+        #[arg(short, long, help = "Apply without asking")]
+        yes: bool,
     },
 
     #[command(about = "Manage worktrees")]
@@ -58,7 +114,308 @@ enum Commands {
         command: ConfigCommands,
     },
     #[command(about = "Show current repo/worktree status")]
-    Status,
+    Status {
+        #[arg(long, help = "Show total disk usage of all worktrees")]
+        size: bool,
+    },
+
+    #[command(about = "Run and supervise coding agents in worktrees")]
+    Agent {
+        #[command(subcommand)]
+        command: AgentCommands,
+    },
+
+    #[command(about = "Write and resume handoff documents between agent sessions")]
+    Handoff {
+        #[command(subcommand)]
+        command: HandoffCommands,
+    },
+
+    #[command(about = "Manage the thoughts/ directory shared by all worktrees")]
+    Thoughts {
+        #[command(subcommand)]
+        command: ThoughtsCommands,
+    },
+
+    #[command(about = "Track implementation plans and the worktrees implementing them")]
+    Plan {
+        #[command(subcommand)]
+        command: PlanCommands,
+    },
+
+    #[command(about = "List, render and install the bundled agent prompts")]
+    Prompts {
+        #[command(subcommand)]
+        command: PromptsCommands,
+    },
+
+    #[command(about = "Generate every agent's rule file from .kayfabe/rules/")]
+    Rules {
+        #[command(subcommand)]
+        command: RulesCommands,
+    },
+
+    #[command(about = "Manage tmux/zellij sessions per worktree")]
+    Session {
+        #[command(subcommand)]
+        command: SessionCommands,
+    },
+
+    #[command(about = "Run a command in one or more worktrees")]
+    #[command(alias = "run")]
+    Exec {
+        #[arg(
+            help = "Worktree name",
+            required_unless_present = "all",
+            add = ArgValueCandidates::new(ShellCommand::worktree_candidates)
+        )]
+        name: Option<String>,
+
+        #[arg(long, conflicts_with = "name", help = "Run in every worktree")]
+        all: bool,
+
+        #[arg(
+            long,
+            requires = "all",
+            help = "Only worktrees whose name matches this glob"
+        )]
+        filter: Option<String>,
+
+        #[arg(short, long, requires = "all", help = "Run in all worktrees at once")]
+        parallel: bool,
+
+        #[arg(last = true, required = true, help = "Command to run")]
+        command: Vec<String>,
+    },
+
+    #[command(about = "Print shell completion setup")]
+    Completions {
+        #[arg(value_enum, help = "Shell")]
+        shell: Shell,
+    },
+
+    #[command(about = "Print shell integration defining `kf` (with `kf cd <name>`)")]
+    ShellInit {
+        #[arg(value_parser = ["bash", "zsh", "fish"], help = "Shell")]
+        shell: String,
+    },
+
+    #[command(about = "Change to a worktree (requires shell-init)")]
+    Cd {
+        #[arg(help = "Worktree name", add = ArgValueCandidates::new(ShellCommand::worktree_candidates))]
+        name: Option<String>,
+    },
+
+    #[command(about = "Print worktree, branch and dirty state for a shell prompt")]
+    Prompt {
+        #[arg(
+            long,
+            default_value = "{worktree}:{branch}{dirty}",
+            help = "Output format using {worktree}, {branch} and {dirty}"
+        )]
+        format: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum AgentCommands {
+    #[command(about = "Start an agent in the background in a worktree")]
+    Run {
+        #[arg(help = "Worktree name", add = ArgValueCandidates::new(ShellCommand::worktree_candidates))]
+        worktree: String,
+
+        #[arg(
+            long,
+            help = "Agent CLI [claude|aider|codex] (default: agents.preferred)"
+        )]
+        agent: Option<String>,
+
+        #[arg(long, help = "Prompt file (default: .kayfabe/task.md in the worktree)")]
+        prompt: Option<PathBuf>,
+    },
+
+    #[command(about = "List agents and whether they are still running")]
+    Ps,
+
+    #[command(about = "Show an agent's output")]
+    Logs {
+        #[arg(help = "Worktree name", add = ArgValueCandidates::new(ShellCommand::worktree_candidates))]
+        worktree: String,
+
+        #[arg(short, long, help = "Keep printing output until the agent exits")]
+        follow: bool,
+    },
+
+    #[command(about = "Stop the agent running in a worktree")]
+    Stop {
+        #[arg(help = "Worktree name", add = ArgValueCandidates::new(ShellCommand::worktree_candidates))]
+        worktree: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum HandoffCommands {
+    #[command(about = "Create a handoff with the worktree's branch, commit and git status")]
+    Create {
+        #[arg(help = "Short description of the work")]
+        description: String,
+
+        #[arg(short, long, help = "Worktree (default: current)", add = ArgValueCandidates::new(ShellCommand::worktree_candidates))]
+        worktree: Option<String>,
+
+        #[arg(long, help = "Read the handoff body from a file (- for stdin)")]
+        from: Option<PathBuf>,
+    },
+
+    #[command(about = "List handoffs, newest first")]
+    List {
+        #[arg(help = "Only this worktree's handoffs", add = ArgValueCandidates::new(ShellCommand::worktree_candidates))]
+        worktree: Option<String>,
+    },
+
+    #[command(about = "Print a handoff (default: the current worktree's latest)")]
+    Show {
+        #[arg(help = "Handoff file name or path")]
+        handoff: Option<String>,
+    },
+
+    #[command(about = "Prime a new agent session with the latest handoff")]
+    Resume {
+        #[arg(help = "Worktree (default: current)", add = ArgValueCandidates::new(ShellCommand::worktree_candidates))]
+        worktree: Option<String>,
+
+        #[arg(long, help = "Start this agent on it instead of printing the prompt")]
+        agent: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ThoughtsCommands {
+    #[command(about = "Create thoughts/ at the layout root and link it into every worktree")]
+    Init,
+
+    #[command(about = "Find documents containing all of the given terms")]
+    Search {
+        #[arg(required = true, help = "Search terms (case-insensitive)")]
+        terms: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum PromptsCommands {
+    #[command(about = "List built-in prompts and user overrides")]
+    List,
+
+    #[command(about = "Print a prompt rendered with this project's variables")]
+    Show {
+        #[arg(help = "Prompt name")]
+        name: String,
+
+        #[arg(long, help = "Print the template without rendering it")]
+        raw: bool,
+    },
+
+    #[command(about = "Show the variables prompts are rendered with")]
+    Vars,
+
+    #[command(about = "Render prompts into .claude/commands and .claude/agents")]
+    Install {
+        #[arg(help = "Prompts to install (default: all)")]
+        names: Vec<String>,
+
+        #[arg(
+            long,
+            help = "Directory to install into (default: .claude in this checkout)"
+        )]
+        dir: Option<PathBuf>,
+
+        #[arg(long, help = "Overwrite files that already exist")]
+        force: bool,
+    },
+
+    #[command(about = "Edit your copy of a prompt, starting from the built-in")]
+    Edit {
+        #[arg(help = "Prompt name")]
+        name: String,
+    },
+
+    #[command(about = "Compare your overrides with the built-in prompts")]
+    Diff {
+        #[arg(help = "Prompt name (default: every override)")]
+        name: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum RulesCommands {
+    #[command(about = "Create .kayfabe/rules/, importing existing rule files")]
+    Init,
+
+    #[command(about = "Regenerate rule files for each agent")]
+    Sync {
+        #[arg(long, help = "Replace rule files that were not generated by kayfabe")]
+        force: bool,
+    },
+
+    #[command(about = "Fail if generated rule files are out of date")]
+    Check,
+}
+
+#[derive(Subcommand)]
+enum PlanCommands {
+    #[command(about = "Create a plan from the standard template")]
+    New {
+        #[arg(help = "Plan title")]
+        title: String,
+
+        #[arg(long, help = "Ticket number to put in the file name (e.g. ENG-1234)")]
+        ticket: Option<String>,
+
+        #[arg(long, default_value_t = 2, help = "Number of phases")]
+        phases: usize,
+
+        #[arg(short, long, help = "Link the plan to this worktree", add = ArgValueCandidates::new(ShellCommand::worktree_candidates))]
+        worktree: Option<String>,
+    },
+
+    #[command(about = "List plans with their phase progress")]
+    List,
+
+    #[command(about = "Show per-phase completion (default: the current worktree's plan)")]
+    Status {
+        #[arg(help = "Plan name, or a unique part of it")]
+        plan: Option<String>,
+    },
+
+    #[command(about = "Link a plan to the worktree implementing it")]
+    Link {
+        #[arg(help = "Plan name, or a unique part of it")]
+        plan: String,
+
+        #[arg(short, long, help = "Worktree (default: current)", add = ArgValueCandidates::new(ShellCommand::worktree_candidates))]
+        worktree: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum SessionCommands {
+    #[command(about = "Attach to a worktree's session, creating it if needed")]
+    Attach {
+        #[arg(help = "Worktree name (default: current worktree)", add = ArgValueCandidates::new(ShellCommand::worktree_candidates))]
+        name: Option<String>,
+
+        #[arg(long, help = "Start the session without attaching")]
+        detach: bool,
+    },
+
+    #[command(about = "List running worktree sessions")]
+    List,
+
+    #[command(about = "Kill a worktree's session")]
+    Kill {
+        #[arg(help = "Worktree name", add = ArgValueCandidates::new(ShellCommand::worktree_candidates))]
+        name: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -71,28 +428,115 @@ enum WorktreeCommands {
         #[arg(long, help = "Base branch (default: main)")]
         base: Option<String>,
 
-        #[arg(long, help = "Launch IDE [windsurf|idea|code]")]
+        #[arg(
+            long,
+            help = "Launch IDE (default: defaults.ide) [code|cursor|zed|nvim|idea|...]"
+        )]
         open: Option<String>,
 
         #[arg(long, help = "Don't launch any IDE")]
         no_open: bool,
     },
 
+    #[command(about = "Create N worktrees for the same task from the same base commit")]
+    Fanout {
+        #[arg(help = "Task name; worktrees are named <task>-1..<task>-N")]
+        task: String,
+
+        #[arg(
+            short = 'n',
+            long = "count",
+            default_value_t = 2,
+            help = "Number of worktrees"
+        )]
+        count: usize,
+
+        #[arg(long, help = "Base branch or commit (default: main)")]
+        base: Option<String>,
+
+        #[arg(
+            long,
+            help = "Task prompt file, copied to .kayfabe/task.md in each worktree"
+        )]
+        prompt: Option<PathBuf>,
+
+        #[arg(short, long, conflicts_with = "prompt", help = "Task prompt text")]
+        message: Option<String>,
+
+        #[arg(
+            long,
+            value_delimiter = ',',
+            help = "Agents to start, assigned round-robin (e.g. claude,codex)"
+        )]
+        agents: Vec<String>,
+    },
+
+    #[command(about = "Compare the worktrees of a fanned-out task")]
+    Compare {
+        #[arg(help = "Task name given to fanout")]
+        task: String,
+
+        #[arg(long, help = "Command to run in each worktree (e.g. \"cargo test\")")]
+        test: Option<String>,
+    },
+
     #[command(about = "List worktrees")]
     List {
         #[arg(long, help = "Show only stale worktrees (days)")]
         stale: Option<u64>,
+
+        #[arg(long, help = "Show disk usage per worktree")]
+        size: bool,
     },
 
+    #[command(about = "Show disk usage per worktree, with build artifacts split out")]
+    Du,
+
     #[command(about = "Remove a worktree")]
     Remove {
-        #[arg(help = "Name of the worktree to remove")]
+        #[arg(help = "Name of the worktree to remove", add = ArgValueCandidates::new(ShellCommand::worktree_candidates))]
         name: String,
 
         #[arg(long, help = "Force removal even if unmerged")]
         force: bool,
     },
 
+    #[command(about = "Open existing worktrees in an IDE")]
+    Open {
+        #[arg(required = true, help = "Worktrees to open", add = ArgValueCandidates::new(ShellCommand::worktree_candidates))]
+        names: Vec<String>,
+
+        #[arg(long, help = "IDE to use (default: defaults.ide)")]
+        ide: Option<String>,
+
+        #[arg(long, help = "File to open, relative to the worktree")]
+        file: Option<PathBuf>,
+
+        #[arg(long, help = "Always open a new window")]
+        new_window: bool,
+    },
+
+    #[command(about = "Pick a worktree from a searchable list and act on it")]
+    Pick,
+
+    #[command(about = "Print the path of a worktree")]
+    Path {
+        #[arg(help = "Name of the worktree (default: main)", add = ArgValueCandidates::new(ShellCommand::worktree_candidates))]
+        name: Option<String>,
+    },
+
+    #[command(about = "Re-sync [worktree.copy]/[worktree.link] files from the anchor")]
+    RefreshFiles {
+        #[arg(help = "Name of the worktree (default: current worktree)", add = ArgValueCandidates::new(ShellCommand::worktree_candidates))]
+        name: Option<String>,
+
+        #[arg(long, conflicts_with = "name", help = "Refresh every worktree")]
+        all: bool,
+
+        #[arg(long, help = "Replace files that differ from the anchor")]
+        overwrite: bool,
+    },
+
     #[command(about = "Cleanup stale worktrees")]
     Cleanup {
         #[arg(long, default_value = "14", help = "Staleness threshold in days")]
@@ -106,6 +550,30 @@ enum WorktreeCommands {
 
         #[arg(long, help = "Also remove worktrees with unmerged commits")]
         include_unmerged: bool,
+
+        #[arg(
+            long,
+            conflicts_with_all = ["include_unmerged", "older_than"],
+            help = "Non-interactive safe cleanup using configured stale_days/protect_unmerged"
+        )]
+        auto: bool,
+
+        #[arg(long, requires = "auto", help = "Log file for --auto runs")]
+        log_file: Option<PathBuf>,
+
+        #[arg(
+            long,
+            conflicts_with = "auto",
+            help = "Show which cleanup rule matched each worktree"
+        )]
+        explain: bool,
+
+        #[arg(
+            long,
+            conflicts_with_all = ["auto", "explain"],
+            help = "Clear build artifacts from stale worktrees instead of removing them"
+        )]
+        artifacts_only: bool,
     },
 }
 
@@ -123,8 +591,11 @@ enum ConfigCommands {
         agent: Option<String>,
     },
 
-    #[command(about = "Validate agent configurations")]
-    Validate,
+    #[command(about = "Check agent files for broken links, stale commands and size limits")]
+    Validate {
+        #[arg(long, help = "Fail on warnings as well as errors")]
+        strict: bool,
+    },
 
     #[command(about = "Initialize global configuration")]
     Init,
@@ -158,9 +629,28 @@ enum TemplateCommands {
 }
 
 fn main() {
+    CompleteEnv::with_factory(Cli::command)
+        .var(shell::COMPLETE_VAR)
+        .complete();
+
     let cli = Cli::parse();
 
-    let result = match cli.command {
+    // Bare `kayfabe` opens the picker inside a worktree layout
+    let Some(command) = cli.command else {
+        let in_layout = std::env::current_dir()
+            .ok()
+            .and_then(|dir| GitRepo::discover(&dir).ok())
+            .is_some_and(|repo| repo.is_worktree_layout());
+        let result = if in_layout {
+            PickCommand::execute()
+        } else {
+            Cli::command().print_help().map_err(Into::into)
+        };
+        exit_on_error(result);
+        return;
+    };
+
+    let result = match command {
         Commands::Init {
             path,
             no_convert: _,
@@ -171,7 +661,19 @@ fn main() {
             path,
             non_interactive,
             agents,
-        } => InstallCommand::execute(path, non_interactive, agents),
+            scope,
+            dry_run,
+        } => InstallCommand::execute(path, non_interactive, agents, scope, dry_run),
+
+        Commands::Uninstall {
+            path,
+            agents,
+            scope,
+            layout,
+            force,
+        } => UninstallCommand::execute(path, agents, scope, layout, force),
+
+        Commands::UpgradeAgents { dry_run, yes } => UpgradeAgentsCommand::execute(dry_run, yes),
 
         Commands::Worktree { command } => match command {
             WorktreeCommands::Create {
@@ -180,28 +682,223 @@ fn main() {
                 open,
                 no_open,
             } => WorktreeCommand::create(name, base, open, no_open),
-            WorktreeCommands::List { stale } => WorktreeCommand::list(stale),
+            WorktreeCommands::Fanout {
+                task,
+                count,
+                base,
+                prompt,
+                message,
+                agents,
+            } => FanoutCommand::fanout(task, count, base, prompt, message, agents),
+            WorktreeCommands::Compare { task, test } => FanoutCommand::compare(task, test),
+            WorktreeCommands::List { stale, size } => WorktreeCommand::list(stale, size),
+            WorktreeCommands::Du => WorktreeCommand::du(),
+            WorktreeCommands::Open {
+                names,
+                ide,
+                file,
+                new_window,
+            } => WorktreeCommand::open(names, ide, file, new_window),
+            WorktreeCommands::Pick => PickCommand::execute(),
+            WorktreeCommands::Path { name } => WorktreeCommand::path(name),
+            WorktreeCommands::RefreshFiles {
+                name,
+                all,
+                overwrite,
+            } => WorktreeCommand::refresh_files(name, all, overwrite),
             WorktreeCommands::Remove { name, force } => WorktreeCommand::remove(name, force),
+            WorktreeCommands::Cleanup {
+                auto: true,
+                dry_run,
+                log_file,
+                ..
+            } => WorktreeCommand::auto_cleanup(dry_run, log_file),
             WorktreeCommands::Cleanup {
                 older_than,
                 dry_run,
                 force,
                 include_unmerged,
-            } => WorktreeCommand::cleanup(older_than, dry_run, force, include_unmerged),
+                explain,
+                artifacts_only,
+                ..
+            } => WorktreeCommand::cleanup(
+                older_than,
+                dry_run,
+                force,
+                include_unmerged,
+                explain,
+                artifacts_only,
+            ),
         },
 
         Commands::Config { command } => match command {
             ConfigCommands::Show { agent } => ConfigCommand::show(agent),
             ConfigCommands::Edit { agent } => ConfigCommand::edit(agent),
-            ConfigCommands::Validate => ConfigCommand::validate(),
+            ConfigCommands::Validate { strict } => {
+                ConfigCommand::validate(strict, kayfabe_commands())
+            }
             ConfigCommands::Init => ConfigCommand::init(),
         },
 
-        Commands::Status => StatusCommand::execute(),
+        Commands::Status { size } => StatusCommand::execute(size),
+
+        Commands::Agent { command } => match command {
+            AgentCommands::Run {
+                worktree,
+                agent,
+                prompt,
+            } => AgentCommand::run(worktree, agent, prompt),
+            AgentCommands::Ps => AgentCommand::ps(),
+            AgentCommands::Logs { worktree, follow } => AgentCommand::logs(worktree, follow),
+            AgentCommands::Stop { worktree } => AgentCommand::stop(worktree),
+        },
+
+        Commands::Handoff { command } => match command {
+            HandoffCommands::Create {
+                description,
+                worktree,
+                from,
+            } => HandoffCommand::create(description, worktree, from),
+            HandoffCommands::List { worktree } => HandoffCommand::list(worktree),
+            HandoffCommands::Show { handoff } => HandoffCommand::show(handoff),
+            HandoffCommands::Resume { worktree, agent } => HandoffCommand::resume(worktree, agent),
+        },
+
+        Commands::Thoughts { command } => match command {
+            ThoughtsCommands::Init => ThoughtsCommand::init(),
+            ThoughtsCommands::Search { terms } => ThoughtsCommand::search(terms),
+        },
+
+        Commands::Prompts { command } => match command {
+            PromptsCommands::List => PromptsCommand::list(),
+            PromptsCommands::Show { name, raw } => PromptsCommand::show(name, raw),
+            PromptsCommands::Vars => PromptsCommand::vars(),
+            PromptsCommands::Install { names, dir, force } => {
+                PromptsCommand::install(names, dir, force)
+            }
+            PromptsCommands::Edit { name } => PromptsCommand::edit(name),
+            PromptsCommands::Diff { name } => PromptsCommand::diff(name),
+        },
+
+        Commands::Rules { command } => match command {
+            RulesCommands::Init => RulesCommand::init(),
+            RulesCommands::Sync { force } => RulesCommand::sync(force),
+            RulesCommands::Check => RulesCommand::check(),
+        },
+
+        Commands::Plan { command } => match command {
+            PlanCommands::New {
+                title,
+                ticket,
+                phases,
+                worktree,
+            } => PlanCommand::create(title, ticket, phases, worktree),
+            PlanCommands::List => PlanCommand::list(),
+            PlanCommands::Status { plan } => PlanCommand::status(plan),
+            PlanCommands::Link { plan, worktree } => PlanCommand::link(plan, worktree),
+        },
+
+        Commands::Session { command } => match command {
+            SessionCommands::Attach { name, detach } => SessionCommand::attach(name, detach),
+            SessionCommands::List => SessionCommand::list(),
+            SessionCommands::Kill { name } => SessionCommand::kill(name),
+        },
+
+        Commands::Exec {
+            name,
+            all,
+            filter,
+            parallel,
+            command,
+        } => ExecCommand::execute(name, all, filter, parallel, command),
+
+        Commands::Completions { shell } => ShellCommand::completions(shell),
+        Commands::ShellInit { shell } => ShellCommand::init(&shell),
+        Commands::Cd { .. } => ShellCommand::cd(),
+        Commands::Prompt { format } => ShellCommand::prompt(&format),
+    };
+
+    exit_on_error(result);
+}
+
+/// Subcommand names (and aliases) two levels deep, for `config validate`
+fn kayfabe_commands() -> Vec<(String, Vec<String>)> {
+    let names = |command: &clap::Command| {
+        std::iter::once(command.get_name().to_string())
+            .chain(command.get_all_aliases().map(str::to_string))
+            .collect::<Vec<_>>()
     };
 
+    Cli::command()
+        .get_subcommands()
+        .flat_map(|command| {
+            let subcommands: Vec<String> = command.get_subcommands().flat_map(names).collect();
+            names(command)
+                .into_iter()
+                .map(move |name| (name, subcommands.clone()))
+        })
+        .collect()
+}
+
+fn exit_on_error(result: kayfabe::error::Result<()>) {
     if let Err(e) = result {
         eprintln!("Error: {}", e);
         process::exit(1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kayfabe::agents::managed::ManagedFile;
+    use kayfabe::agents::validate::{Severity, Validator};
+    use kayfabe::agents::{AgentInstaller, Manifest, Scope};
+
+    #[test]
+    fn test_installed_files_pass_validation() {
+        // No build files, and projects missing the usual test/lint entries
+        let projects = [
+            None,
+            Some(("package.json", r#"{"scripts": {"build": "tsc"}}"#)),
+            Some(("Makefile", "build:\n\tcc main.c\n")),
+        ];
+        for project in projects {
+            let temp_dir = tempfile::TempDir::new().unwrap();
+            let root = temp_dir.path().canonicalize().unwrap();
+            if let Some((file, content)) = project {
+                std::fs::write(root.join(file), content).unwrap();
+            }
+
+            let vars = PromptsCommand::variables_at(&root).unwrap();
+            let mut manifest = Manifest::load(Manifest::project_path(&root)).unwrap();
+            let installer = AgentInstaller::new(Scope::Project, root.clone(), &vars, false);
+            for agent in ["claude", "windsurf"] {
+                installer.install(agent, &mut manifest).unwrap();
+            }
+
+            let validator = Validator::new(&root, kayfabe_commands());
+            let files = validator.files();
+            assert!(files.len() > 2);
+            for file in files {
+                let content = std::fs::read_to_string(root.join(&file.path)).unwrap();
+                let errors: Vec<String> = validator
+                    .validate(&file, &content)
+                    .into_iter()
+                    .filter(|finding| finding.severity == Severity::Error)
+                    .map(|finding| finding.message)
+                    .collect();
+                assert!(
+                    errors.is_empty(),
+                    "{} with {:?}: {:?}",
+                    file.path.display(),
+                    project,
+                    errors
+                );
+            }
+
+            for entry in &manifest.files {
+                ManagedFile::new(entry.path.clone()).forget().unwrap();
+            }
+        }
+    }
+}
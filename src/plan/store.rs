@@ -0,0 +1,336 @@
+use crate::error::{KayfabeError, Result};
+use crate::thoughts::{slugify, Thoughts};
+use std::path::{Path, PathBuf};
+
+/// One `## Phase N: ...` section and its checklist
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Phase {
+    pub name: String,
+    pub done: usize,
+    pub total: usize,
+}
+
+impl Phase {
+    pub fn is_complete(&self) -> bool {
+        self.total > 0 && self.done == self.total
+    }
+}
+
+/// An implementation plan in the shape `agents/create_plan.md` writes
+#[derive(Debug, Clone)]
+pub struct Plan {
+    pub path: PathBuf,
+    pub title: String,
+    pub phases: Vec<Phase>,
+}
+
+impl Plan {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            KayfabeError::Other(format!("Failed to read plan {}: {}", path.display(), e))
+        })?;
+        let (title, phases) = parse(&content);
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            title: title.unwrap_or_else(|| Self::id_of(path)),
+            phases,
+        })
+    }
+
+    /// File stem, used to refer to the plan on the command line
+    pub fn id(&self) -> String {
+        Self::id_of(&self.path)
+    }
+
+    pub fn completed_phases(&self) -> usize {
+        self.phases
+            .iter()
+            .filter(|phase| phase.is_complete())
+            .count()
+    }
+
+    fn id_of(path: &Path) -> String {
+        path.file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Plans live in `thoughts/shared/plans`, named
+/// `YYYY-MM-DD[-TICKET]-<description>.md`
+pub struct PlanStore {
+    dir: PathBuf,
+}
+
+impl PlanStore {
+    pub fn new(layout_root: &Path) -> Self {
+        Self {
+            dir: Thoughts::new(layout_root).shared("plans"),
+        }
+    }
+
+    /// Write a plan skeleton with `phases` empty phases
+    pub fn create(&self, title: &str, ticket: Option<&str>, phases: usize) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)?;
+
+        let mut name = chrono::Local::now().format("%Y-%m-%d").to_string();
+        if let Some(ticket) = ticket {
+            name.push_str(&format!("-{}", ticket));
+        }
+        name.push_str(&format!("-{}.md", slugify(title)));
+        let path = self.dir.join(name);
+        if path.exists() {
+            return Err(KayfabeError::Other(format!(
+                "Plan already exists: {}",
+                path.display()
+            )));
+        }
+
+        std::fs::write(&path, template(title, phases))?;
+        Ok(path)
+    }
+
+    /// All plans, newest first
+    pub fn list(&self) -> Result<Vec<Plan>> {
+        if !self.dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "md") {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        paths.reverse();
+
+        paths.iter().map(|path| Plan::load(path)).collect()
+    }
+
+    /// Find a plan by path, file name, file stem, or a part of the stem that
+    /// only one plan has
+    pub fn find(&self, id: &str) -> Result<Plan> {
+        let path = Path::new(id);
+        if path.is_file() {
+            return Plan::load(path);
+        }
+
+        let id = id.trim_end_matches(".md");
+        let plans = self.list()?;
+        if let Some(plan) = plans.iter().find(|plan| plan.id() == id) {
+            return Ok(plan.clone());
+        }
+
+        let mut matches = plans.into_iter().filter(|plan| plan.id().contains(id));
+        match (matches.next(), matches.next()) {
+            (Some(plan), None) => Ok(plan),
+            (Some(_), Some(_)) => Err(KayfabeError::Other(format!(
+                "{} matches several plans; use the full name",
+                id
+            ))),
+            _ => Err(KayfabeError::Other(format!("Plan not found: {}", id))),
+        }
+    }
+
+    /// Path of a plan recorded in worktree metadata by file name
+    pub fn path(&self, file_name: &str) -> PathBuf {
+        self.dir.join(file_name)
+    }
+
+    /// The name a plan is recorded under in worktree metadata. Plans
+    /// elsewhere can't be found again by name, so they are rejected.
+    pub fn file_name(&self, path: &Path) -> Result<String> {
+        let in_store = match (
+            path.canonicalize().ok().as_deref().and_then(Path::parent),
+            self.dir.canonicalize(),
+        ) {
+            (Some(parent), Ok(dir)) => parent == dir,
+            _ => false,
+        };
+        if !in_store {
+            return Err(KayfabeError::Other(format!(
+                "{} is not in {}; move it there to link it",
+                path.display(),
+                self.dir.display()
+            )));
+        }
+        Ok(path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default())
+    }
+}
+
+/// Title from the first `# ` heading, and each phase's checkbox counts.
+/// A phase runs until the next `## ` heading.
+fn parse(content: &str) -> (Option<String>, Vec<Phase>) {
+    let mut title = None;
+    let mut phases: Vec<Phase> = Vec::new();
+    let mut in_phase = false;
+    let mut in_code = false;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+
+        if let Some(heading) = line.strip_prefix("# ") {
+            if title.is_none() {
+                let heading = heading.trim();
+                let heading = heading
+                    .strip_suffix("Implementation Plan")
+                    .unwrap_or(heading);
+                title = Some(heading.trim().to_string());
+            }
+        } else if let Some(heading) = line.strip_prefix("## ") {
+            in_phase = heading.starts_with("Phase ");
+            if in_phase {
+                let name = heading
+                    .split_once(':')
+                    .map(|(_, name)| name)
+                    .unwrap_or(heading);
+                phases.push(Phase {
+                    name: name.trim().to_string(),
+                    done: 0,
+                    total: 0,
+                });
+            }
+        } else if in_phase {
+            let checked = if line.starts_with("- [ ]") {
+                Some(false)
+            } else if line.starts_with("- [x]") || line.starts_with("- [X]") {
+                Some(true)
+            } else {
+                None
+            };
+            if let (Some(checked), Some(phase)) = (checked, phases.last_mut()) {
+                phase.total += 1;
+                if checked {
+                    phase.done += 1;
+                }
+            }
+        }
+    }
+
+    (title, phases)
+}
+
+fn template(title: &str, phases: usize) -> String {
+    let mut plan = format!(
+        "# {} Implementation Plan\n\n\
+         ## Overview\n\n\
+         ## Current State Analysis\n\n\
+         ## Desired End State\n\n\
+         ## What We're NOT Doing\n\n\
+         ## Implementation Approach\n\n",
+        title
+    );
+
+    for i in 1..=phases {
+        plan.push_str(&format!(
+            "## Phase {}: [Descriptive Name]\n\n\
+             ### Overview\n\n\
+             ### Changes Required:\n\n\
+             ### Success Criteria:\n\n\
+             #### Automated Verification:\n\
+             - [ ] Tests pass\n\n\
+             #### Manual Verification:\n\
+             - [ ] Works as expected\n\n\
+             ---\n\n",
+            i
+        ));
+    }
+
+    plan.push_str("## Testing Strategy\n\n## References\n");
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_phases() {
+        let content = "\
+# Rate Limiting Implementation Plan
+
+## Overview
+- [ ] not a phase item
+
+## Phase 1: Token bucket
+
+### Success Criteria:
+- [x] Unit tests pass: `make test`
+  - [X] Lint passes
+
+```markdown
+- [ ] inside a code block
+```
+
+## Phase 2: Wire up middleware
+- [x] Middleware registered
+- [ ] Load test
+
+## Phase 3: Docs
+
+## Testing Strategy
+- [ ] not a phase item either
+";
+        let (title, phases) = parse(content);
+        assert_eq!(title.as_deref(), Some("Rate Limiting"));
+        assert_eq!(
+            phases,
+            vec![
+                Phase {
+                    name: "Token bucket".to_string(),
+                    done: 2,
+                    total: 2
+                },
+                Phase {
+                    name: "Wire up middleware".to_string(),
+                    done: 1,
+                    total: 2
+                },
+                Phase {
+                    name: "Docs".to_string(),
+                    done: 0,
+                    total: 0
+                },
+            ]
+        );
+        assert!(phases[0].is_complete());
+        assert!(!phases[2].is_complete());
+    }
+
+    #[test]
+    fn test_template_parses_back() {
+        let (title, phases) = parse(&template("Auth flow", 3));
+        assert_eq!(title.as_deref(), Some("Auth flow"));
+        assert_eq!(phases.len(), 3);
+        assert!(phases
+            .iter()
+            .all(|phase| phase.total == 2 && phase.done == 0));
+    }
+
+    #[test]
+    fn test_only_plans_in_the_store_can_be_linked() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let store = PlanStore::new(temp_dir.path());
+        let plan = store.create("Auth flow", Some("ENG-1"), 1).unwrap();
+        let name = plan.file_name().unwrap().to_string_lossy().to_string();
+        assert_eq!(store.file_name(&plan).unwrap(), name);
+
+        let elsewhere = temp_dir.path().join("notes").join(&name);
+        std::fs::create_dir_all(elsewhere.parent().unwrap()).unwrap();
+        std::fs::copy(&plan, &elsewhere).unwrap();
+        assert!(store.file_name(&elsewhere).is_err());
+    }
+}
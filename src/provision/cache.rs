@@ -0,0 +1,203 @@
+use super::{copy_tree, symlink};
+use crate::config::{BuildCacheConfig, CargoTargetStrategy};
+use crate::error::Result;
+use crate::git::GitRepo;
+use std::path::{Path, PathBuf};
+
+const CARGO_CONFIG: &str = ".cargo/config.toml";
+
+pub struct BuildCache;
+
+impl BuildCache {
+    /// Resolve the `CARGO_TARGET_DIR` a worktree should build into, if any
+    pub fn cargo_target_dir(
+        layout_root: &Path,
+        name: &str,
+        config: &BuildCacheConfig,
+    ) -> Option<PathBuf> {
+        let pool = match &config.target_pool {
+            Some(pool) => layout_root.join(pool),
+            None => layout_root.join(".kayfabe").join("cache").join("target"),
+        };
+
+        match config.cargo_target_dir? {
+            CargoTargetStrategy::PerWorktree => Some(pool.join(name)),
+            CargoTargetStrategy::Shared => Some(pool.join("shared")),
+        }
+    }
+
+    /// Delete a removed worktree's own target dir from the pool. A shared
+    /// target dir is left for the other worktrees.
+    pub fn remove(layout_root: &Path, name: &str, config: &BuildCacheConfig) -> Result<()> {
+        if config.cargo_target_dir != Some(CargoTargetStrategy::PerWorktree) {
+            return Ok(());
+        }
+        if let Some(target_dir) = Self::cargo_target_dir(layout_root, name, config) {
+            if target_dir.is_dir() {
+                std::fs::remove_dir_all(target_dir)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Environment variables hooks and commands should see in the worktree
+    pub fn env(layout_root: &Path, name: &str, config: &BuildCacheConfig) -> Vec<(String, String)> {
+        Self::cargo_target_dir(layout_root, name, config)
+            .map(|dir| {
+                vec![(
+                    "CARGO_TARGET_DIR".to_string(),
+                    dir.to_string_lossy().to_string(),
+                )]
+            })
+            .unwrap_or_default()
+    }
+
+    /// Apply every configured strategy to a freshly created worktree and
+    /// return a human-readable line per action taken.
+    pub fn apply(
+        repo: &GitRepo,
+        wt_path: &Path,
+        name: &str,
+        config: &BuildCacheConfig,
+    ) -> Result<Vec<String>> {
+        let mut report = Vec::new();
+        let anchor = repo.anchor_path()?;
+
+        if let Some(target_dir) = Self::cargo_target_dir(repo.layout_root(), name, config) {
+            std::fs::create_dir_all(&target_dir)?;
+            report.push(Self::write_cargo_config(repo, wt_path, &target_dir)?);
+        }
+
+        for dir in &config.seed {
+            let source = anchor.join(dir);
+            let dest = wt_path.join(dir);
+            if !source.is_dir() {
+                report.push(format!("seed {}: not present in anchor, skipped", dir));
+            } else if dest.exists() {
+                report.push(format!("seed {}: already exists, skipped", dir));
+            } else {
+                copy_tree(&source, &dest, config.seed_mode)?;
+                report.push(format!("seeded {} from anchor", dir));
+            }
+        }
+
+        for dir in &config.symlink {
+            let source = anchor.join(dir);
+            let dest = wt_path.join(dir);
+            if dest.symlink_metadata().is_ok() {
+                report.push(format!("symlink {}: already exists, skipped", dir));
+                continue;
+            }
+
+            std::fs::create_dir_all(&source)?;
+            if let Some(parent) = dest.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            symlink(&source, &dest)?;
+            report.push(format!("linked {} to anchor", dir));
+        }
+
+        Ok(report)
+    }
+
+    /// Cargo has no per-checkout env file, so the target dir is set through an
+    /// untracked `.cargo/config.toml` hidden via `info/exclude`. A config the
+    /// project already tracks is left alone.
+    fn write_cargo_config(repo: &GitRepo, wt_path: &Path, target_dir: &Path) -> Result<String> {
+        let config_path = wt_path.join(CARGO_CONFIG);
+        if config_path.exists() {
+            return Ok(format!(
+                "{} exists, set CARGO_TARGET_DIR={} manually",
+                CARGO_CONFIG,
+                target_dir.display()
+            ));
+        }
+
+        if let Some(parent) = config_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(
+            &config_path,
+            format!(
+                "# Generated by kayfabe\n[build]\ntarget-dir = {}\n",
+                toml::Value::String(target_dir.to_string_lossy().to_string())
+            ),
+        )?;
+        repo.add_exclude(&format!("/{}", CARGO_CONFIG))?;
+
+        Ok(format!("cargo target dir → {}", target_dir.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn config(strategy: CargoTargetStrategy) -> BuildCacheConfig {
+        BuildCacheConfig {
+            cargo_target_dir: Some(strategy),
+            ..BuildCacheConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_cargo_target_dir() {
+        let root = Path::new("/repo");
+        assert_eq!(
+            BuildCache::cargo_target_dir(root, "a", &config(CargoTargetStrategy::PerWorktree)),
+            Some(PathBuf::from("/repo/.kayfabe/cache/target/a"))
+        );
+        let shared = BuildCacheConfig {
+            target_pool: Some(PathBuf::from("pool")),
+            ..config(CargoTargetStrategy::Shared)
+        };
+        assert_eq!(
+            BuildCache::cargo_target_dir(root, "a", &shared),
+            Some(PathBuf::from("/repo/pool/shared"))
+        );
+        assert_eq!(
+            BuildCache::cargo_target_dir(root, "a", &BuildCacheConfig::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_apply_and_remove_per_worktree_target() {
+        let temp_dir = TempDir::new().unwrap();
+        let main = temp_dir.path().join("main");
+        let wt = temp_dir.path().join("wt").join("a");
+        std::fs::create_dir_all(&main).unwrap();
+        std::fs::create_dir_all(&wt).unwrap();
+        git2::Repository::init(&main).unwrap();
+        let repo = GitRepo::discover(&main).unwrap();
+
+        // Quotes and backslashes must survive into the TOML
+        let per_worktree = BuildCacheConfig {
+            target_pool: Some(PathBuf::from("pool \"x\" \\y")),
+            ..config(CargoTargetStrategy::PerWorktree)
+        };
+        BuildCache::apply(&repo, &wt, "a", &per_worktree).unwrap();
+        let target_dir =
+            BuildCache::cargo_target_dir(repo.layout_root(), "a", &per_worktree).unwrap();
+        assert!(target_dir.is_dir());
+
+        let written = std::fs::read_to_string(wt.join(CARGO_CONFIG)).unwrap();
+        let parsed: toml::Table = toml::from_str(&written).unwrap();
+        assert_eq!(
+            parsed["build"]["target-dir"].as_str(),
+            Some(target_dir.to_string_lossy().as_ref())
+        );
+
+        // A shared target dir outlives any one worktree
+        BuildCache::remove(
+            repo.layout_root(),
+            "a",
+            &config(CargoTargetStrategy::Shared),
+        )
+        .unwrap();
+        assert!(target_dir.is_dir());
+        BuildCache::remove(repo.layout_root(), "a", &per_worktree).unwrap();
+        assert!(!target_dir.exists());
+    }
+}
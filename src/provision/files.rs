@@ -0,0 +1,238 @@
+use super::{copy_tree, symlink};
+use crate::config::{ConflictPolicy, LocalFilesConfig, SeedMode};
+use crate::error::{KayfabeError, Result};
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Copy,
+    Link,
+}
+
+enum Action {
+    Created,
+    Skipped,
+    Overwrote,
+    BackedUp,
+}
+
+pub struct LocalFiles;
+
+impl LocalFiles {
+    /// Copy and link the configured untracked files from `anchor` into
+    /// `wt_path`. Files that are already up to date are left alone; other
+    /// existing files follow each section's `on_conflict`, unless `overwrite`
+    /// forces replacement. Returns a line per action taken.
+    pub fn sync(
+        anchor: &Path,
+        wt_path: &Path,
+        copy: &LocalFilesConfig,
+        link: &LocalFilesConfig,
+        overwrite: bool,
+    ) -> Result<Vec<String>> {
+        let mut report = Vec::new();
+
+        for (config, mode) in [(copy, Mode::Copy), (link, Mode::Link)] {
+            let on_conflict = if overwrite {
+                ConflictPolicy::Overwrite
+            } else {
+                config.on_conflict
+            };
+
+            for pattern in &config.files {
+                let matches = Self::resolve(anchor, pattern)?;
+                if matches.is_empty() {
+                    report.push(format!("{}: no matches in anchor", pattern));
+                }
+
+                for relative in matches {
+                    let source = anchor.join(&relative);
+                    let dest = wt_path.join(&relative);
+                    let shown = relative.display();
+                    report.push(match Self::sync_one(&source, &dest, mode, on_conflict)? {
+                        None => continue,
+                        Some(Action::Created) if mode == Mode::Copy => format!("copied {}", shown),
+                        Some(Action::Created) => format!("linked {}", shown),
+                        Some(Action::Skipped) => format!("skipped {} (already exists)", shown),
+                        Some(Action::Overwrote) => format!("overwrote {}", shown),
+                        Some(Action::BackedUp) => {
+                            format!("replaced {} (previous copy kept as .kayfabe-bak)", shown)
+                        }
+                    });
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Paths under `anchor` matching `pattern`, relative to `anchor`. The
+    /// pattern must stay inside the anchor, whose own path is matched
+    /// literally even if it contains glob characters.
+    fn resolve(anchor: &Path, pattern: &str) -> Result<Vec<PathBuf>> {
+        let escapes = Path::new(pattern)
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir));
+        if escapes {
+            return Err(KayfabeError::Other(format!(
+                "Invalid pattern {}: must be relative to the anchor without `..`",
+                pattern
+            )));
+        }
+
+        let base = glob::Pattern::escape(&anchor.to_string_lossy());
+        let full = Path::new(&base).join(pattern);
+        let paths = glob::glob(&full.to_string_lossy())
+            .map_err(|e| KayfabeError::Other(format!("Invalid pattern {}: {}", pattern, e)))?;
+
+        Ok(paths
+            .filter_map(|path| path.ok())
+            .filter_map(|path| path.strip_prefix(anchor).ok().map(Path::to_path_buf))
+            .collect())
+    }
+
+    fn sync_one(
+        source: &Path,
+        dest: &Path,
+        mode: Mode,
+        on_conflict: ConflictPolicy,
+    ) -> Result<Option<Action>> {
+        let action = if dest.symlink_metadata().is_err() {
+            Action::Created
+        } else if Self::is_up_to_date(source, dest, mode) {
+            return Ok(None);
+        } else {
+            match on_conflict {
+                ConflictPolicy::Skip => return Ok(Some(Action::Skipped)),
+                ConflictPolicy::Overwrite => {
+                    Self::remove(dest)?;
+                    Action::Overwrote
+                }
+                ConflictPolicy::Backup => {
+                    let mut backup = dest.as_os_str().to_owned();
+                    backup.push(".kayfabe-bak");
+                    std::fs::rename(dest, backup)?;
+                    Action::BackedUp
+                }
+            }
+        };
+
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        match mode {
+            Mode::Copy if source.is_dir() => copy_tree(source, dest, SeedMode::Reflink)?,
+            Mode::Copy => {
+                std::fs::copy(source, dest)?;
+            }
+            Mode::Link => symlink(source, dest)?,
+        }
+
+        Ok(Some(action))
+    }
+
+    fn is_up_to_date(source: &Path, dest: &Path, mode: Mode) -> bool {
+        match mode {
+            Mode::Link => std::fs::read_link(dest).is_ok_and(|target| target == source),
+            Mode::Copy if source.is_file() && dest.is_file() => {
+                match (std::fs::read(source), std::fs::read(dest)) {
+                    (Ok(a), Ok(b)) => a == b,
+                    _ => false,
+                }
+            }
+            Mode::Copy => false,
+        }
+    }
+
+    fn remove(path: &Path) -> Result<()> {
+        let metadata = path.symlink_metadata()?;
+        if metadata.is_dir() {
+            std::fs::remove_dir_all(path)?;
+        } else {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn files(patterns: &[&str], on_conflict: ConflictPolicy) -> LocalFilesConfig {
+        LocalFilesConfig {
+            files: patterns.iter().map(|p| p.to_string()).collect(),
+            on_conflict,
+        }
+    }
+
+    #[test]
+    fn test_sync_copies_and_links() {
+        let temp_dir = TempDir::new().unwrap();
+        let anchor = temp_dir.path().join("main");
+        let wt = temp_dir.path().join("wt");
+        fs::create_dir_all(anchor.join("secrets")).unwrap();
+        fs::create_dir_all(&wt).unwrap();
+        fs::write(anchor.join(".env"), "A=1").unwrap();
+        fs::write(anchor.join("secrets").join("key.pem"), "key").unwrap();
+
+        let copy = files(&[".env"], ConflictPolicy::Skip);
+        let link = files(&["secrets/*.pem"], ConflictPolicy::Skip);
+        let report = LocalFiles::sync(&anchor, &wt, &copy, &link, false).unwrap();
+
+        assert_eq!(report, vec!["copied .env", "linked secrets/key.pem"]);
+        assert_eq!(fs::read_to_string(wt.join(".env")).unwrap(), "A=1");
+        assert_eq!(
+            fs::read_link(wt.join("secrets").join("key.pem")).unwrap(),
+            anchor.join("secrets").join("key.pem")
+        );
+
+        // A second sync has nothing to do
+        let report = LocalFiles::sync(&anchor, &wt, &copy, &link, false).unwrap();
+        assert!(report.is_empty());
+    }
+
+    #[test]
+    fn test_sync_conflict_policies() {
+        let temp_dir = TempDir::new().unwrap();
+        let anchor = temp_dir.path().join("main");
+        let wt = temp_dir.path().join("wt");
+        fs::create_dir_all(&anchor).unwrap();
+        fs::create_dir_all(&wt).unwrap();
+        fs::write(anchor.join(".env"), "A=1").unwrap();
+        fs::write(wt.join(".env"), "A=2").unwrap();
+
+        let none = LocalFilesConfig::default();
+        let skip = files(&[".env"], ConflictPolicy::Skip);
+        let report = LocalFiles::sync(&anchor, &wt, &skip, &none, false).unwrap();
+        assert_eq!(report, vec!["skipped .env (already exists)"]);
+        assert_eq!(fs::read_to_string(wt.join(".env")).unwrap(), "A=2");
+
+        let backup = files(&[".env"], ConflictPolicy::Backup);
+        LocalFiles::sync(&anchor, &wt, &backup, &none, false).unwrap();
+        assert_eq!(fs::read_to_string(wt.join(".env")).unwrap(), "A=1");
+        assert_eq!(
+            fs::read_to_string(wt.join(".env.kayfabe-bak")).unwrap(),
+            "A=2"
+        );
+    }
+
+    #[test]
+    fn test_resolve_stays_in_anchor() {
+        let temp_dir = TempDir::new().unwrap();
+        let anchor = temp_dir.path().join("ma[in]");
+        fs::create_dir_all(&anchor).unwrap();
+        fs::write(anchor.join(".env"), "A=1").unwrap();
+        fs::write(temp_dir.path().join("secret"), "B=2").unwrap();
+
+        assert_eq!(
+            LocalFiles::resolve(&anchor, ".env").unwrap(),
+            vec![PathBuf::from(".env")]
+        );
+        assert!(LocalFiles::resolve(&anchor, "../secret").is_err());
+        assert!(LocalFiles::resolve(&anchor, "/etc/passwd").is_err());
+    }
+}
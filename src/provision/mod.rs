@@ -0,0 +1,112 @@
+pub mod cache;
+pub mod files;
+pub mod resources;
+
+pub use cache::BuildCache;
+pub use files::LocalFiles;
+pub use resources::Resources;
+
+use crate::config::{ProjectConfig, SeedMode, WorktreeMetadata};
+use crate::error::Result;
+use crate::git::GitRepo;
+use std::path::Path;
+
+pub const ENV_FILE: &str = ".env.kayfabe";
+
+/// Environment every hook and command run in a worktree should see
+pub fn worktree_env(
+    layout_root: &Path,
+    name: &str,
+    config: &ProjectConfig,
+) -> Result<Vec<(String, String)>> {
+    let mut env = vec![("KAYFABE_WORKTREE".to_string(), name.to_string())];
+    env.extend(BuildCache::env(layout_root, name, &config.worktree.cache));
+    env.extend(Resources::env(&WorktreeMetadata::load(layout_root, name)?));
+    Ok(env)
+}
+
+/// Write `env` to the worktree's `.env.kayfabe`, kept out of git via `info/exclude`
+pub fn write_env_file(repo: &GitRepo, wt_path: &Path, env: &[(String, String)]) -> Result<()> {
+    let content: String = env
+        .iter()
+        .map(|(key, value)| format!("{}={}\n", key, quote_env(value)))
+        .collect();
+    std::fs::write(wt_path.join(ENV_FILE), content)?;
+    repo.add_exclude(&format!("/{}", ENV_FILE))
+}
+
+/// Single-quote a value unless it is plain, so the file can be sourced by a
+/// shell as well as read by dotenv loaders
+fn quote_env(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-./:@%+,".contains(c));
+    if plain {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+pub(crate) fn copy_tree(source: &Path, dest: &Path, mode: SeedMode) -> Result<()> {
+    std::fs::create_dir_all(dest)?;
+
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let from = entry.path();
+        let to = dest.join(entry.file_name());
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+            copy_tree(&from, &to, mode)?;
+        } else if file_type.is_symlink() {
+            symlink(&std::fs::read_link(&from)?, &to)?;
+        } else {
+            match mode {
+                // std::fs::copy clones on APFS and uses copy_file_range on Linux,
+                // which reflinks on btrfs/XFS
+                SeedMode::Reflink => {
+                    std::fs::copy(&from, &to)?;
+                }
+                SeedMode::Hardlink => {
+                    if std::fs::hard_link(&from, &to).is_err() {
+                        std::fs::copy(&from, &to)?;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+pub(crate) fn symlink(source: &Path, dest: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(source, dest)?;
+    Ok(())
+}
+
+#[cfg(windows)]
+pub(crate) fn symlink(source: &Path, dest: &Path) -> Result<()> {
+    if source.is_dir() {
+        std::os::windows::fs::symlink_dir(source, dest)?;
+    } else {
+        std::os::windows::fs::symlink_file(source, dest)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_env() {
+        assert_eq!(quote_env("/tmp/wt/feat"), "/tmp/wt/feat");
+        assert_eq!(quote_env("4000"), "4000");
+        assert_eq!(quote_env(""), "''");
+        assert_eq!(quote_env("my dir/$HOME"), "'my dir/$HOME'");
+        assert_eq!(quote_env("it's"), "'it'\\''s'");
+    }
+}
@@ -0,0 +1,174 @@
+use crate::config::{PortLease, PortsConfig, ResourcesConfig, WorktreeMetadata};
+use crate::error::{KayfabeError, Result};
+use std::collections::BTreeSet;
+use std::net::TcpListener;
+use std::path::Path;
+
+pub struct Resources;
+
+impl Resources {
+    /// Lease the lowest free slot to `name` and record it in the worktree's
+    /// metadata. An existing lease is kept, so this is safe to call again.
+    pub fn allocate(
+        layout_root: &Path,
+        name: &str,
+        config: &ResourcesConfig,
+    ) -> Result<WorktreeMetadata> {
+        let mut metadata = WorktreeMetadata::load(layout_root, name)?;
+        if config.is_empty() || metadata.slot.is_some() {
+            return Ok(metadata);
+        }
+
+        let taken: BTreeSet<u32> = WorktreeMetadata::list(layout_root)?
+            .into_iter()
+            .filter(|other| other.name != name)
+            .filter_map(|other| other.slot)
+            .collect();
+
+        let max_slots = config
+            .ports
+            .as_ref()
+            .map_or(u32::MAX, |ports| ports.max_slots);
+        let mut free = None;
+        for slot in (0..max_slots).filter(|slot| !taken.contains(slot)) {
+            if let Some(ports) = &config.ports {
+                if !Self::ports_free(Self::port_lease(ports, slot)?) {
+                    continue;
+                }
+            }
+            free = Some(slot);
+            break;
+        }
+        let slot = free.ok_or_else(|| {
+            KayfabeError::Other(format!(
+                "No free resource slot (all {} leased or in use)",
+                max_slots
+            ))
+        })?;
+
+        metadata.slot = Some(slot);
+        metadata.ports = config
+            .ports
+            .as_ref()
+            .map(|ports| Self::port_lease(ports, slot))
+            .transpose()?;
+        metadata.resources = config
+            .named
+            .iter()
+            .map(|(key, template)| (key.clone(), Self::render(template, name, slot)))
+            .collect();
+        metadata.save(layout_root)?;
+
+        Ok(metadata)
+    }
+
+    pub fn env(metadata: &WorktreeMetadata) -> Vec<(String, String)> {
+        let mut env = Vec::new();
+
+        if let Some(slot) = metadata.slot {
+            env.push(("KAYFABE_SLOT".to_string(), slot.to_string()));
+        }
+        if let Some(ports) = metadata.ports {
+            env.push(("PORT".to_string(), ports.start.to_string()));
+            env.push(("KAYFABE_PORT_START".to_string(), ports.start.to_string()));
+            env.push(("KAYFABE_PORT_END".to_string(), ports.end.to_string()));
+        }
+        for (key, value) in &metadata.resources {
+            env.push((key.clone(), value.clone()));
+        }
+
+        env
+    }
+
+    /// The port block for `slot`, or an error if it would run past 65535
+    fn port_lease(ports: &PortsConfig, slot: u32) -> Result<PortLease> {
+        let block_size = ports.block_size as u64;
+        let start = ports.base as u64 + slot as u64 * block_size;
+        if start + block_size > 65536 {
+            return Err(KayfabeError::Other(format!(
+                "Ports for slot {} would run past 65535; lower [resources.ports] base, block_size or max_slots",
+                slot
+            )));
+        }
+        Ok(PortLease {
+            start: start as u16,
+            end: (start + block_size.saturating_sub(1)) as u16,
+        })
+    }
+
+    /// Skip blocks where something outside kayfabe is already listening
+    fn ports_free(lease: PortLease) -> bool {
+        lease.end >= lease.start
+            && (lease.start..=lease.end).all(|port| TcpListener::bind(("127.0.0.1", port)).is_ok())
+    }
+
+    fn render(template: &str, name: &str, slot: u32) -> String {
+        let sanitized: String = name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        template
+            .replace("{worktree}", &sanitized)
+            .replace("{slot}", &slot.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use tempfile::TempDir;
+
+    fn config() -> ResourcesConfig {
+        let mut named = BTreeMap::new();
+        named.insert("DATABASE_NAME".to_string(), "app_{worktree}".to_string());
+        ResourcesConfig {
+            ports: Some(PortsConfig {
+                base: 41000,
+                block_size: 5,
+                max_slots: 10,
+            }),
+            named,
+        }
+    }
+
+    #[test]
+    fn test_allocate_non_overlapping_slots() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        let a = Resources::allocate(root, "feature-a", &config()).unwrap();
+        let b = Resources::allocate(root, "feature-b", &config()).unwrap();
+
+        assert_ne!(a.slot, b.slot);
+        let (a, b) = (a.ports.unwrap(), b.ports.unwrap());
+        assert!(a.end < b.start || b.end < a.start);
+    }
+
+    #[test]
+    fn test_allocate_is_idempotent() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        let first = Resources::allocate(root, "feature-a", &config()).unwrap();
+        let again = Resources::allocate(root, "feature-a", &config()).unwrap();
+        assert_eq!(first.slot, again.slot);
+        assert_eq!(
+            again.resources.get("DATABASE_NAME").map(String::as_str),
+            Some("app_feature_a")
+        );
+    }
+
+    #[test]
+    fn test_port_lease_past_last_port_is_an_error() {
+        let ports = PortsConfig {
+            base: 65516,
+            block_size: 10,
+            max_slots: 10,
+        };
+
+        let last = Resources::port_lease(&ports, 1).unwrap();
+        assert_eq!((last.start, last.end), (65526, 65535));
+        assert!(Resources::port_lease(&ports, 2).is_err());
+    }
+}
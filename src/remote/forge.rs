@@ -0,0 +1,256 @@
+use crate::error::{KayfabeError, Result};
+use serde::Deserialize;
+
+const DEFAULT_API_URL: &str = "https://api.github.com";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrState {
+    Open,
+    Merged,
+    Closed,
+}
+
+impl PrState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PrState::Open => "PR open",
+            PrState::Merged => "PR merged",
+            PrState::Closed => "PR closed",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PullRequest {
+    pub number: u64,
+    pub state: PrState,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RemoteStatus {
+    pub pull_request: Option<PullRequest>,
+    pub branch_deleted: bool,
+}
+
+impl RemoteStatus {
+    pub fn pr_state(&self) -> Option<PrState> {
+        self.pull_request.as_ref().map(|pr| pr.state)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiPullRequest {
+    number: u64,
+    state: String,
+    merged_at: Option<String>,
+    html_url: String,
+}
+
+/// Queries the forge (GitHub or GitHub Enterprise) hosting `origin` for
+/// pull request and branch state.
+pub struct ForgeClient {
+    runtime: tokio::runtime::Runtime,
+    http: reqwest::Client,
+    api_url: String,
+    owner: String,
+    repo: String,
+    token: Option<String>,
+}
+
+impl ForgeClient {
+    pub fn from_remote_url(url: &str) -> Result<Self> {
+        let (owner, repo) = Self::parse_remote_url(url)
+            .ok_or_else(|| KayfabeError::Other(format!("Unsupported remote URL: {}", url)))?;
+
+        let api_url =
+            std::env::var("KAYFABE_GITHUB_API_URL").unwrap_or_else(|_| DEFAULT_API_URL.to_string());
+        let token = std::env::var("GITHUB_TOKEN")
+            .or_else(|_| std::env::var("GH_TOKEN"))
+            .ok();
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()?;
+        let http = reqwest::Client::builder()
+            .user_agent(concat!("kayfabe/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| KayfabeError::Other(format!("Failed to build HTTP client: {}", e)))?;
+
+        Ok(Self {
+            runtime,
+            http,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            owner,
+            repo,
+            token,
+        })
+    }
+
+    /// Extract `(owner, repo)` from an SSH or HTTPS remote URL.
+    pub fn parse_remote_url(url: &str) -> Option<(String, String)> {
+        let url = url.trim().trim_end_matches('/');
+        let path = if let Some(rest) = url.split_once("://").map(|(_, rest)| rest) {
+            rest.split_once('/')?.1
+        } else {
+            url.split_once(':')?.1
+        };
+
+        let path = path.strip_suffix(".git").unwrap_or(path);
+        let mut parts = path.rsplitn(2, '/');
+        let repo = parts.next()?;
+        let owner = parts.next()?.rsplit('/').next()?;
+
+        if owner.is_empty() || repo.is_empty() {
+            return None;
+        }
+
+        Some((owner.to_string(), repo.to_string()))
+    }
+
+    /// Look up the most recent pull request for `branch` and whether the
+    /// branch has been deleted on the remote. `had_remote_branch` tells us
+    /// whether the branch was ever pushed, so a missing branch can be told
+    /// apart from one that was never published.
+    pub fn status(&self, branch: &str, had_remote_branch: bool) -> Result<RemoteStatus> {
+        self.runtime.block_on(async {
+            let pull_request = self.find_pull_request(branch).await?;
+            let branch_deleted = had_remote_branch && !self.branch_exists(branch).await?;
+
+            Ok(RemoteStatus {
+                pull_request,
+                branch_deleted,
+            })
+        })
+    }
+
+    async fn find_pull_request(&self, branch: &str) -> Result<Option<PullRequest>> {
+        let url = Self::endpoint(&self.api_url, &["repos", &self.owner, &self.repo, "pulls"])?;
+        let head = format!("{}:{}", self.owner, branch);
+        let response = self
+            .request(&url)
+            .query(&[("head", head.as_str()), ("state", "all"), ("per_page", "1")])
+            .send()
+            .await
+            .map_err(Self::http_error)?;
+
+        if !response.status().is_success() {
+            return Err(KayfabeError::Other(format!(
+                "Forge returned {} for {}",
+                response.status(),
+                url
+            )));
+        }
+
+        let body = response.text().await.map_err(Self::http_error)?;
+        let pulls: Vec<ApiPullRequest> = serde_json::from_str(&body).map_err(|e| {
+            KayfabeError::Other(format!("Failed to parse pull request response: {}", e))
+        })?;
+
+        Ok(pulls.into_iter().next().map(|pr| PullRequest {
+            number: pr.number,
+            state: if pr.merged_at.is_some() {
+                PrState::Merged
+            } else if pr.state == "open" {
+                PrState::Open
+            } else {
+                PrState::Closed
+            },
+            url: pr.html_url,
+        }))
+    }
+
+    async fn branch_exists(&self, branch: &str) -> Result<bool> {
+        let url = Self::endpoint(
+            &self.api_url,
+            &["repos", &self.owner, &self.repo, "branches", branch],
+        )?;
+        let response = self.request(&url).send().await.map_err(Self::http_error)?;
+
+        match response.status() {
+            status if status.is_success() => Ok(true),
+            reqwest::StatusCode::NOT_FOUND => Ok(false),
+            status => Err(KayfabeError::Other(format!(
+                "Forge returned {} for {}",
+                status, url
+            ))),
+        }
+    }
+
+    /// `api_url` with `segments` appended, each percent-encoded, so branch
+    /// names like `feature/x` stay one segment
+    fn endpoint(api_url: &str, segments: &[&str]) -> Result<reqwest::Url> {
+        let mut url = reqwest::Url::parse(api_url).map_err(|e| {
+            KayfabeError::Other(format!("Invalid forge API URL {}: {}", api_url, e))
+        })?;
+        url.path_segments_mut()
+            .map_err(|_| KayfabeError::Other(format!("Invalid forge API URL {}", api_url)))?
+            .pop_if_empty()
+            .extend(segments);
+        Ok(url)
+    }
+
+    fn request(&self, url: &reqwest::Url) -> reqwest::RequestBuilder {
+        let builder = self
+            .http
+            .get(url.clone())
+            .header("Accept", "application/vnd.github+json");
+        match &self.token {
+            Some(token) => builder.bearer_auth(token),
+            None => builder,
+        }
+    }
+
+    fn http_error(e: reqwest::Error) -> KayfabeError {
+        KayfabeError::Other(format!("Forge request failed: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ssh_remote_url() {
+        assert_eq!(
+            ForgeClient::parse_remote_url("git@github.com:acme/widgets.git"),
+            Some(("acme".to_string(), "widgets".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_https_remote_url() {
+        assert_eq!(
+            ForgeClient::parse_remote_url("https://github.com/acme/widgets"),
+            Some(("acme".to_string(), "widgets".to_string()))
+        );
+        assert_eq!(
+            ForgeClient::parse_remote_url("ssh://git@github.example.com/acme/widgets.git/"),
+            Some(("acme".to_string(), "widgets".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_endpoint_encodes_branch() {
+        let url = ForgeClient::endpoint(
+            "https://github.example.com/api/v3",
+            &["repos", "acme", "widgets", "branches", "feature/x#1"],
+        )
+        .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://github.example.com/api/v3/repos/acme/widgets/branches/feature%2Fx%231"
+        );
+        assert_eq!(
+            ForgeClient::endpoint(DEFAULT_API_URL, &["repos", "acme", "widgets", "pulls"])
+                .unwrap()
+                .as_str(),
+            "https://api.github.com/repos/acme/widgets/pulls"
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_remote_url() {
+        assert_eq!(ForgeClient::parse_remote_url("/srv/git/widgets.git"), None);
+    }
+}
@@ -0,0 +1,3 @@
+pub mod forge;
+
+pub use forge::{ForgeClient, PrState, PullRequest, RemoteStatus};
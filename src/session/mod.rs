@@ -0,0 +1,3 @@
+pub mod multiplexer;
+
+pub use multiplexer::SessionManager;
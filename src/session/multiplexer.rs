@@ -0,0 +1,396 @@
+use crate::config::{Multiplexer, SessionConfig};
+use crate::error::{KayfabeError, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// tmux user option set on sessions kayfabe creates, holding the layout root
+const TAG_OPTION: &str = "@kayfabe";
+
+/// Creates, attaches to and kills the multiplexer session that belongs to a
+/// worktree. Sessions are named from `[session] name`, and only sessions
+/// kayfabe created for this project are ever killed.
+pub struct SessionManager {
+    config: SessionConfig,
+    project: String,
+    layout_root: PathBuf,
+}
+
+impl SessionManager {
+    pub fn new(config: &SessionConfig, layout_root: &Path) -> Self {
+        let project = layout_root
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        Self {
+            config: config.clone(),
+            project,
+            layout_root: layout_root.to_path_buf(),
+        }
+    }
+
+    /// tmux treats `.` and `:` in targets as window/pane separators
+    pub fn session_name(&self, worktree: &str) -> String {
+        self.config
+            .name
+            .replace("{worktree}", worktree)
+            .replace("{project}", &self.project)
+            .chars()
+            .map(|c| if c == '.' || c == ':' { '_' } else { c })
+            .collect()
+    }
+
+    pub fn exists(&self, worktree: &str) -> bool {
+        let name = self.session_name(worktree);
+        match self.config.multiplexer {
+            Multiplexer::Tmux => self
+                .tmux()
+                .args(["has-session", "-t", &format!("={}", name)])
+                .output()
+                .is_ok_and(|output| output.status.success()),
+            Multiplexer::Zellij => self.running().is_ok_and(|names| names.contains(&name)),
+        }
+    }
+
+    /// Names of all running sessions on this multiplexer
+    pub fn running(&self) -> Result<Vec<String>> {
+        let output = match self.config.multiplexer {
+            Multiplexer::Tmux => self
+                .tmux()
+                .args(["list-sessions", "-F", "#{session_name}"])
+                .output(),
+            Multiplexer::Zellij => Command::new("zellij")
+                .args(["list-sessions", "--short", "--no-formatting"])
+                .output(),
+        };
+
+        // No server running (or no multiplexer installed) means no sessions
+        Ok(match output {
+            Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(str::to_string)
+                .collect(),
+            _ => Vec::new(),
+        })
+    }
+
+    /// Start a detached session with the configured panes. Returns `false` if
+    /// it was already running.
+    pub fn start(&self, worktree: &str, path: &Path, env: &[(String, String)]) -> Result<bool> {
+        if self.exists(worktree) {
+            return Ok(false);
+        }
+
+        match self.config.multiplexer {
+            Multiplexer::Tmux => self.start_tmux(worktree, path, env)?,
+            Multiplexer::Zellij => {
+                return Err(KayfabeError::Other(
+                    "zellij sessions can't be started detached; use `kayfabe session attach`"
+                        .to_string(),
+                ))
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Attach to the worktree's session, starting it first if needed
+    pub fn attach(&self, worktree: &str, path: &Path, env: &[(String, String)]) -> Result<()> {
+        let name = self.session_name(worktree);
+
+        let status = match self.config.multiplexer {
+            Multiplexer::Tmux => {
+                self.start(worktree, path, env)?;
+                // Inside tmux, attaching would nest; switch the client instead
+                let inside_tmux =
+                    std::env::var_os("TMUX").is_some() && self.config.socket.is_none();
+                let verb = if inside_tmux {
+                    "switch-client"
+                } else {
+                    "attach-session"
+                };
+                self.tmux()
+                    .args([verb, "-t", &format!("={}", name)])
+                    .status()?
+            }
+            Multiplexer::Zellij if self.exists(worktree) => {
+                Command::new("zellij").args(["attach", &name]).status()?
+            }
+            Multiplexer::Zellij => {
+                let layout = self.write_zellij_layout(&name)?;
+                Command::new("zellij")
+                    .arg("--session")
+                    .arg(&name)
+                    .arg("--new-session-with-layout")
+                    .arg(layout)
+                    .current_dir(path)
+                    .envs(env.iter().cloned())
+                    .status()?
+            }
+        };
+
+        if !status.success() {
+            return Err(KayfabeError::Other(format!(
+                "Failed to attach to session {}",
+                name
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Whether the worktree's session was created by kayfabe for this
+    /// project, rather than being someone else's session with the same name.
+    /// tmux sessions carry a tag; zellij sessions have a layout file here.
+    pub fn is_ours(&self, worktree: &str) -> bool {
+        let name = self.session_name(worktree);
+        match self.config.multiplexer {
+            Multiplexer::Tmux => self
+                .tmux()
+                .args([
+                    "show-options",
+                    "-v",
+                    "-t",
+                    &format!("={}:", name),
+                    TAG_OPTION,
+                ])
+                .output()
+                .is_ok_and(|output| {
+                    output.status.success()
+                        && String::from_utf8_lossy(&output.stdout).trim() == self.tag()
+                }),
+            Multiplexer::Zellij => self.zellij_layout_path(&name).is_file(),
+        }
+    }
+
+    /// Kill the worktree's session. Returns `false` if none was running, or
+    /// the session with that name isn't kayfabe's.
+    pub fn kill(&self, worktree: &str) -> Result<bool> {
+        if !self.exists(worktree) || !self.is_ours(worktree) {
+            return Ok(false);
+        }
+
+        let name = self.session_name(worktree);
+        match self.config.multiplexer {
+            Multiplexer::Tmux => {
+                self.tmux()
+                    .args(["kill-session", "-t", &format!("={}", name)])
+                    .output()?;
+            }
+            Multiplexer::Zellij => {
+                Command::new("zellij")
+                    .args(["kill-session", &name])
+                    .output()?;
+                Command::new("zellij")
+                    .args(["delete-session", &name])
+                    .output()?;
+                std::fs::remove_file(self.zellij_layout_path(&name))?;
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn tag(&self) -> String {
+        self.layout_root.to_string_lossy().to_string()
+    }
+
+    fn tmux(&self) -> Command {
+        let mut command = Command::new("tmux");
+        if let Some(socket) = &self.config.socket {
+            command.args(["-L", socket]);
+        }
+        command
+    }
+
+    fn start_tmux(&self, worktree: &str, path: &Path, env: &[(String, String)]) -> Result<()> {
+        let name = self.session_name(worktree);
+        let env_args: Vec<String> = env
+            .iter()
+            .flat_map(|(key, value)| ["-e".to_string(), format!("{}={}", key, value)])
+            .collect();
+
+        // Detached sessions default to 80x24, which is too small to split into
+        // several panes, so start larger; attaching resizes to the client.
+        let first = self.tmux_output(
+            self.tmux()
+                .args(["new-session", "-d", "-P", "-F", "#{pane_id}"])
+                .args(["-s", &name, "-x", "200", "-y", "50", "-c"])
+                .arg(path)
+                .args(&env_args),
+        )?;
+        self.tmux_output(self.tmux().args([
+            "set-option",
+            "-t",
+            &format!("={}:", name),
+            TAG_OPTION,
+            &self.tag(),
+        ]))?;
+
+        let pane_count = self.config.panes.len().max(1);
+        let mut pane_ids = vec![first];
+        for _ in 1..pane_count {
+            pane_ids.push(
+                self.tmux_output(
+                    self.tmux()
+                        .args(["split-window", "-P", "-F", "#{pane_id}"])
+                        .args(["-t", &format!("={}:", name), "-c"])
+                        .arg(path)
+                        .args(&env_args),
+                )?,
+            );
+            // Re-tile after every split so the next one has room
+            self.tmux_output(
+                self.tmux()
+                    .args(["select-layout", "-t", &pane_ids[0], "tiled"]),
+            )?;
+        }
+
+        if let Some(layout) = &self.config.layout {
+            self.tmux_output(
+                self.tmux()
+                    .args(["select-layout", "-t", &pane_ids[0], layout]),
+            )?;
+        }
+
+        for (pane_id, pane) in pane_ids.iter().zip(&self.config.panes) {
+            if let Some(title) = &pane.name {
+                self.tmux_output(
+                    self.tmux()
+                        .args(["select-pane", "-t", pane_id, "-T", title]),
+                )?;
+            }
+            if let Some(command) = &pane.command {
+                self.tmux_output(
+                    self.tmux()
+                        .args(["send-keys", "-t", pane_id, command, "Enter"]),
+                )?;
+            }
+        }
+        self.tmux_output(self.tmux().args(["select-pane", "-t", &pane_ids[0]]))?;
+
+        Ok(())
+    }
+
+    fn tmux_output(&self, command: &mut Command) -> Result<String> {
+        let output = command.output()?;
+        if !output.status.success() {
+            return Err(KayfabeError::Other(format!(
+                "tmux failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// zellij takes pane layouts as KDL files, kept under `.kayfabe/sessions`
+    fn write_zellij_layout(&self, name: &str) -> Result<PathBuf> {
+        let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+
+        let mut kdl = String::from("layout {\n");
+        for pane in &self.config.panes {
+            kdl.push_str("    pane");
+            if let Some(title) = &pane.name {
+                kdl.push_str(&format!(" name={}", quote(title)));
+            }
+            match &pane.command {
+                Some(command) => kdl.push_str(&format!(
+                    " command=\"sh\" {{\n        args \"-c\" {}\n    }}\n",
+                    quote(&format!("{}; exec \"${{SHELL:-sh}}\"", command))
+                )),
+                None => kdl.push('\n'),
+            }
+        }
+        if self.config.panes.is_empty() {
+            kdl.push_str("    pane\n");
+        }
+        kdl.push_str("}\n");
+
+        let file = self.zellij_layout_path(name);
+        if let Some(dir) = file.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&file, kdl)?;
+
+        Ok(file)
+    }
+
+    fn zellij_layout_path(&self, name: &str) -> PathBuf {
+        self.layout_root
+            .join(".kayfabe")
+            .join("sessions")
+            .join(format!("{}.kdl", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PaneConfig;
+    use tempfile::TempDir;
+
+    fn has_tmux() -> bool {
+        Command::new("tmux")
+            .arg("-V")
+            .output()
+            .is_ok_and(|output| output.status.success())
+    }
+
+    #[test]
+    fn test_tmux_session_lifecycle() {
+        if !has_tmux() {
+            return;
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let socket = format!("kayfabe-test-{}", std::process::id());
+        let config = SessionConfig {
+            socket: Some(socket.clone()),
+            panes: vec![
+                PaneConfig {
+                    name: Some("agent".to_string()),
+                    command: Some("echo agent".to_string()),
+                },
+                PaneConfig::default(),
+                PaneConfig::default(),
+            ],
+            ..SessionConfig::default()
+        };
+        let layout_root = temp_dir.path().join("proj");
+        std::fs::create_dir(&layout_root).unwrap();
+        let manager = SessionManager::new(&config, &layout_root);
+        let env = vec![("PORT".to_string(), "4010".to_string())];
+
+        assert!(manager.start("feature.x", temp_dir.path(), &env).unwrap());
+        assert!(manager.exists("feature.x"));
+        assert!(manager.is_ours("feature.x"));
+        assert!(!manager.start("feature.x", temp_dir.path(), &env).unwrap());
+        assert_eq!(manager.running().unwrap(), vec!["proj-feature_x"]);
+
+        let panes = Command::new("tmux")
+            .args(["-L", &socket, "list-panes", "-t", "=proj-feature_x:"])
+            .args(["-F", "#{pane_title}"])
+            .output()
+            .unwrap();
+        let titles = String::from_utf8_lossy(&panes.stdout);
+        assert_eq!(titles.lines().count(), 3);
+        assert!(titles.lines().any(|title| title == "agent"));
+
+        assert!(manager.kill("feature.x").unwrap());
+        assert!(!manager.exists("feature.x"));
+        assert!(!manager.kill("feature.x").unwrap());
+
+        // The user's own session with the same name is left alone
+        Command::new("tmux")
+            .args(["-L", &socket, "new-session", "-d", "-s", "proj-other"])
+            .output()
+            .unwrap();
+        assert!(manager.exists("other"));
+        assert!(!manager.kill("other").unwrap());
+        assert!(manager.exists("other"));
+
+        let _ = Command::new("tmux")
+            .args(["-L", &socket, "kill-server"])
+            .output();
+    }
+}
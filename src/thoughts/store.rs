@@ -0,0 +1,196 @@
+use crate::error::Result;
+use crate::git::GitRepo;
+use crate::provision::symlink;
+use std::path::{Path, PathBuf};
+
+/// Name of the store at the layout root, and of its link in each worktree
+pub const DIR: &str = "thoughts";
+
+/// Subdirectories of `shared/` the bundled prompts expect
+const SHARED_DIRS: &[&str] = &["research", "plans", "tickets", "prs", "handoffs"];
+
+/// Lines shown per matching file
+const MAX_LINES_PER_HIT: usize = 3;
+
+/// A file in the store that matched a search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit {
+    /// Relative to the store root
+    pub path: PathBuf,
+    /// (line number, line) for lines containing any of the terms
+    pub lines: Vec<(usize, String)>,
+}
+
+/// The `thoughts/` directory of research, plans, tickets and handoffs. It
+/// lives once at the layout root and is symlinked into every worktree so
+/// notes written in one checkout are visible in all of them.
+pub struct Thoughts {
+    root: PathBuf,
+}
+
+impl Thoughts {
+    pub fn new(layout_root: &Path) -> Self {
+        Self {
+            root: layout_root.join(DIR),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// `shared/<kind>`, e.g. `shared/plans`
+    pub fn shared(&self, kind: &str) -> PathBuf {
+        self.root.join("shared").join(kind)
+    }
+
+    /// Create the store with its standard subdirectories. Returns `false` if
+    /// it already existed.
+    pub fn init(&self) -> Result<bool> {
+        let created = !self.root.exists();
+        for kind in SHARED_DIRS {
+            std::fs::create_dir_all(self.shared(kind))?;
+        }
+        Ok(created)
+    }
+
+    /// Symlink the store into `wt_path` and hide the link via `info/exclude`.
+    /// A `thoughts` path the checkout already has is left alone. Returns a
+    /// line describing what was done, if anything.
+    pub fn link(&self, repo: &GitRepo, wt_path: &Path) -> Result<Option<String>> {
+        let dest = wt_path.join(DIR);
+        if let Ok(target) = std::fs::read_link(&dest) {
+            if target == self.root {
+                return Ok(None);
+            }
+        }
+        if dest.symlink_metadata().is_ok() {
+            return Ok(Some(format!("{}/ already exists, not linked", DIR)));
+        }
+
+        symlink(&self.root, &dest)?;
+        repo.add_exclude(&format!("/{}", DIR))?;
+        Ok(Some(format!("linked {}/", DIR)))
+    }
+
+    /// Files whose path or contents contain every term, case-insensitively
+    pub fn search(&self, terms: &[String]) -> Result<Vec<SearchHit>> {
+        let terms: Vec<String> = terms.iter().map(|term| term.to_lowercase()).collect();
+        let mut files = Vec::new();
+        if self.root.is_dir() {
+            Self::walk(&self.root, &mut files)?;
+        }
+        files.sort();
+
+        let mut hits = Vec::new();
+        for file in files {
+            // Skip anything that isn't text
+            let Ok(content) = std::fs::read_to_string(&file) else {
+                continue;
+            };
+            let relative = file.strip_prefix(&self.root).unwrap_or(&file).to_path_buf();
+            let haystack = format!("{}\n{}", relative.display(), content).to_lowercase();
+            if !terms.iter().all(|term| haystack.contains(term)) {
+                continue;
+            }
+
+            let lines = content
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| {
+                    let line = line.to_lowercase();
+                    terms.iter().any(|term| line.contains(term))
+                })
+                .take(MAX_LINES_PER_HIT)
+                .map(|(i, line)| (i + 1, line.trim().to_string()))
+                .collect();
+            hits.push(SearchHit {
+                path: relative,
+                lines,
+            });
+        }
+
+        Ok(hits)
+    }
+
+    /// Collect regular files, not following symlinks
+    fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                Self::walk(&entry.path(), files)?;
+            } else if file_type.is_file() {
+                files.push(entry.path());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Kebab-case file name component for a title
+pub fn slugify(title: &str) -> String {
+    let slug = title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let slug: String = slug.chars().take(60).collect();
+    let slug = slug.trim_end_matches('-');
+
+    if slug.is_empty() {
+        "untitled".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_search_requires_all_terms() {
+        let temp_dir = TempDir::new().unwrap();
+        let thoughts = Thoughts::new(temp_dir.path());
+        assert!(thoughts.init().unwrap());
+        assert!(!thoughts.init().unwrap());
+
+        std::fs::write(
+            thoughts.shared("research").join("rate-limiting.md"),
+            "# Rate limiting\n\nToken bucket per API key.\nSliding window was slower.\n",
+        )
+        .unwrap();
+        std::fs::write(
+            thoughts.shared("plans").join("auth.md"),
+            "# Auth plan\n\nRotate the API key on login.\n",
+        )
+        .unwrap();
+
+        let hits = thoughts.search(&["api".to_string()]).unwrap();
+        assert_eq!(hits.len(), 2);
+
+        // "rate" matches the file name, "key" its contents
+        let hits = thoughts
+            .search(&["RATE".to_string(), "key".to_string()])
+            .unwrap();
+        assert_eq!(
+            hits,
+            vec![SearchHit {
+                path: PathBuf::from("shared/research/rate-limiting.md"),
+                lines: vec![
+                    (1, "# Rate limiting".to_string()),
+                    (3, "Token bucket per API key.".to_string()),
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Fix: the Login bug!"), "fix-the-login-bug");
+        assert_eq!(slugify("???"), "untitled");
+    }
+}
@@ -1,4 +1,5 @@
 use console::style;
+use similar::{ChangeTag, TextDiff};
 
 pub struct Output;
 
@@ -34,4 +35,23 @@ impl Output {
     pub fn section(title: &str) {
         println!("\n{}", style(title).bold());
     }
+
+    /// Colored unified diff of `old` against `new`
+    pub fn diff(old: &str, new: &str, old_label: &str, new_label: &str) {
+        println!("{}", style(format!("--- {}", old_label)).bold());
+        println!("{}", style(format!("+++ {}", new_label)).bold());
+        let diff = TextDiff::from_lines(old, new);
+        for hunk in diff.unified_diff().context_radius(3).iter_hunks() {
+            println!("{}", style(hunk.header()).cyan());
+            for change in hunk.iter_changes() {
+                let line = format!("{}{}", change.tag(), change);
+                let line = line.trim_end_matches('\n');
+                match change.tag() {
+                    ChangeTag::Delete => println!("{}", style(line).red()),
+                    ChangeTag::Insert => println!("{}", style(line).green()),
+                    ChangeTag::Equal => println!("{}", line),
+                }
+            }
+        }
+    }
 }